@@ -0,0 +1,250 @@
+//! Twitter API endpoints not wrapped by `egg-mode`.
+//!
+//! Requests are signed with OAuth 1.0a the same way `egg-mode` signs its own, and errors are returned as its `Error`s, so
+//! they can be handled the same way as the ones from `egg-mode` proper.
+
+
+use hyper::header::{Authorization, ContentType};
+use rustc_serialize::base64::{self, ToBase64};
+use egg_mode::error::{Error, TwitterErrors};
+use std::time::{SystemTime, UNIX_EPOCH};
+use rustc_serialize::json::{self, Json};
+use self::super::ops::Recipient;
+use std::collections::BTreeMap;
+use hyper::status::StatusCode;
+use rand::{Rng, thread_rng};
+use crypto::hmac::Hmac;
+use crypto::sha1::Sha1;
+use crypto::mac::Mac;
+use egg_mode::Token;
+use hyper::Client;
+use std::io::Read;
+
+
+/// Percent-encode the specified string the way Twitter wants it, i.e. leaving only unreserved characters as-is.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::api::percent_encode;
+/// assert_eq!(percent_encode("Ladies + Gentlemen"), "Ladies%20%2B%20Gentlemen");
+/// assert_eq!(percent_encode("An encoded string!"), "An%20encoded%20string%21");
+/// assert_eq!(percent_encode("Dogs, Cats & Mice"), "Dogs%2C%20Cats%20%26%20Mice");
+/// assert_eq!(percent_encode("☃"), "%E2%98%83");
+/// ```
+pub fn percent_encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b if (b < 0x80 && (b as char).is_alphanumeric()) || b"-._~".contains(&b) => (b as char).to_string(),
+            b => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Calculate the OAuth 1.0a HMAC-SHA1 signature of a request with the specified parameters, OAuth ones included.
+///
+/// # Examples
+///
+/// The example from Twitter's documentation:
+///
+/// ```
+/// # use tweetr::api::signature;
+/// let params = vec![("status", "Hello Ladies + Gentlemen, a signed OAuth request!"),
+///                   ("include_entities", "true"),
+///                   ("oauth_consumer_key", "xvz1evFS4wEEPTGEFPHBog"),
+///                   ("oauth_nonce", "kYjzVBB8Y0ZFabxSWbWovY3uYSQ2pTgmZeNu2VS4cg"),
+///                   ("oauth_signature_method", "HMAC-SHA1"),
+///                   ("oauth_timestamp", "1318622958"),
+///                   ("oauth_token", "370773112-GmHxMAgYyLbNEtIKZeRNFsMKPR9EyMZeS9weJAEb"),
+///                   ("oauth_version", "1.0")];
+/// assert_eq!(signature("POST",
+///                      "https://api.twitter.com/1/statuses/update.json",
+///                      &params.into_iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+///                      "kAcSOqF21Fu85e7zjz7ZN2U4ZRhfV3WpwPAoE3Z7kBw",
+///                      "LswwdoUaIvS8ltyTt5jkRh4J50vUPVVHtR2YPi5kE"),
+///            "tnnArxj06cWHq44gCs1OSKk/jLY=");
+/// ```
+pub fn signature(method: &str, uri: &str, params: &Vec<(String, String)>, consumer_secret: &str, token_secret: &str) -> String {
+    let mut query: Vec<_> = params.iter().map(|&(ref k, ref v)| format!("{}={}", percent_encode(k), percent_encode(v))).collect();
+    query.sort();
+
+    let base = format!("{}&{}&{}", percent_encode(method), percent_encode(uri), percent_encode(&query.join("&")));
+    let key = format!("{}&{}", percent_encode(consumer_secret), percent_encode(token_secret));
+
+    let mut hmac = Hmac::new(Sha1::new(), key.as_bytes());
+    hmac.input(base.as_bytes());
+    hmac.result().code().to_base64(base64::STANDARD)
+}
+
+/// Make a signed `POST` request with the specified form parameters on behalf of the specified user, returning the response
+/// JSON.
+pub fn post(uri: &str, con_token: &Token, access_token: &Token, params: &Vec<(&str, String)>) -> Result<Json, Error> {
+    let mut oauth_params = vec![("oauth_consumer_key", con_token.key.to_string()),
+                                ("oauth_nonce", thread_rng().gen_ascii_chars().take(32).collect()),
+                                ("oauth_signature_method", "HMAC-SHA1".to_string()),
+                                ("oauth_timestamp", SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs().to_string()),
+                                ("oauth_token", access_token.key.to_string()),
+                                ("oauth_version", "1.0".to_string())];
+    let signature = signature("POST",
+                              uri,
+                              &oauth_params.iter().chain(params.iter()).map(|&(k, ref v)| (k.to_string(), v.clone())).collect(),
+                              &con_token.secret,
+                              &access_token.secret);
+    oauth_params.push(("oauth_signature", signature));
+
+    let header = format!("OAuth {}",
+                         oauth_params.iter().map(|&(k, ref v)| format!("{}=\"{}\"", k, percent_encode(v))).collect::<Vec<_>>().join(", "));
+    let body = params.iter().map(|&(k, ref v)| format!("{}={}", percent_encode(k), percent_encode(v))).collect::<Vec<_>>().join("&");
+
+    let mut resp = try!(Client::new()
+        .post(uri)
+        .body(body.as_bytes())
+        .header(Authorization(header))
+        .header(ContentType::form_url_encoded())
+        .send()
+        .map_err(Error::NetError));
+    let mut resp_str = String::new();
+    try!(resp.read_to_string(&mut resp_str).map_err(Error::IOError));
+
+    if let Ok(errs) = json::decode::<TwitterErrors>(&resp_str) {
+        return Err(Error::TwitterError(errs));
+    }
+    if resp.status != StatusCode::Ok {
+        return Err(Error::BadStatus(resp.status));
+    }
+
+    Json::from_str(&resp_str).map_err(Error::JSONError)
+}
+
+/// Update the specified parts of the user's profile, returning the user's ID.
+///
+/// # Examples
+///
+/// ```no_run
+/// # extern crate egg_mode;
+/// # extern crate tweetr;
+/// # use tweetr::api::update_profile;
+/// # use egg_mode::Token;
+/// # fn main() {
+/// let id = update_profile(Some("tweetr test account"),
+///                         None,
+///                         &Token::new("qzuqpwr101q4RtK9mDorI9ndm", "HW4YG3Kdcap5ovcZ5fZfBJFedKR6GQe9MtZDS9Gm34hXiirkU5"),
+///                         &Token::new("529443-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4", "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5"))
+///     .unwrap();
+/// assert_eq!(id, 529443);
+/// # }
+/// ```
+pub fn update_profile(name: Option<&str>, description: Option<&str>, con_token: &Token, access_token: &Token) -> Result<i64, Error> {
+    let mut params = vec![];
+    if let Some(name) = name {
+        params.push(("name", name.to_string()));
+    }
+    if let Some(description) = description {
+        params.push(("description", description.to_string()));
+    }
+
+    let resp = try!(post("https://api.twitter.com/1.1/account/update_profile.json", con_token, access_token, &params));
+    resp.find("id").and_then(Json::as_i64).ok_or(Error::MissingValue("id"))
+}
+
+/// Pin the tweet with the specified ID to the top of the user's profile.
+///
+/// This uses the endpoint Twitter's own web client does, as there isn't a public one.
+pub fn pin_tweet(id: i64, con_token: &Token, access_token: &Token) -> Result<(), Error> {
+    post("https://api.twitter.com/1.1/account/pin_tweet.json", con_token, access_token, &vec![("id", id.to_string())]).map(|_| ())
+}
+
+/// Unpin the tweet with the specified ID from the top of the user's profile.
+///
+/// This uses the endpoint Twitter's own web client does, as there isn't a public one.
+pub fn unpin_tweet(id: i64, con_token: &Token, access_token: &Token) -> Result<(), Error> {
+    post("https://api.twitter.com/1.1/account/unpin_tweet.json", con_token, access_token, &vec![("id", id.to_string())]).map(|_| ())
+}
+
+/// Send a direct message with the specified text to the specified user, returning the message's ID.
+pub fn send_direct_message(recipient: &Recipient, text: &str, con_token: &Token, access_token: &Token) -> Result<i64, Error> {
+    let params = vec![match *recipient {
+                          Recipient::Id(id) => ("user_id", id.to_string()),
+                          Recipient::Handle(ref handle) => ("screen_name", handle.clone()),
+                      },
+                      ("text", text.to_string())];
+
+    let resp = try!(post("https://api.twitter.com/1.1/direct_messages/new.json", con_token, access_token, &params));
+    resp.find("id").and_then(Json::as_i64).ok_or(Error::MissingValue("id"))
+}
+
+/// Delete the direct message with the specified ID.
+pub fn delete_direct_message(id: i64, con_token: &Token, access_token: &Token) -> Result<(), Error> {
+    post("https://api.twitter.com/1.1/direct_messages/destroy.json", con_token, access_token, &vec![("id", id.to_string())]).map(|_| ())
+}
+
+/// Create a poll card with the specified options open for the specified amount of minutes, returning its URI.
+///
+/// The poll is only visible once a tweet with the card is posted via `update_status_with_card()`.
+pub fn create_poll(options: &Vec<String>, duration_minutes: u64, con_token: &Token, access_token: &Token) -> Result<String, Error> {
+    let mut card_data = BTreeMap::new();
+    card_data.insert("twitter:card".to_string(), Json::String(format!("poll{}choice_text_only", options.len())));
+    card_data.insert("twitter:api:api:endpoint".to_string(), Json::String("1".to_string()));
+    card_data.insert("twitter:long:duration_minutes".to_string(), Json::U64(duration_minutes));
+    for (i, option) in options.iter().enumerate() {
+        card_data.insert(format!("twitter:string:choice{}_label", i + 1), Json::String(option.clone()));
+    }
+
+    let resp = try!(post("https://caps.twitter.com/v2/cards/create.json",
+                         con_token,
+                         access_token,
+                         &vec![("card_data", Json::Object(card_data).to_string())]));
+    resp.find("card_uri").and_then(Json::as_string).map(str::to_string).ok_or(Error::MissingValue("card_uri"))
+}
+
+/// Post a tweet with the specified content and card, returning its creation time in the format used by the Twitter API
+/// and its ID.
+pub fn update_status_with_card(status: &str, card_uri: &str, con_token: &Token, access_token: &Token) -> Result<(String, i64), Error> {
+    let resp = try!(post("https://api.twitter.com/1.1/statuses/update.json",
+                         con_token,
+                         access_token,
+                         &vec![("status", status.to_string()), ("card_uri", card_uri.to_string())]));
+    match (resp.find("created_at").and_then(Json::as_string), resp.find("id").and_then(Json::as_i64)) {
+        (Some(created_at), Some(id)) => Ok((created_at.to_string(), id)),
+        (None, _) => Err(Error::MissingValue("created_at")),
+        (_, None) => Err(Error::MissingValue("id")),
+    }
+}
+
+/// Check whether the specified error means the user's access token doesn't work anymore, e.g. because the app was revoked.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate egg_mode;
+/// # extern crate tweetr;
+/// # extern crate hyper;
+/// # use egg_mode::error::{Error, TwitterErrors, TwitterErrorCode};
+/// # use tweetr::api::is_authentication_error;
+/// # use hyper::status::StatusCode;
+/// # fn main() {
+/// assert!(is_authentication_error(&Error::TwitterError(TwitterErrors {
+///     errors: vec![TwitterErrorCode {
+///                      message: "Invalid or expired token.".to_string(),
+///                      code: 89,
+///                  }],
+/// })));
+/// assert!(is_authentication_error(&Error::BadStatus(StatusCode::Unauthorized)));
+///
+/// assert!(!is_authentication_error(&Error::TwitterError(TwitterErrors {
+///     errors: vec![TwitterErrorCode {
+///                      message: "Status is a duplicate.".to_string(),
+///                      code: 187,
+///                  }],
+/// })));
+/// assert!(!is_authentication_error(&Error::MissingValue("id")));
+/// # }
+/// ```
+pub fn is_authentication_error(err: &Error) -> bool {
+    match *err {
+        // 32: Could not authenticate you; 89: Invalid or expired token
+        Error::TwitterError(ref errs) => errs.errors.iter().any(|e| e.code == 32 || e.code == 89),
+        Error::BadStatus(StatusCode::Unauthorized) => true,
+        _ => false,
+    }
+}
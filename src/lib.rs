@@ -21,6 +21,11 @@
 //! 2 - required data or file needs to be created by running the specified filesysstem
 //! 3 - an error was returned by the Twitter API
 //! 4 - failed to parse the specified file
+//! 5 - the tweets to queue were invalid
+//! 6 - a different user was authorised than the one being re-authorised
+//! 7 - the specified credentials were invalid
+//! 8 - files containing secrets were accessible to other users
+//! 9 - problems were found in the configuration
 //! ```
 //!
 //! ## Executable manpage
@@ -36,11 +41,15 @@ extern crate chrono;
 extern crate regex;
 #[macro_use]
 extern crate clap;
+extern crate crypto;
+extern crate hyper;
 extern crate toml;
+extern crate rand;
 
 mod outcome;
 
 pub mod ops;
+pub mod api;
 pub mod util;
 pub mod options;
 
@@ -1,10 +1,13 @@
 extern crate tweetr;
+extern crate egg_mode;
+extern crate chrono;
 
-use std::thread;
+use std::io::{stdin, stdout, stderr};
+use std::collections::BTreeMap;
+use std::time::Duration;
 use std::process::exit;
 use std::path::PathBuf;
-use std::time::Duration;
-use std::io::{stdin, stdout, stderr};
+use std::thread;
 
 
 fn main() {
@@ -16,10 +19,24 @@ fn actual_main() -> i32 {
     let opts = tweetr::options::Options::parse();
 
     let err = match opts.subsystem.clone() {
-            tweetr::options::Subsystem::Init { force } => init_main(opts, force),
-            tweetr::options::Subsystem::AddUser { verbose } => add_user_main(opts, verbose),
-            tweetr::options::Subsystem::QueueTweet { file_to_load } => queue_tweet_main(opts, file_to_load),
-            tweetr::options::Subsystem::StartDaemon { delay, verbose } => start_daemon_main(opts, delay, verbose),
+            tweetr::options::Subsystem::Init { force, tokens, json, app } => init_main(opts, force, tokens, json, app),
+            tweetr::options::Subsystem::AddUser { verbose, callback, import_token, import_json, app } => {
+                add_user_main(opts, verbose, callback, import_token, import_json, app)
+            }
+            tweetr::options::Subsystem::QueueTweet { file_to_load, allow_unknown_authors } => {
+                queue_tweet_main(opts, file_to_load, allow_unknown_authors)
+            }
+            tweetr::options::Subsystem::Check { file, allow_unknown_authors } => check_main(opts, file, allow_unknown_authors),
+            tweetr::options::Subsystem::StartDaemon { delay, verbose, catch_up } => start_daemon_main(opts, delay, verbose, catch_up),
+            tweetr::options::Subsystem::Reschedule { shift, selector } => reschedule_main(opts, shift, selector),
+            tweetr::options::Subsystem::Respread { start, end, selector } => respread_main(opts, start, end, selector),
+            tweetr::options::Subsystem::RefreshUsers { verbose } => refresh_users_main(opts, verbose),
+            tweetr::options::Subsystem::ListUsers => list_users_main(opts),
+            tweetr::options::Subsystem::RemoveUser { name } => remove_user_main(opts, name),
+            tweetr::options::Subsystem::ReauthUser { name, verbose, callback } => reauth_user_main(opts, name, verbose, callback),
+            tweetr::options::Subsystem::VerifyUsers { verbose } => verify_users_main(opts, verbose),
+            tweetr::options::Subsystem::Doctor { online, verbose } => doctor_main(opts, online, verbose),
+            tweetr::options::Subsystem::Rekey { new_passphrase } => rekey_main(opts, new_passphrase),
         }
         .err()
         .unwrap_or(tweetr::Outcome::NoError);
@@ -27,35 +44,76 @@ fn actual_main() -> i32 {
     err.exit_value()
 }
 
-fn init_main(opts: tweetr::options::Options, force: bool) -> Result<(), tweetr::Outcome> {
-    let app_path = try!(tweetr::ops::init::verify(&opts.config_dir, force));
+fn init_main(opts: tweetr::options::Options, force: bool, tokens: Option<(String, String)>, json: bool, app: Option<String>)
+             -> Result<(), tweetr::Outcome> {
+    let app_path = try!(tweetr::ops::init::verify(&opts.config_dir, app.as_ref().map(|a| &a[..]), force));
+    let key = try!(unlock(&opts));
 
     let stdin = stdin();
     let mut lock = stdin.lock();
 
-    let data = tweetr::ops::init::get_data(&mut lock, &mut stdout());
-    data.write(&app_path);
+    let data = match tokens {
+        Some((key, secret)) => {
+            tweetr::ops::AppTokens {
+                key: key,
+                secret: secret,
+                secret_command: None,
+            }
+        }
+        None if json => try!(tweetr::ops::init::data_from_json(&mut lock)),
+        None => tweetr::ops::init::get_data(&mut lock, &mut stdout()),
+    };
+    try!(tweetr::ops::init::validate_data(&data));
+    data.write(&app_path, key.as_ref());
 
     Ok(())
 }
 
-fn add_user_main(opts: tweetr::options::Options, verbose: bool) -> Result<(), tweetr::Outcome> {
-    let (app_path, users_path) = try!(tweetr::ops::add_user::verify(&opts.config_dir));
-    let app = try!(tweetr::ops::AppTokens::read(&app_path).map_err(Option::unwrap));
+fn add_user_main(opts: tweetr::options::Options, verbose: bool, callback: bool, import_token: Option<(String, String)>, import_json: bool,
+                 app_profile: Option<String>)
+                 -> Result<(), tweetr::Outcome> {
+    let (app_path, users_path) = try!(tweetr::ops::add_user::verify(&opts.config_dir, app_profile.as_ref().map(|a| &a[..])));
+    let key = try!(unlock(&opts));
+    let app = try!(try!(tweetr::ops::AppTokens::read(&app_path, key.as_ref()).map_err(Option::unwrap)).token());
 
-    let stdin = stdin();
-    let mut lock = stdin.lock();
+    let import_token = if import_json {
+        let stdin = stdin();
+        let mut lock = stdin.lock();
+        Some(try!(tweetr::ops::add_user::token_from_json(&mut lock)))
+    } else {
+        import_token
+    };
 
-    let user = try!(tweetr::ops::add_user::authorise(&mut lock, &mut stdout(), app, verbose));
-    println!("");
+    let mut user = match import_token {
+        Some((key, secret)) => try!(tweetr::ops::add_user::import_token(&key, &secret, &mut stdout(), app, verbose)),
+        None => {
+            let user = try!(authorise(app, verbose, callback));
+            println!("");
+            user
+        }
+    };
+    user.app = app_profile;
     tweetr::ops::add_user::print_success_message(&mut stdout(), &user, verbose);
 
-    Err(tweetr::ops::add_user::append_user(&users_path, user))
+    Err(tweetr::ops::add_user::append_user(&users_path, user, key.as_ref()))
 }
 
-fn queue_tweet_main(opts: tweetr::options::Options, file_to_load: Option<PathBuf>) -> Result<(), tweetr::Outcome> {
+fn queue_tweet_main(opts: tweetr::options::Options, file_to_load: Option<PathBuf>, allow_unknown_authors: bool) -> Result<(), tweetr::Outcome> {
     let tweets_path = tweetr::ops::queue_tweet::tweets_path(&opts.config_dir.1);
 
+    let users_path = tweetr::ops::queue_tweet::users_path(&opts.config_dir.1);
+    let (users, groups) = if users_path.exists() {
+        (try!(tweetr::ops::User::read(&users_path, None).map_err(Option::unwrap)), try!(tweetr::ops::UserGroup::read(&users_path).map_err(Option::unwrap)))
+    } else {
+        (vec![], vec![])
+    };
+    let known_authors = tweetr::ops::queue_tweet::known_authors(&users, &groups);
+    let known_authors = if allow_unknown_authors {
+        None
+    } else {
+        Some(&known_authors)
+    };
+
     let mut tweets_to_queue = match file_to_load {
         Some(ftl) => try!(tweetr::ops::QueuedTweet::read(&ftl).map_err(Option::unwrap)),
         None => {
@@ -63,45 +121,307 @@ fn queue_tweet_main(opts: tweetr::options::Options, file_to_load: Option<PathBuf
             let mut lock = stdin.lock();
 
             let mut ttq = Vec::new();
-            while let Some(tweet) = tweetr::ops::queue_tweet::get_tweet(&mut lock, &mut stdout()) {
+            while let Some(tweet) = tweetr::ops::queue_tweet::get_tweet(&mut lock, &mut stdout(), known_authors) {
                 ttq.push(tweet);
             }
             ttq
         }
     };
+    try!(tweetr::ops::queue_tweet::validate_tweets(&tweets_to_queue, known_authors));
+
+    for tweet in &mut tweets_to_queue {
+        tweetr::ops::queue_tweet::bind_author(tweet, &users);
+        tweetr::ops::queue_tweet::apply_jitter(tweet, &users);
+    }
 
     let mut tweets = try!(tweetr::ops::QueuedTweet::read(&tweets_path).map_err(Option::unwrap));
     tweets.append(&mut tweets_to_queue);
+    tweetr::ops::QueuedTweet::resolve_anchors(&mut tweets, &mut stdout());
+    tweets.sort();
+
+    tweetr::ops::QueuedTweet::write(tweets, &tweets_path);
+
+    Ok(())
+}
+
+fn reschedule_main(opts: tweetr::options::Options, shift: chrono::Duration, selector: tweetr::ops::TweetSelector) -> Result<(), tweetr::Outcome> {
+    let tweets_path = try!(tweetr::ops::reschedule::verify(&opts.config_dir));
+    let mut tweets = try!(tweetr::ops::QueuedTweet::read(&tweets_path).map_err(Option::unwrap));
+
+    let moved = tweetr::ops::reschedule::shift(&mut tweets, &selector, shift);
+    tweetr::ops::QueuedTweet::resolve_anchors(&mut tweets, &mut stdout());
     tweets.sort();
+    tweetr::ops::QueuedTweet::write(tweets, &tweets_path);
 
+    println!("Rescheduled {} tweets.", moved);
+    Ok(())
+}
+
+fn respread_main(opts: tweetr::options::Options, start: chrono::DateTime<chrono::FixedOffset>, end: chrono::DateTime<chrono::FixedOffset>,
+                 selector: tweetr::ops::TweetSelector)
+                 -> Result<(), tweetr::Outcome> {
+    let tweets_path = try!(tweetr::ops::respread::verify(&opts.config_dir));
+    let mut tweets = try!(tweetr::ops::QueuedTweet::read(&tweets_path).map_err(Option::unwrap));
+
+    let moved = tweetr::ops::respread::respread(&mut tweets, &selector, start, end);
+    tweetr::ops::QueuedTweet::resolve_anchors(&mut tweets, &mut stdout());
+    tweets.sort();
     tweetr::ops::QueuedTweet::write(tweets, &tweets_path);
 
+    println!("Respread {} tweets.", moved);
+    Ok(())
+}
+
+fn refresh_users_main(opts: tweetr::options::Options, verbose: bool) -> Result<(), tweetr::Outcome> {
+    let users_path = try!(tweetr::ops::refresh_users::verify(&opts.config_dir));
+    let key = try!(unlock(&opts));
+    let apps = try!(read_apps(&opts, key.as_ref()));
+    let mut users = try!(tweetr::ops::User::read(&users_path, key.as_ref()).map_err(Option::unwrap));
+    let mut groups = try!(tweetr::ops::UserGroup::read(&users_path).map_err(Option::unwrap));
+    let users_before = users.clone();
+
+    for user in &mut users {
+        match tweetr::ops::AppTokens::for_user(&apps, user).and_then(tweetr::ops::AppTokens::token) {
+            Ok(app) => tweetr::ops::refresh_users::refresh_user(user, &app, verbose, &mut stdout()).print_error(&mut stderr()),
+            Err(out) => out.print_error(&mut stderr()),
+        }
+    }
+    let renamed_members = tweetr::ops::refresh_users::rename_members(&mut groups, &users_before, &users);
+    tweetr::ops::User::write_with_groups(users.clone(), groups, &users_path, key.as_ref());
+    if renamed_members != 0 {
+        println!("Updated {} group members.", renamed_members);
+    }
+
+    let tweets_path = tweetr::ops::queue_tweet::tweets_path(&opts.config_dir.1);
+    if tweets_path.exists() {
+        let mut tweets = try!(tweetr::ops::QueuedTweet::read(&tweets_path).map_err(Option::unwrap));
+        let renamed = tweetr::ops::refresh_users::rename_authors(&mut tweets, &users);
+        tweetr::ops::QueuedTweet::write(tweets, &tweets_path);
+
+        println!("Updated the author of {} tweets.", renamed);
+    }
+
+    Ok(())
+}
+
+fn list_users_main(opts: tweetr::options::Options) -> Result<(), tweetr::Outcome> {
+    let users_path = try!(tweetr::ops::list_users::verify(&opts.config_dir));
+    let users = try!(tweetr::ops::User::read(&users_path, None).map_err(Option::unwrap));
+    let groups = try!(tweetr::ops::UserGroup::read(&users_path).map_err(Option::unwrap));
+
+    tweetr::ops::list_users::print_users(&mut stdout(), &users, &groups);
+    Ok(())
+}
+
+fn remove_user_main(opts: tweetr::options::Options, name: String) -> Result<(), tweetr::Outcome> {
+    let users_path = try!(tweetr::ops::remove_user::verify(&opts.config_dir));
+    let mut users = try!(tweetr::ops::User::read(&users_path, None).map_err(Option::unwrap));
+    let mut groups = try!(tweetr::ops::UserGroup::read(&users_path).map_err(Option::unwrap));
+
+    let user = users.remove(try!(tweetr::ops::remove_user::find_user(&users, &name)));
+
+    let tweets_path = tweetr::ops::queue_tweet::tweets_path(&opts.config_dir.1);
+    if tweets_path.exists() {
+        let tweets = try!(tweetr::ops::QueuedTweet::read(&tweets_path).map_err(Option::unwrap));
+        let orphans = tweetr::ops::remove_user::orphaned_tweets(&tweets, &user);
+        tweetr::ops::remove_user::print_orphan_warning(&mut stderr(), &user.name, &orphans);
+    }
+
+    let left_groups = tweetr::ops::remove_user::remove_from_groups(&mut groups, &user.name);
+    tweetr::ops::User::write_with_groups(users, groups, &users_path, None);
+    println!("Removed user {}#{}", user.name, user.id);
+    if !left_groups.is_empty() {
+        println!("Removed {} from groups {}", user.name, left_groups.join(", "));
+    }
+
+    Ok(())
+}
+
+fn reauth_user_main(opts: tweetr::options::Options, name: String, verbose: bool, callback: bool) -> Result<(), tweetr::Outcome> {
+    let users_path = try!(tweetr::ops::reauth_user::verify(&opts.config_dir));
+    let key = try!(unlock(&opts));
+    let mut users = try!(tweetr::ops::User::read(&users_path, key.as_ref()).map_err(Option::unwrap));
+    let user_i = try!(tweetr::ops::remove_user::find_user(&users, &name));
+    let app_path = try!(tweetr::ops::AppTokens::profile_path(&opts.config_dir, users[user_i].app.as_ref().map(|a| &a[..])));
+    let app = try!(try!(tweetr::ops::AppTokens::read(&app_path, key.as_ref()).map_err(Option::unwrap)).token());
+
+    let authorised = try!(authorise(app, verbose, callback));
+    println!("");
+    let unstored_secret = try!(tweetr::ops::reauth_user::replace_tokens(&mut users[user_i], authorised));
+    tweetr::ops::add_user::print_success_message(&mut stdout(), &users[user_i], verbose);
+    if let Some(secret) = unstored_secret {
+        println!("The access token secret of {} is fetched via `{}`, so it has to be updated there for the new tokens to work:",
+                 users[user_i].name,
+                 users[user_i].secret_command.as_ref().unwrap());
+        println!("  {}", secret);
+    }
+
+    try!(tweetr::ops::User::write(users, &users_path, key.as_ref()));
     Ok(())
 }
 
-fn start_daemon_main(opts: tweetr::options::Options, delay: Duration, verbose: bool) -> Result<(), tweetr::Outcome> {
-    let (app_path, users_path, tweets_path) = try!(tweetr::ops::start_daemon::verify(&opts.config_dir));
-    let app = try!(tweetr::ops::AppTokens::read(&app_path).map_err(Option::unwrap));
-    let app_tokens = app.raw_token();
+fn authorise(app: egg_mode::Token<'static>, verbose: bool, callback: bool) -> Result<tweetr::ops::User, tweetr::Outcome> {
+    if callback {
+        match tweetr::ops::add_user::callback_listener() {
+            Some(listener) => return tweetr::ops::add_user::authorise_callback(listener, &mut stdout(), app, verbose),
+            None => println!("No display to authorise via a callback in, falling back to PIN authorisation."),
+        }
+    }
+
+    let stdin = stdin();
+    let mut lock = stdin.lock();
+
+    tweetr::ops::add_user::authorise(&mut lock, &mut stdout(), app, verbose)
+}
+
+fn verify_users_main(opts: tweetr::options::Options, verbose: bool) -> Result<(), tweetr::Outcome> {
+    let users_path = try!(tweetr::ops::verify_users::verify(&opts.config_dir));
+    let key = try!(unlock(&opts));
+    let apps = try!(read_apps(&opts, key.as_ref()));
+    let users = try!(tweetr::ops::User::read(&users_path, key.as_ref()).map_err(Option::unwrap));
+
+    let mut revoked = vec![];
+    let mut failed = 0;
+    for user in &users {
+        match tweetr::ops::AppTokens::for_user(&apps, user).and_then(tweetr::ops::AppTokens::token)
+            .and_then(|app| tweetr::ops::verify_users::verify_user(user, &app, verbose, &mut stdout())) {
+            Ok(true) => {}
+            Ok(false) => revoked.push(user.name.clone()),
+            Err(out) => {
+                out.print_error(&mut stderr());
+                failed += 1;
+            }
+        }
+    }
+
+    Err(tweetr::ops::verify_users::revoked_outcome(&revoked, failed))
+}
+
+fn check_main(opts: tweetr::options::Options, file: PathBuf, allow_unknown_authors: bool) -> Result<(), tweetr::Outcome> {
+    let tweets_path = tweetr::ops::queue_tweet::tweets_path(&opts.config_dir.1);
+    let queued = if tweets_path.exists() {
+        try!(tweetr::ops::QueuedTweet::read(&tweets_path).map_err(Option::unwrap))
+    } else {
+        vec![]
+    };
+
+    let users_path = tweetr::ops::queue_tweet::users_path(&opts.config_dir.1);
+    let (users, groups) = if users_path.exists() {
+        (try!(tweetr::ops::User::read(&users_path, None).map_err(Option::unwrap)), try!(tweetr::ops::UserGroup::read(&users_path).map_err(Option::unwrap)))
+    } else {
+        (vec![], vec![])
+    };
+    let known_authors = tweetr::ops::queue_tweet::known_authors(&users, &groups);
+    let known_authors = if allow_unknown_authors {
+        None
+    } else {
+        Some(&known_authors)
+    };
+
+    let tweets = try!(tweetr::ops::QueuedTweet::read_with_lines(&file).map_err(Option::unwrap));
+    let now = chrono::Local::now();
+    try!(tweetr::ops::check::check_tweets(&tweets, known_authors, &queued, now.with_timezone(now.offset())));
+
+    println!("{} tweets OK.", tweets.len());
+    Ok(())
+}
+
+fn start_daemon_main(opts: tweetr::options::Options, delay: Duration, verbose: bool, catch_up: tweetr::ops::start_daemon::CatchUpPolicy)
+                     -> Result<(), tweetr::Outcome> {
+    let (users_path, tweets_path) = try!(tweetr::ops::start_daemon::verify(&opts.config_dir));
+    match tweetr::ops::start_daemon::check_permissions(&opts.config_dir) {
+        tweetr::Outcome::NoError => (),
+        out => return Err(out),
+    }
+    let key = try!(unlock(&opts));
+    let apps = try!(read_apps(&opts, key.as_ref()));
+    let evergreen_path = tweetr::ops::start_daemon::evergreen_path(&opts.config_dir.1);
+    let catch_up_delay = chrono::Duration::from_std(delay).unwrap();
+    let mut first_pass = true;
 
     loop {
-        match (tweetr::ops::User::read(&users_path), tweetr::ops::QueuedTweet::read(&tweets_path)) {
-            (Ok(users), Ok(mut tweets)) => {
-                let tweets_to_post = tweetr::ops::start_daemon::tweet_indices_to_post(&tweets);
+        match (tweetr::ops::User::read(&users_path, key.as_ref()), tweetr::ops::QueuedTweet::read(&tweets_path)) {
+            (Ok(mut users), Ok(mut tweets)) => {
+                let users_read = users.clone();
+                match tweetr::ops::UserGroup::read(&users_path) {
+                    Ok(groups) => tweetr::ops::start_daemon::expand_groups(&mut tweets, &groups, &mut stdout()),
+                    Err(err) => err.unwrap().print_error(&mut stderr()),
+                }
+                tweetr::ops::QueuedTweet::resolve_anchors(&mut tweets, &mut stdout());
+                tweetr::ops::start_daemon::expire_tweets(&mut tweets, &mut stdout());
+                tweetr::ops::start_daemon::apply_catch_up_policy(&mut tweets, &users, &catch_up, catch_up_delay, first_pass, &mut stdout());
+                first_pass = false;
+                let tweets_to_post = tweetr::ops::start_daemon::tweet_indices_to_post(&mut tweets, &users, &catch_up);
 
                 for i in tweets_to_post {
                     let tweet_to_post = &mut tweets[i];
 
-                    match tweetr::ops::start_daemon::find_user_index_for_tweet(tweet_to_post, &users) {
-                        Ok(user_i) => {
-                            tweetr::ops::start_daemon::post_tweet(tweet_to_post, &users[user_i], &app_tokens, verbose, &mut stdout())
+                    match tweetr::ops::start_daemon::find_user_index_for_tweet(tweet_to_post, &users)
+                        .and_then(|user_i| tweetr::ops::AppTokens::for_user(&apps, &users[user_i]).and_then(tweetr::ops::AppTokens::token).map(|app| (user_i, app))) {
+                        Ok((user_i, app)) => {
+                            tweetr::ops::start_daemon::post_tweet(tweet_to_post, &mut users[user_i], &app, verbose, &mut stdout())
                                 .print_error(&mut stderr());
                         }
                         Err(out) => out.print_error(&mut stderr()),
                     }
                 }
 
+                for i in tweetr::ops::start_daemon::tweet_indices_to_delete(&tweets) {
+                    let tweet_to_delete = &mut tweets[i];
+
+                    match tweetr::ops::start_daemon::find_user_index_for_tweet(tweet_to_delete, &users)
+                        .and_then(|user_i| tweetr::ops::AppTokens::for_user(&apps, &users[user_i]).and_then(tweetr::ops::AppTokens::token).map(|app| (user_i, app))) {
+                        Ok((user_i, app)) => {
+                            tweetr::ops::start_daemon::delete_tweet(tweet_to_delete, &mut users[user_i], &app, verbose, &mut stdout())
+                                .print_error(&mut stderr());
+                        }
+                        Err(out) => out.print_error(&mut stderr()),
+                    }
+                }
+
+                if evergreen_path.exists() {
+                    match tweetr::ops::EvergreenPool::read(&evergreen_path) {
+                        Ok(mut pools) => {
+                            for pool in &mut pools {
+                                if let Some(i) = tweetr::ops::start_daemon::evergreen_tweet_index_to_post(pool, &tweets, &users) {
+                                    let mut tweet = tweetr::ops::start_daemon::evergreen_tweet_to_post(pool, i);
+
+                                    match tweetr::ops::start_daemon::find_user_index_for_tweet(&tweet, &users)
+                                        .and_then(|user_i| tweetr::ops::AppTokens::for_user(&apps, &users[user_i]).and_then(tweetr::ops::AppTokens::token).map(|app| (user_i, app))) {
+                                        Ok((user_i, app)) => {
+                                            tweetr::ops::start_daemon::post_tweet(&mut tweet, &mut users[user_i], &app, verbose, &mut stdout())
+                                                .print_error(&mut stderr());
+                                        }
+                                        Err(out) => out.print_error(&mut stderr()),
+                                    }
+
+                                    if tweet.id.is_some() {
+                                        pool.tweets[i].last_posted = tweet.time_posted;
+                                        tweets.push(tweet);
+                                    }
+                                }
+                            }
+
+                            tweetr::ops::EvergreenPool::write(pools, &evergreen_path);
+                        }
+                        Err(Some(err)) => err.print_error(&mut stderr()),
+                        Err(None) => {
+                            tweetr::Outcome::FileParsingFailed {
+                                    desc: "evergreen pools",
+                                    errors: vec![format!("couldn't read {}", evergreen_path.display())],
+                                }
+                                .print_error(&mut stderr())
+                        }
+                    }
+                }
+
+                tweets.sort();
                 tweetr::ops::QueuedTweet::write(tweets, &tweets_path);
+                if users != users_read {
+                    if let Err(out) = tweetr::ops::User::write(users, &users_path, key.as_ref()) {
+                        out.print_error(&mut stderr());
+                    }
+                }
 
                 thread::sleep(delay);
             }
@@ -110,3 +430,49 @@ fn start_daemon_main(opts: tweetr::options::Options, delay: Duration, verbose: b
         }
     }
 }
+
+fn doctor_main(opts: tweetr::options::Options, online: bool, verbose: bool) -> Result<(), tweetr::Outcome> {
+    let (key, mut findings) = tweetr::ops::doctor::check_secrets(&opts.config_dir.1, opts.passphrase.as_ref().map(|p| &p[..]));
+    let unlocked = key.is_ok();
+    let key = key.unwrap_or(None);
+
+    let (apps, app_findings) = tweetr::ops::doctor::check_apps(&opts.config_dir, key.as_ref());
+    findings.extend(app_findings);
+    let (users, user_findings) = tweetr::ops::doctor::check_users(&opts.config_dir, key.as_ref(), apps.as_ref());
+    findings.extend(user_findings);
+
+    let known_authors = users.as_ref().map(|&(ref users, ref groups)| tweetr::ops::queue_tweet::known_authors(users, groups));
+    let now = chrono::Local::now();
+    findings.extend(tweetr::ops::doctor::check_tweets(&opts.config_dir, known_authors.as_ref(), now.with_timezone(now.offset())));
+    findings.push(tweetr::ops::doctor::check_permissions(&opts.config_dir));
+
+    if online && unlocked {
+        if let (Some(apps), Some((users, _))) = (apps, users) {
+            findings.extend(tweetr::ops::doctor::check_tokens(&users, &apps, verbose, &mut stdout()));
+        }
+    }
+
+    tweetr::ops::doctor::print_report(&mut stdout(), &findings);
+    Err(tweetr::ops::doctor::outcome(&findings))
+}
+
+fn rekey_main(opts: tweetr::options::Options, new_passphrase: Option<String>) -> Result<(), tweetr::Outcome> {
+    let paths = try!(tweetr::ops::rekey::verify(&opts.config_dir));
+    let old_key = try!(unlock(&opts));
+    let new_key = new_passphrase.map(|p| tweetr::ops::SecretKey::new(&p));
+
+    tweetr::ops::rekey::rekey(&paths, old_key.as_ref(), new_key.as_ref(), &mut stdout())
+}
+
+fn unlock(opts: &tweetr::options::Options) -> Result<Option<tweetr::ops::SecretKey>, tweetr::Outcome> {
+    tweetr::ops::SecretKey::unlock(&opts.config_dir.1, opts.passphrase.as_ref().map(|p| &p[..]))
+}
+
+fn read_apps(opts: &tweetr::options::Options, key: Option<&tweetr::ops::SecretKey>)
+             -> Result<BTreeMap<Option<String>, tweetr::ops::AppTokens>, tweetr::Outcome> {
+    let (apps, errors) = try!(tweetr::ops::AppTokens::read_profiles(&opts.config_dir.1, key));
+    for err in errors {
+        err.print_error(&mut stderr());
+    }
+    Ok(apps)
+}
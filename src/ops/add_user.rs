@@ -6,25 +6,32 @@
 //! Options::parse()
 //! |> ops::add_user::verify()
 //! |> ops::AppTokens::read()
-//! |> ops::add_user::authorise()
+//! |> ops::add_user::token_from_json()
+//! |> ops::add_user::callback_listener()
+//! |> ops::add_user::authorise_callback() or ops::add_user::authorise() or ops::add_user::import_token()
 //! |> ops::add_user::append_user()
 //! |> ops::add_user::print_success_message()
 //! ```
 
-use egg_mode::{Token, request_token, authorize_url, access_token};
-use self::super::super::util::{prompt_exact_len, span_r};
-use self::super::{User, verify_file};
+use egg_mode::{Token, request_token, authorize_url, access_token, verify_tokens};
+use self::super::super::util::{prompt_nonzero_len, percent_decode, span_r};
+use std::io::{BufReader, BufRead, ErrorKind, Read, Write};
+use self::super::super::api::is_authentication_error;
+use self::super::{AppTokens, SecretKey, User};
+use std::time::{Duration, Instant};
 use self::super::super::Outcome;
 use std::path::{Path, PathBuf};
-use std::io::{BufRead, Write};
-use std::str::FromStr;
+use rustc_serialize::json;
+use std::net::TcpListener;
+use std::thread;
+use std::env;
 
 
 /// Verify if, given the current configuration, it's permitted to continue with the subsequent steps of the `add-user`
 /// subsystem.
 ///
-/// The return value contains either the path to the file containing the global app configuration and the path to the file
-/// containing the global users data or why getting them failed.
+/// The return value contains either the path to the file containing the global app configuration, or the specified app
+/// profile's (see `AppTokens`), and the path to the file containing the global users data or why getting them failed.
 ///
 /// # Examples
 ///
@@ -39,7 +46,7 @@ use std::str::FromStr;
 /// fs::create_dir_all(&tf).unwrap();
 /// File::create(tf.join("app.toml")).unwrap().write(&[]).unwrap();
 ///
-/// assert_eq!(add_user::verify(&("$TEMP/ops-add-user-verify-0".to_string(), tf.clone())),
+/// assert_eq!(add_user::verify(&("$TEMP/ops-add-user-verify-0".to_string(), tf.clone()), None),
 ///            Ok((tf.join("app.toml"), tf.join("users.toml"))));
 /// ```
 ///
@@ -50,14 +57,14 @@ use std::str::FromStr;
 /// # use std::env::temp_dir;
 /// # use tweetr::Outcome;
 /// let tf = temp_dir().join("tweetr-doctest").join("ops-add-user-verify-1");
-/// assert_eq!(add_user::verify(&("$TEMP/ops-add-user-verify-1".to_string(), tf)),
+/// assert_eq!(add_user::verify(&("$TEMP/ops-add-user-verify-1".to_string(), tf), None),
 ///            Err(Outcome::RequiredFileFromSubsystemNonexistant {
 ///                subsys: "init",
 ///                fname: "$TEMP/ops-add-user-verify-1/app.toml".to_string(),
 ///            }));
 /// ```
-pub fn verify(config_dir: &(String, PathBuf)) -> Result<(PathBuf, PathBuf), Outcome> {
-    let app = try!(verify_file("app.toml", true, config_dir, false, "init"));
+pub fn verify(config_dir: &(String, PathBuf), profile: Option<&str>) -> Result<(PathBuf, PathBuf), Outcome> {
+    let app = try!(AppTokens::profile_path(config_dir, profile));
     Ok((app, config_dir.1.join("users.toml")))
 }
 
@@ -73,6 +80,7 @@ pub fn verify(config_dir: &(String, PathBuf)) -> Result<(PathBuf, PathBuf), Outc
 /// assert!(add_user::authorise(&mut BufReader::new(b"1234567\n" as &[u8]), &mut Vec::new(), AppTokens {
 ///     key: "GeVFiYk7q8DhUmgMXE0iODrFa".to_string(),
 ///     secret: "bH3VIvYEwwVmMXkTnXB8N3HEQf4ShOf2Z4e1dkaqSJNGorK2pe".to_string(),
+///     secret_command: None,
 /// }, false).is_ok());
 /// ```
 pub fn authorise<'t, R, W, T>(input: &mut R, output: &mut W, conn_token: T, verbose: bool) -> Result<User, Outcome>
@@ -94,7 +102,7 @@ pub fn authorise<'t, R, W, T>(input: &mut R, output: &mut W, conn_token: T, verb
                                                           true));
 
     writeln!(output, "Visit this URL: {}", authorize_url(&req_token)).unwrap();
-    let pin = prompt_exact_len(input, output, "Enter the PIN from that page", |s| u32::from_str(s).is_ok(), 7).unwrap();
+    let pin = prompt_nonzero_len(input, output, "Enter the PIN from that page", |s| s.chars().all(|c| c.is_digit(10))).unwrap();
 
     let access_token_data = try!(wrap_network_op_in_ellipsis_done(output,
                                                                   || {
@@ -109,7 +117,198 @@ pub fn authorise<'t, R, W, T>(input: &mut R, output: &mut W, conn_token: T, verb
     Ok(User::from_raw_access_token(access_token_data))
 }
 
-/// Append the specified user to the authenticated users list at the specified path.
+/// Start listening for the OAuth callback on a random loopback port, for use with `authorise_callback()`.
+///
+/// Returns `None` if the machine seems to be headless, i.e. has no graphical session to open the authorisation URL in, or
+/// listening failed, in which case `authorise()` should be used instead.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::add_user;
+/// if let Some(listener) = add_user::callback_listener() {
+///     assert!(listener.local_addr().unwrap().ip().is_loopback());
+/// }
+/// ```
+pub fn callback_listener() -> Option<TcpListener> {
+    let headless = cfg!(unix) && !cfg!(target_os = "macos") && env::var_os("DISPLAY").is_none() &&
+                   env::var_os("WAYLAND_DISPLAY").is_none();
+    if headless {
+        None
+    } else {
+        TcpListener::bind("127.0.0.1:0").ok()
+    }
+}
+
+/// Direct the user towards the authorisation URL and wait for Twitter to redirect it back to the specified listener.
+///
+/// Gives up after 5 minutes.
+///
+/// Returns `Err()` if accessing the Twitter API failed for whatever reason or the user denied the authorisation.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use tweetr::ops::{add_user, AppTokens};
+/// assert!(add_user::authorise_callback(add_user::callback_listener().unwrap(), &mut Vec::new(), AppTokens {
+///     key: "GeVFiYk7q8DhUmgMXE0iODrFa".to_string(),
+///     secret: "bH3VIvYEwwVmMXkTnXB8N3HEQf4ShOf2Z4e1dkaqSJNGorK2pe".to_string(),
+///     secret_command: None,
+/// }, false).is_ok());
+/// ```
+pub fn authorise_callback<'t, W, T>(listener: TcpListener, output: &mut W, conn_token: T, verbose: bool) -> Result<User, Outcome>
+    where W: Write,
+          T: Into<Token<'t>>
+{
+    let conn_token = conn_token.into();
+    let callback = format!("http://{}/callback", listener.local_addr().unwrap());
+
+    let req_token = try!(wrap_network_op_in_ellipsis_done(output,
+                                                          || {
+                                                              let req_token = request_token(&conn_token, &callback[..])
+                                                                  .map_err(|e| Outcome::TwitterAPIError(format!("{}", e)));
+                                                              (req_token.is_ok(), req_token)
+                                                          },
+                                                          "request token",
+                                                          verbose,
+                                                          false,
+                                                          true));
+
+    writeln!(output, "Visit this URL: {}", authorize_url(&req_token)).unwrap();
+    writeln!(output, "Waiting for authorisation...").unwrap();
+    let verifier = try!(wait_for_verifier(listener, &req_token.key, Duration::from_secs(5 * 60)));
+
+    let access_token_data = try!(wrap_network_op_in_ellipsis_done(output,
+                                                                  || {
+                                                                      let access_token_data = access_token(&conn_token, &req_token, verifier)
+                                                                          .map_err(|e| Outcome::TwitterAPIError(format!("{}", e)));
+                                                                      (access_token_data.is_ok(), access_token_data)
+                                                                  },
+                                                                  "access token",
+                                                                  verbose,
+                                                                  true,
+                                                                  false));
+    Ok(User::from_raw_access_token(access_token_data))
+}
+
+/// Create a user from an existing access token, after checking it works and whose it is.
+///
+/// Returns `Err()` if the token doesn't work or accessing the Twitter API failed for whatever reason.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use tweetr::ops::{add_user, AppTokens};
+/// let user = add_user::import_token("529443-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4",
+///                                   "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5",
+///                                   &mut Vec::new(),
+///                                   AppTokens {
+///                                       key: "GeVFiYk7q8DhUmgMXE0iODrFa".to_string(),
+///                                       secret: "bH3VIvYEwwVmMXkTnXB8N3HEQf4ShOf2Z4e1dkaqSJNGorK2pe".to_string(),
+///                                       secret_command: None,
+///                                   },
+///                                   false)
+///     .unwrap();
+/// assert_eq!(user.name, "nabijaczleweli");
+/// assert_eq!(user.id, 529443);
+/// ```
+pub fn import_token<'t, W, T>(key: &str, secret: &str, output: &mut W, conn_token: T, verbose: bool) -> Result<User, Outcome>
+    where W: Write,
+          T: Into<Token<'t>>
+{
+    let conn_token = conn_token.into();
+    let access_token = Token::new(key, secret);
+
+    let resp = wrap_network_op_in_ellipsis_done(output,
+                                                || {
+                                                    let resp = verify_tokens(&conn_token, &access_token);
+                                                    (resp.is_ok(), resp)
+                                                },
+                                                "user data",
+                                                verbose,
+                                                false,
+                                                false);
+    let resp = try!(resp.map_err(|e| if is_authentication_error(&e) {
+        Outcome::InvalidCredentials(vec![format!("access token doesn't work: {}", e)])
+    } else {
+        Outcome::TwitterAPIError(e.to_string())
+    }));
+    Ok(User::from_raw_access_token((access_token, resp.response.id, resp.response.screen_name)))
+}
+
+/// Read an existing access token's key and secret from a JSON object with `key` and `secret` strings, to pass to
+/// `import_token()`.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::add_user;
+/// assert_eq!(add_user::token_from_json(&mut &br#"{"key": "529443-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4",
+///                                                 "secret": "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5"}"#[..]),
+///            Ok(("529443-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4".to_string(), "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5".to_string())));
+/// assert!(add_user::token_from_json(&mut &br#"{"key": "529443-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4"}"#[..]).is_err());
+/// ```
+pub fn token_from_json<R: Read>(input: &mut R) -> Result<(String, String), Outcome> {
+    let mut buf = String::new();
+    let parsed: Result<ImportedToken, String> =
+        input.read_to_string(&mut buf).map_err(|e| e.to_string()).and_then(|_| json::decode(&buf).map_err(|e| e.to_string()));
+    parsed.map(|token| (token.key, token.secret)).map_err(|e| {
+        Outcome::FileParsingFailed {
+            desc: "access token JSON",
+            errors: vec![e],
+        }
+    })
+}
+
+/// Get the OAuth verifier from the request line of a callback request for the specified request token.
+///
+/// Returns `None` for unrelated requests, which should be ignored, and `Some(Err())` if the user denied the authorisation.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::add_user;
+/// # use tweetr::Outcome;
+/// assert_eq!(add_user::callback_verifier("GET /callback?oauth_token=JJm6VwAAAAAAxW8MAAABV1QSjb4&oauth_verifier=lWkMiB9vG7sBbHS2 \
+///                                         HTTP/1.1",
+///                                        "JJm6VwAAAAAAxW8MAAABV1QSjb4"),
+///            Some(Ok("lWkMiB9vG7sBbHS2".to_string())));
+/// assert_eq!(add_user::callback_verifier("GET /callback?denied=JJm6VwAAAAAAxW8MAAABV1QSjb4 HTTP/1.1", "JJm6VwAAAAAAxW8MAAABV1QSjb4"),
+///            Some(Err(Outcome::TwitterAPIError("authorisation denied".to_string()))));
+/// assert_eq!(add_user::callback_verifier("GET /callback?oauth_token=JJm6VwAAAAAAxW8MAAABV1QSjb4&oauth_verifier=lWkMiB9v%2BG7sB HTTP/1.1",
+///                                        "JJm6VwAAAAAAxW8MAAABV1QSjb4"),
+///            Some(Ok("lWkMiB9v+G7sB".to_string())));
+/// assert_eq!(add_user::callback_verifier("GET /favicon.ico HTTP/1.1", "JJm6VwAAAAAAxW8MAAABV1QSjb4"), None);
+/// ```
+pub fn callback_verifier(request_line: &str, request_token_key: &str) -> Option<Result<String, Outcome>> {
+    let mut parts = request_line.split(' ');
+    let query = match (parts.next(), parts.next()) {
+        (Some("GET"), Some(path)) if path.starts_with("/callback?") => &path["/callback?".len()..],
+        _ => return None,
+    };
+
+    let mut token = None;
+    let mut verifier = None;
+    for param in query.split('&') {
+        let mut kv = param.splitn(2, '=');
+        match (kv.next(), kv.next().and_then(percent_decode)) {
+            (Some("oauth_token"), Some(v)) => token = Some(v),
+            (Some("oauth_verifier"), Some(v)) => verifier = Some(v),
+            (Some("denied"), Some(ref v)) if v == request_token_key => {
+                return Some(Err(Outcome::TwitterAPIError("authorisation denied".to_string())));
+            }
+            _ => {}
+        }
+    }
+
+    match (token, verifier) {
+        (Some(token), Some(verifier)) if token == request_token_key => Some(Ok(verifier)),
+        _ => None,
+    }
+}
+
+/// Append the specified user to the authenticated users list at the specified path, encrypting its secret with the specified
+/// key, if any.
 ///
 /// # Examples
 ///
@@ -121,17 +320,15 @@ pub fn authorise<'t, R, W, T>(input: &mut R, output: &mut W, conn_token: T, verb
 /// fs::create_dir_all(&tf).unwrap();
 ///
 /// let tf = tf.join("users.toml");
-/// add_user::append_user(&tf, User {
-///     name: "random-test-name".to_string(),
-///     id: 0x969696969,
-///     access_token_key: "40423221609-Y0klmK9nWNRAScBuumWvAtSOzmIvBIBLJpc3Ept".to_string(),
-///     access_token_secret: "zFYbEO5wQtST3eK84pGuzSmmEByZbQ0EVY8uAS4BCM1mx".to_string(),
-/// });
+/// add_user::append_user(&tf, User::new("random-test-name",
+///                                      0x969696969,
+///                                      "40423221609-Y0klmK9nWNRAScBuumWvAtSOzmIvBIBLJpc3Ept",
+///                                      "zFYbEO5wQtST3eK84pGuzSmmEByZbQ0EVY8uAS4BCM1mx"), None);
 /// assert!(tf.exists());
 /// ```
-pub fn append_user(users_path: &Path, user: User) -> Outcome {
+pub fn append_user(users_path: &Path, user: User, key: Option<&SecretKey>) -> Outcome {
     let mut users = if users_path.exists() {
-        match User::read(users_path).map_err(Option::unwrap) {
+        match User::read(users_path, key).map_err(Option::unwrap) {
             Ok(users) => users,
             Err(out) => return out,
         }
@@ -144,8 +341,7 @@ pub fn append_user(users_path: &Path, user: User) -> Outcome {
         Err(possidx) => users.insert(possidx, user),
     }
 
-    User::write(users, &users_path);
-    Outcome::NoError
+    User::write(users, &users_path, key).err().unwrap_or(Outcome::NoError)
 }
 
 /// Print the success message mentioning the specified user's name and ID, optionally also mentioning tokens.
@@ -156,12 +352,10 @@ pub fn append_user(users_path: &Path, user: User) -> Outcome {
 /// # use tweetr::ops::{add_user, User};
 /// # use std::iter::FromIterator;
 /// let mut out = Vec::new();
-/// add_user::print_success_message(&mut out, &User {
-///     name: "random-test-name".to_string(),
-///     id: 0x42069,
-///     access_token_key: "270441-N48kdEQFWtj7cUyWomNeE2AsNQw8pnmOaQbcwnV".to_string(),
-///     access_token_secret: "jCcBthGzve36QMt3RAV6jOEg4qtHt7laMV2YFA3qKCRzw".to_string(),
-/// }, false);
+/// add_user::print_success_message(&mut out, &User::new("random-test-name",
+///                                                      0x42069,
+///                                                      "270441-N48kdEQFWtj7cUyWomNeE2AsNQw8pnmOaQbcwnV",
+///                                                      "jCcBthGzve36QMt3RAV6jOEg4qtHt7laMV2YFA3qKCRzw"), false);
 /// assert_eq!(out, Vec::from_iter(b"Successfully authenticated user random-test-name#270441\n".iter().cloned()));
 /// ```
 pub fn print_success_message<W: Write>(output: &mut W, user: &User, verbose: bool) {
@@ -174,6 +368,43 @@ pub fn print_success_message<W: Write>(output: &mut W, user: &User, verbose: boo
 }
 
 
+fn wait_for_verifier(listener: TcpListener, request_token_key: &str, timeout: Duration) -> Result<String, Outcome> {
+    listener.set_nonblocking(true).unwrap();
+    let start = Instant::now();
+
+    while start.elapsed() < timeout {
+        let mut stream = match listener.accept() {
+            Ok((stream, _)) => stream,
+            Err(ref e) if e.kind() == ErrorKind::WouldBlock => {
+                thread::sleep(Duration::from_millis(100));
+                continue;
+            }
+            Err(_) => continue,
+        };
+        let _ = stream.set_nonblocking(false);
+        // A browser can open a connection without sending anything on it, which would otherwise block the loop forever
+        let _ = stream.set_read_timeout(Some(Duration::from_secs(5)));
+
+        let mut request_line = String::new();
+        if BufReader::new(&mut stream).read_line(&mut request_line).is_err() {
+            continue;
+        }
+
+        match callback_verifier(request_line.trim(), request_token_key) {
+            Some(res) => {
+                let _ = stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nConnection: close\r\n\r\n\
+                                           Done, you can close this page and go back to tweetr now.\n");
+                return res;
+            }
+            None => {
+                let _ = stream.write_all(b"HTTP/1.1 404 Not Found\r\nConnection: close\r\n\r\n");
+            }
+        }
+    }
+
+    Err(Outcome::TwitterAPIError("timed out waiting for authorisation".to_string()))
+}
+
 fn wrap_network_op_in_ellipsis_done<W, T, F>(output: &mut W, f: F, desc: &str, verbose: bool, nl_before: bool, nl_after: bool) -> T
     where W: Write,
           F: FnOnce() -> (bool, T)
@@ -201,3 +432,10 @@ fn wrap_network_op_in_ellipsis_done<W, T, F>(output: &mut W, f: F, desc: &str, v
         f().1
     }
 }
+
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, RustcDecodable)]
+struct ImportedToken {
+    key: String,
+    secret: String,
+}
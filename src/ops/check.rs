@@ -0,0 +1,101 @@
+//! This module contains the functions used only by the `check` subsystem.
+//!
+//! The flow of the `check` subsystem is as follows:
+//!
+//! ```plaintext
+//! Options::parse()
+//! |> ops::queue_tweet::tweets_path()
+//! |> ops::QueuedTweet::read()
+//! |> ops::queue_tweet::users_path()
+//! |> ops::User::read()
+//! |> ops::UserGroup::read()
+//! |> ops::queue_tweet::known_authors()
+//! |> ops::QueuedTweet::read_with_lines()
+//! |> ops::check::check_tweets()
+//! ```
+
+
+use self::super::{QueuedTweet, queue_tweet};
+use chrono::{DateTime, FixedOffset};
+use self::super::super::Outcome;
+
+
+/// Check the tweets read from a file to be queued, reporting every problem with the line of the tweet it's in.
+///
+/// Tweets are checked for whether they were read correctly, are valid according to `queue_tweet::validate_tweets()`,
+/// duplicate an already queued tweet or a previous tweet in the file, i.e. have the same author, action and content, and
+/// whether they're scheduled before the specified time.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::{QueuedTweet, check};
+/// # use tweetr::Outcome;
+/// # use chrono::DateTime;
+/// # fn main() {
+/// let tweet = QueuedTweet::new("nabijaczleweli", DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap(), "Capitalism");
+/// let now = DateTime::parse_from_rfc3339("2016-09-08T00:33:30+02:00").unwrap();
+///
+/// assert_eq!(check::check_tweets(&vec![(Some(1), Ok(tweet.clone()))], None, &vec![], now), Ok(()));
+/// assert_eq!(check::check_tweets(&vec![(Some(1), Ok(tweet.clone())), (Some(5), Err("time \"tomorrow\" isn't valid".to_string()))],
+///                                None,
+///                                &vec![tweet.clone()],
+///                                now),
+///            Err(Outcome::InvalidTweets(vec![
+///                "line 1: tweet \"Capitalism\" scheduled for 2016-09-09T00:33:30+02:00 by nabijaczleweli: already queued".to_string(),
+///                "line 5: time \"tomorrow\" isn't valid".to_string(),
+///            ])));
+/// # }
+/// ```
+pub fn check_tweets(tweets: &Vec<(Option<usize>, Result<QueuedTweet, String>)>, known_authors: Option<&Vec<String>>, queued: &Vec<QueuedTweet>,
+                    now: DateTime<FixedOffset>)
+                    -> Result<(), Outcome> {
+    let errors: Vec<_> = tweets.iter()
+        .enumerate()
+        .flat_map(|(i, &(line, ref tweet))| {
+            let location = match line {
+                Some(line) => format!("line {}", line),
+                None => format!("tweet {}", i + 1),
+            };
+
+            let errors = match *tweet {
+                Ok(ref tweet) => {
+                    let invalid_errors = match queue_tweet::validate_tweets(&vec![tweet.clone()], known_authors) {
+                        Err(Outcome::InvalidTweets(errors)) => errors,
+                        _ => vec![],
+                    };
+
+                    let duplicate_error = if queued.iter().any(|qt| is_duplicate(qt, tweet)) {
+                        Some("already queued")
+                    } else if tweets[..i].iter().any(|&(_, ref prev)| prev.as_ref().map(|prev| is_duplicate(prev, tweet)).unwrap_or(false)) {
+                        Some("duplicate of a previous tweet in the file")
+                    } else {
+                        None
+                    };
+                    let past_error = if tweet.time < now { Some("scheduled in the past") } else { None };
+
+                    invalid_errors.into_iter()
+                        .chain(duplicate_error.into_iter()
+                            .chain(past_error)
+                            .map(|err| format!("tweet \"{}\" scheduled for {} by {}: {}", tweet.content, tweet.time.to_rfc3339(), tweet.author, err)))
+                        .collect()
+                }
+                Err(ref err) => vec![err.clone()],
+            };
+            errors.into_iter().map(|err| format!("{}: {}", location, err)).collect::<Vec<_>>()
+        })
+        .collect();
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(Outcome::InvalidTweets(errors))
+    }
+}
+
+
+fn is_duplicate(lhs: &QueuedTweet, rhs: &QueuedTweet) -> bool {
+    lhs.author.eq_ignore_ascii_case(&rhs.author) && lhs.action == rhs.action && lhs.content == rhs.content
+}
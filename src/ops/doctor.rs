@@ -0,0 +1,353 @@
+//! This module contains the functions used only by the `doctor` subsystem.
+//!
+//! The flow of the `doctor` subsystem is as follows:
+//!
+//! ```plaintext
+//! Options::parse()
+//! |> ops::doctor::check_secrets()
+//! |> ops::doctor::check_apps()
+//! |> ops::doctor::check_users()
+//! |> ops::doctor::check_tweets()
+//! |> ops::doctor::check_permissions()
+//! |> ops::doctor::check_tokens()
+//! |> ops::doctor::print_report()
+//! |> ops::doctor::outcome()
+//! ```
+
+
+use self::super::{AppTokens, QueuedTweet, SecretKey, UserGroup, User};
+use self::super::{queue_tweet, start_daemon, verify_users};
+use chrono::{DateTime, FixedOffset};
+use self::super::super::Outcome;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::io::Write;
+
+
+/// How bad a finding is.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Everything's fine.
+    Ok,
+    /// Something will probably go wrong, but not necessarily.
+    Warning,
+    /// Something will go wrong.
+    Error,
+}
+
+/// A single result of a health check.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct Finding {
+    /// How bad it is.
+    pub severity: Severity,
+    /// What was checked, e.g. a file or a user.
+    pub subject: String,
+    /// What was found.
+    pub message: String,
+    /// Further details, e.g. parsing errors or affected tweets.
+    pub details: Vec<String>,
+    /// What to do about it, if anything.
+    pub fix: Option<String>,
+}
+
+impl Finding {
+    /// Create a finding without details or a fix.
+    pub fn new<S: Into<String>, M: Into<String>>(severity: Severity, subject: S, message: M) -> Finding {
+        Finding {
+            severity: severity,
+            subject: subject.into(),
+            message: message.into(),
+            details: vec![],
+            fix: None,
+        }
+    }
+
+    /// Attach the specified details to the finding.
+    pub fn with_details(mut self, details: Vec<String>) -> Finding {
+        self.details = details;
+        self
+    }
+
+    /// Attach the specified fix to the finding.
+    pub fn with_fix<F: Into<String>>(mut self, fix: F) -> Finding {
+        self.fix = Some(fix.into());
+        self
+    }
+}
+
+
+/// Check whether the secrets are encrypted and, if they are, whether they can be unlocked with the specified passphrase.
+///
+/// Returns the key to decrypt the secrets with, or `Err(())` if they're encrypted, but that's impossible.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::doctor::{self, Severity};
+/// # use std::env::temp_dir;
+/// # use std::fs;
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-doctor-check_secrets-0");
+/// fs::create_dir_all(&tf).unwrap();
+///
+/// let (key, findings) = doctor::check_secrets(&tf, None);
+/// assert_eq!(key, Ok(None));
+/// assert_eq!(findings[0].severity, Severity::Ok);
+/// ```
+pub fn check_secrets(config_dir: &PathBuf, passphrase: Option<&str>) -> (Result<Option<SecretKey>, ()>, Vec<Finding>) {
+    if !config_dir.join("secrets.toml").exists() {
+        return (Ok(None),
+                vec![Finding::new(Severity::Ok, "secrets", "stored in plaintext").with_fix("encrypt them with `tweetr rekey`, if needed")]);
+    }
+
+    match SecretKey::unlock(config_dir, passphrase) {
+        Ok(key) => (Ok(key), vec![Finding::new(Severity::Ok, "secrets", "encrypted and unlocked")]),
+        Err(_) if passphrase.is_none() => {
+            (Err(()),
+             vec![Finding::new(Severity::Warning, "secrets", "encrypted, but no passphrase was specified, so the tokens can't be checked")
+                      .with_fix("specify the passphrase with --passphrase-file or $TWEETR_PASSPHRASE")])
+        }
+        Err(out) => {
+            (Err(()),
+             vec![Finding::new(Severity::Error, "secrets", "couldn't be unlocked")
+                      .with_details(outcome_lines(&out))
+                      .with_fix("specify the right passphrase with --passphrase-file or $TWEETR_PASSPHRASE")])
+        }
+    }
+}
+
+/// Check whether the app profiles parse and there's at least one.
+pub fn check_apps(config_dir: &(String, PathBuf), key: Option<&SecretKey>) -> (Option<BTreeMap<Option<String>, AppTokens>>, Vec<Finding>) {
+    match AppTokens::read_profiles(&config_dir.1, key) {
+        Ok((apps, errors)) => {
+            let mut findings: Vec<_> = errors.iter()
+                .map(|out| {
+                    Finding::new(Severity::Error, "app profiles", "failed to read")
+                        .with_details(outcome_lines(out))
+                        .with_fix("correct the app.toml or app-<name>.toml files, or reinitialise them with `tweetr init --force`")
+                })
+                .collect();
+
+            if apps.is_empty() {
+                if errors.is_empty() {
+                    findings.push(Finding::new(Severity::Error, "app profiles", "none were initialised").with_fix("initialise the app with `tweetr init`"));
+                }
+                (None, findings)
+            } else {
+                let names: Vec<_> = apps.keys().map(|p| p.as_ref().map(|p| &p[..]).unwrap_or("default")).collect();
+                findings.insert(0, Finding::new(Severity::Ok, "app profiles", names.join(", ")));
+                (Some(apps), findings)
+            }
+        }
+        Err(out) => (None, vec![Finding::new(Severity::Error, "app profiles", "failed to read").with_details(outcome_lines(&out))]),
+    }
+}
+
+/// Check whether the users data parses, whether the users' apps were initialised, whether any are suspended, and whether
+/// all user groups' members exist.
+pub fn check_users(config_dir: &(String, PathBuf), key: Option<&SecretKey>, apps: Option<&BTreeMap<Option<String>, AppTokens>>)
+                   -> (Option<(Vec<User>, Vec<UserGroup>)>, Vec<Finding>) {
+    let users_path = queue_tweet::users_path(&config_dir.1);
+    if !users_path.exists() {
+        return (None, vec![Finding::new(Severity::Error, "users.toml", "doesn't exist").with_fix("add a user with `tweetr add-user`")]);
+    }
+
+    let (users, groups) = match (User::read(&users_path, key), UserGroup::read(&users_path)) {
+        (Ok(users), Ok(groups)) => (users, groups),
+        (Err(out), _) | (_, Err(out)) => {
+            return (None,
+                    vec![Finding::new(Severity::Error, "users.toml", "failed to parse")
+                             .with_details(out.map(|o| outcome_lines(&o)).unwrap_or_default())
+                             .with_fix("correct users.toml")])
+        }
+    };
+
+    let mut findings = vec![Finding::new(Severity::Ok, "users.toml", format!("{} users, {} groups", users.len(), groups.len()))];
+    for user in &users {
+        if let Some(apps) = apps {
+            if let Err(out) = AppTokens::for_user(apps, user) {
+                findings.push(Finding::new(Severity::Error, format!("user {}", user.name), "its app wasn't initialised")
+                    .with_details(outcome_lines(&out))
+                    .with_fix(match user.app {
+                        Some(ref app) => format!("initialise it with `tweetr init --app {}`", app),
+                        None => "initialise it with `tweetr init`".to_string(),
+                    }));
+            }
+        }
+        if let Some(ref suspension) = user.suspended {
+            findings.push(Finding::new(Severity::Warning,
+                                       format!("user {}", user.name),
+                                       format!("suspended at {}: {}", suspension.time.to_rfc3339(), suspension.reason))
+                .with_fix(format!("re-authorise it with `tweetr reauth-user {}`", user.name)));
+        }
+    }
+    for group in &groups {
+        let missing: Vec<_> = group.members.iter().filter(|m| !users.iter().any(|u| u.name.eq_ignore_ascii_case(m))).cloned().collect();
+        if !missing.is_empty() {
+            findings.push(Finding::new(Severity::Error, format!("group {}", group.name), format!("has unknown members {}", missing.join(", ")))
+                .with_fix("add them with `tweetr add-user` or remove them from the group in users.toml"));
+        }
+    }
+
+    (Some((users, groups)), findings)
+}
+
+/// Check whether the tweet queue parses, whether the unposted tweets are valid, i.e. by known authors, if specified, and not
+/// too long, and whether any are overdue as of the specified time.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::doctor::{self, Severity};
+/// # use std::env::temp_dir;
+/// # use chrono::DateTime;
+/// # use std::fs;
+/// # fn main() {
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-doctor-check_tweets-0");
+/// let _ = fs::remove_dir_all(&tf);
+/// fs::create_dir_all(&tf).unwrap();
+///
+/// let findings = doctor::check_tweets(&("$TEMP/ops-doctor-check_tweets-0".to_string(), tf),
+///                                     None,
+///                                     DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap());
+/// assert_eq!(findings.len(), 1);
+/// assert_eq!(findings[0].severity, Severity::Warning);
+/// # }
+/// ```
+pub fn check_tweets(config_dir: &(String, PathBuf), known_authors: Option<&Vec<String>>, now: DateTime<FixedOffset>) -> Vec<Finding> {
+    let tweets_path = queue_tweet::tweets_path(&config_dir.1);
+    if !tweets_path.exists() {
+        return vec![Finding::new(Severity::Warning, "tweets.toml", "doesn't exist, so nothing will be posted")
+                        .with_fix("queue tweets with `tweetr queue-tweet`")];
+    }
+
+    let tweets = match QueuedTweet::read(&tweets_path) {
+        Ok(tweets) => tweets,
+        Err(out) => {
+            return vec![Finding::new(Severity::Error, "tweets.toml", "failed to parse")
+                            .with_details(out.map(|o| outcome_lines(&o)).unwrap_or_default())
+                            .with_fix("correct tweets.toml")]
+        }
+    };
+    let unposted: Vec<_> = tweets.iter().filter(|t| t.id.is_none() && t.failed.is_none()).cloned().collect();
+
+    let mut findings = vec![Finding::new(Severity::Ok, "tweets.toml", format!("{} tweets, {} unposted", tweets.len(), unposted.len()))];
+    if let Err(Outcome::InvalidTweets(errors)) = queue_tweet::validate_tweets(&unposted, known_authors) {
+        findings.push(Finding::new(Severity::Error, "tweets.toml", format!("{} problems with unposted tweets", errors.len()))
+            .with_details(errors)
+            .with_fix("correct the tweets in tweets.toml, or add their authors with `tweetr add-user`"));
+    }
+
+    let overdue: Vec<_> = unposted.iter()
+        .filter(|t| t.time < now)
+        .map(|t| format!("\"{}\" scheduled for {} by {}", t.content, t.time.to_rfc3339(), t.author))
+        .collect();
+    if !overdue.is_empty() {
+        findings.push(Finding::new(Severity::Warning, "tweets.toml", format!("{} unposted tweets are overdue", overdue.len()))
+            .with_details(overdue)
+            .with_fix("start the daemon with `tweetr start-daemon`, or move them with `tweetr reschedule` or `tweetr respread`"));
+    }
+
+    findings
+}
+
+/// Check whether the files containing secrets are accessible only to their owner.
+pub fn check_permissions(config_dir: &(String, PathBuf)) -> Finding {
+    match start_daemon::check_permissions(config_dir) {
+        Outcome::InsecurePermissions(files) => {
+            Finding::new(Severity::Error, "permissions", "files containing secrets are accessible to other users")
+                .with_details(files)
+                .with_fix("restrict access to them with `chmod 600`")
+        }
+        _ => Finding::new(Severity::Ok, "permissions", "files containing secrets are accessible only to their owner"),
+    }
+}
+
+/// Check whether the access tokens of the specified users that aren't suspended still work, printing progress if verbose.
+pub fn check_tokens<W: Write>(users: &Vec<User>, apps: &BTreeMap<Option<String>, AppTokens>, verbose: bool, output: &mut W) -> Vec<Finding> {
+    users.iter()
+        .filter(|u| u.suspended.is_none())
+        .map(|user| {
+            let subject = format!("user {}", user.name);
+            let result = AppTokens::for_user(apps, user).and_then(|app| app.token()).and_then(|app| if verbose {
+                verify_users::verify_user(user, &app, verbose, output)
+            } else {
+                verify_users::verify_user(user, &app, verbose, &mut vec![])
+            });
+
+            match result {
+                Ok(true) => Finding::new(Severity::Ok, subject, "access tokens work"),
+                Ok(false) => {
+                    Finding::new(Severity::Error, subject, "access tokens were revoked")
+                        .with_fix(format!("re-authorise it with `tweetr reauth-user {}`", user.name))
+                }
+                Err(out) => {
+                    Finding::new(Severity::Warning, subject, "couldn't check access tokens")
+                        .with_details(outcome_lines(&out))
+                        .with_fix("check the network connection and run `tweetr verify-users`")
+                }
+            }
+        })
+        .collect()
+}
+
+/// Print the specified findings, each with its details and fix.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::doctor::{self, Finding, Severity};
+/// # use std::iter::FromIterator;
+/// let mut out = Vec::new();
+/// doctor::print_report(&mut out,
+///                      &vec![Finding::new(Severity::Ok, "app profiles", "default"),
+///                            Finding::new(Severity::Error, "users.toml", "doesn't exist").with_fix("add a user with `tweetr add-user`")]);
+/// assert_eq!(String::from_iter(out.iter().map(|&i| i as char)),
+///            "OK      app profiles: default\n\
+///             ERROR   users.toml: doesn't exist\n        \
+///                     Fix: add a user with `tweetr add-user`\n");
+/// ```
+pub fn print_report<W: Write>(output: &mut W, findings: &Vec<Finding>) {
+    for finding in findings {
+        let severity = match finding.severity {
+            Severity::Ok => "OK",
+            Severity::Warning => "WARNING",
+            Severity::Error => "ERROR",
+        };
+
+        writeln!(output, "{:7} {}: {}", severity, finding.subject, finding.message).unwrap();
+        for detail in &finding.details {
+            writeln!(output, "          {}", detail).unwrap();
+        }
+        if finding.severity != Severity::Ok {
+            if let Some(ref fix) = finding.fix {
+                writeln!(output, "        Fix: {}", fix).unwrap();
+            }
+        }
+    }
+}
+
+/// Get the outcome of the specified findings, i.e. an error if any of them are errors.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::doctor::{self, Finding, Severity};
+/// # use tweetr::Outcome;
+/// assert_eq!(doctor::outcome(&vec![Finding::new(Severity::Warning, "tweets.toml", "doesn't exist")]), Outcome::NoError);
+/// assert_eq!(doctor::outcome(&vec![Finding::new(Severity::Error, "users.toml", "doesn't exist")]), Outcome::ConfigurationProblems(1));
+/// ```
+pub fn outcome(findings: &Vec<Finding>) -> Outcome {
+    match findings.iter().filter(|f| f.severity == Severity::Error).count() {
+        0 => Outcome::NoError,
+        errors => Outcome::ConfigurationProblems(errors),
+    }
+}
+
+
+fn outcome_lines(out: &Outcome) -> Vec<String> {
+    let mut buf = vec![];
+    out.print_error(&mut buf);
+    String::from_utf8_lossy(&buf).lines().map(|l| l.trim().to_string()).collect()
+}
@@ -0,0 +1,172 @@
+//! Pools of evergreen tweets to recycle when an author's queue runs dry
+//!
+//! Serialised the same way as `QueuedTweet`s are, i.e. via `EvergreenPoolForSerialisation`s, which have `DateTime`s
+//! converted to RFC-3339 `String`s and the selection mode converted to its name.
+
+
+use self::super::super::util::create_private_file;
+use chrono::{DateTime, FixedOffset, ParseError};
+use self::super::super::Outcome;
+use self::super::read_toml_file;
+use std::iter::FromIterator;
+use toml::encode_str;
+use std::path::Path;
+use std::io::Write;
+
+
+/// A pool of evergreen tweets belonging to a single author, along with the rules for recycling them.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct EvergreenPool {
+    /// The author to post on behalf of.
+    ///
+    /// Has to have been previously added via the `add-user` subsystem.
+    pub author: String,
+    /// Minimum amount of days before the same tweet can be reposted.
+    pub min_days_between_repeats: u64,
+    /// How to pick the next tweet to repost.
+    pub selection: EvergreenSelection,
+    /// Maximum amount of recycled tweets to post in any 24 hours.
+    ///
+    /// Recycled tweets are also spread out evenly, i.e. no more often than every `24h / max_per_day`.
+    pub max_per_day: u64,
+
+    /// The tweets to choose from.
+    pub tweets: Vec<EvergreenTweet>,
+}
+
+/// A single tweet in an evergreen pool.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct EvergreenTweet {
+    /// The string content of the tweet.
+    pub content: String,
+    /// The last time this tweet was posted, if ever.
+    pub last_posted: Option<DateTime<FixedOffset>>,
+}
+
+/// The way of choosing the next tweet to repost from an evergreen pool.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum EvergreenSelection {
+    /// Pick any eligible tweet at random.
+    Random,
+    /// Pick the eligible tweet that was posted the longest time ago, never-posted ones first.
+    RoundRobin,
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+struct EvergreenPoolForSerialisation {
+    pub author: String,
+    pub min_days_between_repeats: u64,
+    pub selection: String,
+    pub max_per_day: u64,
+
+    pub tweet: Vec<EvergreenTweetForSerialisation>,
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+struct EvergreenTweetForSerialisation {
+    pub content: String,
+    pub last_posted: Option<String>,
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+struct EvergreenPools {
+    pool: Vec<EvergreenPoolForSerialisation>,
+}
+
+
+impl EvergreenPool {
+    /// Read all evergreen pools from the specified file.
+    pub fn read(p: &Path) -> Result<Vec<EvergreenPool>, Option<Outcome>> {
+        let pools: EvergreenPools = try!(read_toml_file(p, "evergreen pools"));
+        Result::from_iter(pools.pool.into_iter().map(|ep| ep.into()).collect::<Vec<_>>()).map_err(|_| None)
+    }
+
+    /// Save all evergreen pools to the specified file.
+    pub fn write(pools: Vec<EvergreenPool>, p: &Path) {
+        create_private_file(p)
+            .unwrap()
+            .write_all(encode_str(&EvergreenPools { pool: pools.into_iter().map(EvergreenPoolForSerialisation::from).collect() }).as_bytes())
+            .unwrap();
+    }
+}
+
+impl EvergreenSelection {
+    /// Get the selection mode corresponding to the specified name, as used in the pool file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::EvergreenSelection;
+    /// assert_eq!(EvergreenSelection::from_name("random"), Some(EvergreenSelection::Random));
+    /// assert_eq!(EvergreenSelection::from_name("round-robin"), Some(EvergreenSelection::RoundRobin));
+    /// assert_eq!(EvergreenSelection::from_name("sequential"), None);
+    /// ```
+    pub fn from_name(name: &str) -> Option<EvergreenSelection> {
+        match name {
+            "random" => Some(EvergreenSelection::Random),
+            "round-robin" => Some(EvergreenSelection::RoundRobin),
+            _ => None,
+        }
+    }
+
+    /// Get the name of the selection mode, as used in the pool file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::EvergreenSelection;
+    /// assert_eq!(EvergreenSelection::RoundRobin.name(), "round-robin");
+    /// ```
+    pub fn name(&self) -> &'static str {
+        match *self {
+            EvergreenSelection::Random => "random",
+            EvergreenSelection::RoundRobin => "round-robin",
+        }
+    }
+}
+
+
+impl From<EvergreenPool> for EvergreenPoolForSerialisation {
+    fn from(ep: EvergreenPool) -> EvergreenPoolForSerialisation {
+        EvergreenPoolForSerialisation {
+            author: ep.author,
+            min_days_between_repeats: ep.min_days_between_repeats,
+            selection: ep.selection.name().to_string(),
+            max_per_day: ep.max_per_day,
+            tweet: ep.tweets.into_iter().map(EvergreenTweetForSerialisation::from).collect(),
+        }
+    }
+}
+
+impl From<EvergreenTweet> for EvergreenTweetForSerialisation {
+    fn from(et: EvergreenTweet) -> EvergreenTweetForSerialisation {
+        EvergreenTweetForSerialisation {
+            content: et.content,
+            last_posted: et.last_posted.map(|dt| dt.to_rfc3339()),
+        }
+    }
+}
+
+impl Into<Result<EvergreenPool, ()>> for EvergreenPoolForSerialisation {
+    fn into(self) -> Result<EvergreenPool, ()> {
+        Ok(EvergreenPool {
+            author: self.author,
+            min_days_between_repeats: self.min_days_between_repeats,
+            selection: try!(EvergreenSelection::from_name(&self.selection).ok_or(())),
+            max_per_day: self.max_per_day,
+            tweets: try!(Result::from_iter(self.tweet.into_iter().map(|et| et.into()).collect::<Vec<Result<_, ParseError>>>()).map_err(|_| ())),
+        })
+    }
+}
+
+impl Into<Result<EvergreenTweet, ParseError>> for EvergreenTweetForSerialisation {
+    fn into(self) -> Result<EvergreenTweet, ParseError> {
+        Ok(EvergreenTweet {
+            content: self.content,
+            last_posted: match self.last_posted {
+                Some(dts) => Some(try!(DateTime::parse_from_rfc3339(&dts))),
+                None => None,
+            },
+        })
+    }
+}
@@ -5,21 +5,24 @@
 //! ```plaintext
 //! Options::parse()
 //! |> ops::init::verify()
-//! |> ops::init::get_data()
+//! |> ops::init::get_data() or ops::init::data_from_json()
+//! |> ops::init::validate_data()
 //! |> ops::AppTokens::write()
 //! ```
 
 
-use self::super::super::util::prompt_exact_len;
+use self::super::super::util::prompt_nonzero_len;
 use self::super::{AppTokens, verify_file};
+use std::io::{BufRead, Write, Read};
 use self::super::super::Outcome;
-use std::io::{BufRead, Write};
+use rustc_serialize::json;
 use std::path::PathBuf;
 
 
 /// Verify if, given the current configuration, it's permitted to continue with the subsequent steps of the `init` subsystem.
 ///
-/// The return value contains either the path to the file containing the global app configuration or why getting it failed.
+/// The return value contains either the path to the file containing the global app configuration, or the specified app
+/// profile's (see `AppTokens`), or why getting it failed.
 ///
 /// # Examples
 ///
@@ -29,8 +32,10 @@ use std::path::PathBuf;
 /// # use std::env::temp_dir;
 /// # use tweetr::ops::init;
 /// let tf = temp_dir().join("tweetr-doctest").join("ops-init-verify-0");
-/// assert_eq!(init::verify(&("$TEMP/ops-init-verify-0".to_string(), tf.clone()), true),
+/// assert_eq!(init::verify(&("$TEMP/ops-init-verify-0".to_string(), tf.clone()), None, true),
 ///            Ok(tf.join("app.toml")));
+/// assert_eq!(init::verify(&("$TEMP/ops-init-verify-0".to_string(), tf.clone()), Some("staging"), false),
+///            Ok(tf.join("app-staging.toml")));
 /// ```
 ///
 /// Verifying an existing file without forcing.
@@ -45,15 +50,17 @@ use std::path::PathBuf;
 /// fs::create_dir_all(&tf).unwrap();
 /// File::create(tf.join("app.toml")).unwrap().write(&[]).unwrap();
 ///
-/// assert_eq!(init::verify(&("$TEMP/ops-init-verify-1".to_string(), tf), false),
+/// assert_eq!(init::verify(&("$TEMP/ops-init-verify-1".to_string(), tf), None, false),
 ///            Err(Outcome::OverrideNoForce("$TEMP/ops-init-verify-1/app.toml".to_string())));
 /// ```
-pub fn verify(config_dir: &(String, PathBuf), force: bool) -> Result<PathBuf, Outcome> {
-    verify_file("app.toml", false, config_dir, force, "")
+pub fn verify(config_dir: &(String, PathBuf), profile: Option<&str>, force: bool) -> Result<PathBuf, Outcome> {
+    verify_file(&AppTokens::profile_file_name(profile), false, config_dir, force, "")
 }
 
 /// Prompt the user for application data.
 ///
+/// Only letters and digits are accepted, anything else is reprompted for.
+///
 /// # Examples
 ///
 /// ```
@@ -66,11 +73,83 @@ pub fn verify(config_dir: &(String, PathBuf), force: bool) -> Result<PathBuf, Ou
 ///            AppTokens {
 ///                key: "qdPD7N8CcPYDKiNv81QWNWaHK".to_string(),
 ///                secret: "U9A5CM1LzwNliBiHGPIJyx6tFYAGVr3bCMbVkWKu8Zb13kHD4p".to_string(),
+///                secret_command: None,
 ///            });
 /// ```
 pub fn get_data<R: BufRead, W: Write>(input: &mut R, output: &mut W) -> AppTokens {
     AppTokens {
-        key: prompt_exact_len(input, output, "App key", |_| true, 25).unwrap(),
-        secret: prompt_exact_len(input, output, "App secret", |_| true, 50).unwrap(),
+        key: prompt_nonzero_len(input, output, "App key", |s| is_token_part(s)).unwrap(),
+        secret: prompt_nonzero_len(input, output, "App secret", |s| is_token_part(s)).unwrap(),
+        secret_command: None,
+    }
+}
+
+/// Read application data from a JSON object with `key` and `secret` strings.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::{init, AppTokens};
+/// assert_eq!(init::data_from_json(&mut &br#"{"key": "qdPD7N8CcPYDKiNv81QWNWaHK",
+///                                            "secret": "U9A5CM1LzwNliBiHGPIJyx6tFYAGVr3bCMbVkWKu8Zb13kHD4p"}"#[..]),
+///            Ok(AppTokens {
+///                key: "qdPD7N8CcPYDKiNv81QWNWaHK".to_string(),
+///                secret: "U9A5CM1LzwNliBiHGPIJyx6tFYAGVr3bCMbVkWKu8Zb13kHD4p".to_string(),
+///                secret_command: None,
+///            }));
+/// assert!(init::data_from_json(&mut &br#"{"key": "qdPD7N8CcPYDKiNv81QWNWaHK"}"#[..]).is_err());
+/// ```
+pub fn data_from_json<R: Read>(input: &mut R) -> Result<AppTokens, Outcome> {
+    let mut buf = String::new();
+    let parsed = input.read_to_string(&mut buf).map_err(|e| e.to_string()).and_then(|_| json::decode(&buf).map_err(|e| e.to_string()));
+    parsed.map_err(|e| {
+        Outcome::FileParsingFailed {
+            desc: "application tokens JSON",
+            errors: vec![e],
+        }
+    })
+}
+
+/// Check whether the specified application data looks like a valid Twitter consumer key and secret.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::{init, AppTokens};
+/// # use tweetr::Outcome;
+/// assert_eq!(init::validate_data(&AppTokens {
+///                key: "qdPD7N8CcPYDKiNv81QWNWaHK".to_string(),
+///                secret: "U9A5CM1LzwNliBiHGPIJyx6tFYAGVr3bCMbVkWKu8Zb13kHD4p".to_string(),
+///                secret_command: None,
+///            }),
+///            Ok(()));
+/// assert_eq!(init::validate_data(&AppTokens {
+///                key: "qdPD7N8CcPYDKiNv81QWNWaHK ".to_string(),
+///                secret: "".to_string(),
+///                secret_command: None,
+///            }),
+///            Err(Outcome::InvalidCredentials(vec!["app key \"qdPD7N8CcPYDKiNv81QWNWaHK \" can only contain letters and digits"
+///                                                     .to_string(),
+///                                                 "app secret is empty".to_string()])));
+/// ```
+pub fn validate_data(tokens: &AppTokens) -> Result<(), Outcome> {
+    let mut errors = vec![];
+    for &(what, val) in &[("app key", &tokens.key), ("app secret", &tokens.secret)] {
+        if val.is_empty() {
+            errors.push(format!("{} is empty", what));
+        } else if !is_token_part(val) {
+            errors.push(format!("{} \"{}\" can only contain letters and digits", what, val));
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(Outcome::InvalidCredentials(errors))
     }
 }
+
+
+fn is_token_part(s: &str) -> bool {
+    s.chars().all(|c| c.is_ascii() && c.is_alphanumeric())
+}
@@ -0,0 +1,109 @@
+//! This module contains the functions used only by the `list-users` subsystem.
+//!
+//! The flow of the `list-users` subsystem is as follows:
+//!
+//! ```plaintext
+//! Options::parse()
+//! |> ops::list_users::verify()
+//! |> ops::User::read()
+//! |> ops::UserGroup::read()
+//! |> ops::list_users::print_users()
+//! ```
+
+
+use self::super::{User, UserGroup, verify_file};
+use self::super::super::Outcome;
+use std::path::PathBuf;
+use std::io::Write;
+
+
+/// Verify if, given the current configuration, it's permitted to continue with the subsequent steps of the `list-users`
+/// subsystem.
+///
+/// The return value contains either the path to the file containing the global users data or why getting it failed.
+///
+/// # Examples
+///
+/// Verifying when the global users data doesn't exist.
+///
+/// ```
+/// # use tweetr::ops::list_users;
+/// # use std::env::temp_dir;
+/// # use tweetr::Outcome;
+/// # use std::fs;
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-list-users-verify-0");
+/// fs::create_dir_all(&tf).unwrap();
+///
+/// assert_eq!(list_users::verify(&("$TEMP/ops-list-users-verify-0".to_string(), tf)),
+///            Err(Outcome::RequiredFileFromSubsystemNonexistant {
+///                subsys: "add-user",
+///                fname: "$TEMP/ops-list-users-verify-0/users.toml".to_string(),
+///            }));
+/// ```
+pub fn verify(config_dir: &(String, PathBuf)) -> Result<PathBuf, Outcome> {
+    verify_file("users.toml", true, config_dir, false, "add-user")
+}
+
+/// Print the names, IDs, apps, posting limits and suspensions of the specified users, followed by the specified groups.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::{User, UserGroup, list_users};
+/// # use std::iter::FromIterator;
+/// let mut out = Vec::new();
+/// list_users::print_users(&mut out,
+///                         &vec![User {
+///                                   min_minutes_between_posts: Some(30),
+///                                   jitter_minutes: Some(5),
+///                                   ..User::new("nabijaczleweli",
+///                                               0x81423,
+///                                               "529443-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4",
+///                                               "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5")
+///                               }],
+///                         &vec![UserGroup {
+///                                   name: "everyone".to_string(),
+///                                   members: vec!["nabijaczleweli".to_string()],
+///                                   stagger_minutes: Some(10),
+///                               }]);
+/// assert_eq!(String::from_iter(out.iter().map(|&i| i as char)),
+///            "nabijaczleweli#529443 (at least 30 minutes between posts, 5 minutes of jitter)\n\
+///             Group everyone: nabijaczleweli (10 minutes apart)\n");
+/// ```
+pub fn print_users<W: Write>(output: &mut W, users: &Vec<User>, groups: &Vec<UserGroup>) {
+    if users.is_empty() {
+        writeln!(output, "No users added.").unwrap();
+    }
+
+    for user in users {
+        let mut limits = vec![];
+        if let Some(ref app) = user.app {
+            limits.push(format!("app {}", app));
+        }
+        if let Some(min) = user.min_minutes_between_posts {
+            limits.push(format!("at least {} minutes between posts", min));
+        }
+        if let Some(max) = user.max_posts_per_day {
+            limits.push(format!("at most {} posts per day", max));
+        }
+        if let Some(jitter) = user.jitter_minutes {
+            limits.push(format!("{} minutes of jitter", jitter));
+        }
+
+        if limits.is_empty() {
+            writeln!(output, "{}#{}", user.name, user.id).unwrap();
+        } else {
+            writeln!(output, "{}#{} ({})", user.name, user.id, limits.join(", ")).unwrap();
+        }
+        if let Some(ref suspension) = user.suspended {
+            writeln!(output, "  Suspended at {}: {}", suspension.time.to_rfc3339(), suspension.reason).unwrap();
+        }
+    }
+
+    for group in groups {
+        match group.stagger_minutes {
+            Some(stagger) => writeln!(output, "Group {}: {} ({} minutes apart)", group.name, group.members.join(", "), stagger).unwrap(),
+            None => writeln!(output, "Group {}: {}", group.name, group.members.join(", ")).unwrap(),
+        }
+    }
+}
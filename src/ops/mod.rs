@@ -3,25 +3,41 @@
 //! Each module contains the functions for their respective subsystems.
 
 
-use toml::{Parser, Value, decode};
+use toml::{Parser, Table, Value, decode};
 use rustc_serialize::Decodable;
 use std::path::{PathBuf, Path};
 use self::super::Outcome;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::Read;
 
 mod user;
 mod token;
+mod secret_key;
 mod queued_tweet;
+mod evergreen_pool;
+mod tweet_selector;
 
 pub mod init;
 pub mod add_user;
 pub mod queue_tweet;
 pub mod start_daemon;
+pub mod reschedule;
+pub mod respread;
+pub mod refresh_users;
+pub mod list_users;
+pub mod remove_user;
+pub mod reauth_user;
+pub mod verify_users;
+pub mod rekey;
+pub mod doctor;
+pub mod check;
 
-pub use self::user::User;
+pub use self::user::{User, UserGroup, Suspension};
 pub use self::token::AppTokens;
-pub use self::queued_tweet::QueuedTweet;
+pub use self::secret_key::SecretKey;
+pub use self::queued_tweet::{QueuedTweet, TweetAction, TweetTarget, ProfileUpdate, Recipient, Poll};
+pub use self::evergreen_pool::{EvergreenSelection, EvergreenTweet, EvergreenPool};
+pub use self::tweet_selector::TweetSelector;
 
 
 fn verify_file(fname: &str, should_exist: bool, config_dir: &(String, PathBuf), force: bool, producing_subsystem: &'static str) -> Result<PathBuf, Outcome> {
@@ -43,14 +59,37 @@ fn verify_file(fname: &str, should_exist: bool, config_dir: &(String, PathBuf),
     }
 }
 
+fn verify_app_profiles(config_dir: &(String, PathBuf)) -> Result<(), Outcome> {
+    let initialised = match fs::read_dir(&config_dir.1) {
+        Ok(entries) => entries.filter_map(|e| e.ok()).any(|e| e.file_name().to_str().and_then(AppTokens::profile_of_file_name).is_some()),
+        Err(_) => false,
+    };
+
+    if initialised {
+        Ok(())
+    } else {
+        verify_file("app.toml", true, config_dir, false, "init").map(|_| ())
+    }
+}
+
 fn read_toml_file<T: Decodable>(p: &Path, desc: &'static str) -> Result<T, Option<Outcome>> {
+    let (_, table) = try!(read_toml_table(p, desc));
+    decode(Value::Table(table)).ok_or_else(|| {
+        Some(Outcome::FileParsingFailed {
+            desc: desc,
+            errors: vec![],
+        })
+    })
+}
+
+fn read_toml_table(p: &Path, desc: &'static str) -> Result<(String, Table), Option<Outcome>> {
     let mut buf = String::new();
     try!(try!(File::open(p).map_err(|_| None)).read_to_string(&mut buf).map_err(|_| None));
 
     let mut parser = Parser::new(&buf);
-    let parsed = parser.parse().and_then(|t| decode(Value::Table(t)));
-    parsed.ok_or_else(move || {
-        Some(Outcome::FileParsingFailed {
+    match parser.parse() {
+        Some(table) => Ok((buf.clone(), table)),
+        None => Err(Some(Outcome::FileParsingFailed {
             desc: desc,
             errors: parser.errors
                 .iter()
@@ -59,6 +98,6 @@ fn read_toml_file<T: Decodable>(p: &Path, desc: &'static str) -> Result<T, Optio
                     format!("error: {}:{}: {}", line, col, e.desc)
                 })
                 .collect(),
-        })
-    })
+        })),
+    }
 }
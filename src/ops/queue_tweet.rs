@@ -5,7 +5,14 @@
 //! ```plaintext
 //! Options::parse()
 //! |> ops::queue_tweet::tweets_path()
+//! |> ops::queue_tweet::users_path()
+//! |> ops::User::read()
+//! |> ops::UserGroup::read()
+//! |> ops::queue_tweet::known_authors()
 //! |> ops::queue_tweet::get_tweet()
+//! |> ops::queue_tweet::validate_tweets()
+//! |> ops::queue_tweet::bind_author()
+//! |> ops::queue_tweet::apply_jitter()
 //! |> ops::QueuedTweet::read()
 //! |> ops::QueuedTweet::write()
 //! ```
@@ -16,15 +23,23 @@
 //! Options::parse()
 //! |> ops::queue_tweet::tweets_path()
 //! |> ops::QueuedTweet::read()
+//! |> ops::queue_tweet::users_path()
+//! |> ops::User::read()
+//! |> ops::UserGroup::read()
+//! |> ops::queue_tweet::known_authors()
+//! |> ops::queue_tweet::validate_tweets()
+//! |> ops::queue_tweet::bind_author()
+//! |> ops::queue_tweet::apply_jitter()
 //! |> ops::QueuedTweet::read()
 //! |> ops::QueuedTweet::write()
 //! ```
 
-use self::super::super::util::{prompt_any_len, prompt_nonzero_len, prompt_multiline, parse_relative_time};
-use chrono::{ParseResult, FixedOffset, DateTime, Duration, Local};
+use self::super::super::util::{prompt_suggesting, prompt_nonzero_len, prompt_multiline, closest_match, parse_time};
+use self::super::{QueuedTweet, TweetAction, UserGroup, User};
+use self::super::super::Outcome;
 use std::path::{PathBuf, Path};
 use std::io::{BufRead, Write};
-use self::super::QueuedTweet;
+use chrono::Duration;
 
 
 /// Get the path to the file containing the global tweet queue.
@@ -41,8 +56,27 @@ pub fn tweets_path(config_dir: &Path) -> PathBuf {
     config_dir.join("tweets.toml")
 }
 
+/// Get the path to the file containing the global users data.
+///
+/// Queueing tweets doesn't require it to exist.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::queue_tweet;
+/// # use std::env::temp_dir;
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-queue_tweets-users_path-0");
+/// assert_eq!(queue_tweet::users_path(&tf), tf.join("users.toml"));
+/// ```
+pub fn users_path(config_dir: &Path) -> PathBuf {
+    config_dir.join("users.toml")
+}
+
 /// Prompt the user for application data.
 ///
+/// If known authors are specified, others are rejected, with a suggestion if there's a close match, and they can be
+/// suggested by entering a prefix followed by a tab.
+///
 /// # Examples
 ///
 /// Queueing a tweet.
@@ -50,55 +84,238 @@ pub fn tweets_path(config_dir: &Path) -> PathBuf {
 /// ```
 /// # extern crate tweetr;
 /// # extern crate chrono;
-/// # use tweetr::ops::{queue_tweet, QueuedTweet};
+/// # use tweetr::ops::{QueuedTweet, queue_tweet};
 /// # use std::io::BufReader;
 /// # use chrono::DateTime;
 /// # fn main() {
 /// assert_eq!(queue_tweet::get_tweet(&mut BufReader::new(b"tweetr_test\n\
 ///                                   Test tweet\n\
 ///                                   2016-09-09T00:33:30+02:00\n" as &[u8]),
-///                                   &mut Vec::new()),
-///            Some(QueuedTweet {
-///                author: "tweetr_test".to_string(),
-///                time: DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap(),
-///                content: "Test tweet".to_string(),
-///                time_posted: None,
-///                id: None,
-///            }));
+///                                   &mut Vec::new(),
+///                                   None),
+///            Some(QueuedTweet::new("tweetr_test", DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap(), "Test tweet")));
 /// # }
 /// ```
 ///
+/// Rejecting an unknown author.
+///
+/// ```
+/// # use tweetr::ops::queue_tweet;
+/// # use std::io::BufReader;
+/// let mut out = Vec::new();
+/// let tweet = queue_tweet::get_tweet(&mut BufReader::new(b"tweetr_tset\n\
+///                                                         tweetr_test\n\
+///                                                         Test tweet\n\
+///                                                         now\n" as &[u8]),
+///                                    &mut out,
+///                                    Some(&vec!["tweetr_test".to_string()]));
+/// assert_eq!(tweet.unwrap().author, "tweetr_test");
+/// assert!(String::from_utf8(out).unwrap().contains("did you mean \"tweetr_test\"?"));
+/// ```
+///
 /// Not queueing a tweet.
 ///
 /// ```
-/// # use tweetr::ops::{queue_tweet, QueuedTweet};
+/// # use tweetr::ops::queue_tweet;
 /// # use std::io::BufReader;
-/// assert_eq!(queue_tweet::get_tweet(&mut BufReader::new(b"\n" as &[u8]), &mut Vec::new()), None);
+/// assert_eq!(queue_tweet::get_tweet(&mut BufReader::new(b"\n" as &[u8]), &mut Vec::new(), None), None);
 /// ```
-pub fn get_tweet<R: BufRead, W: Write>(input: &mut R, output: &mut W) -> Option<QueuedTweet> {
-    prompt_any_len(input, output, "Author (or empty to finish)", |_| true).unwrap().map(|uname| {
+pub fn get_tweet<R: BufRead, W: Write>(input: &mut R, output: &mut W, known_authors: Option<&Vec<String>>) -> Option<QueuedTweet> {
+    let no_authors = vec![];
+    let mut author = None;
+    while author.is_none() {
+        match prompt_suggesting(input, output, "Author (or empty to finish)", known_authors.unwrap_or(&no_authors)).unwrap() {
+            Some(uname) => {
+                match known_authors.and_then(|ka| unknown_author(&uname, ka)) {
+                    Some(err) => writeln!(output, "{}.", err).unwrap(),
+                    None => author = Some(uname),
+                }
+            }
+            None => return None,
+        }
+    }
+
+    author.map(|uname| {
         let content = prompt_multiline(input, output, "Tweet content", |s| !s.trim().is_empty()).unwrap();
-        let time = prompt_nonzero_len(input,
-                                      output,
-                                      "Time to post the tweet (RFC2822, RFC3339 or relative)",
-                                      |s| DateTime::parse_from_rfc2822(s).is_ok() || DateTime::parse_from_rfc3339(s).is_ok() || parse_relative_time(s).is_ok())
-            .unwrap();
+        let time = prompt_nonzero_len(input, output, "Time to post the tweet (RFC2822, RFC3339 or relative)", |s| parse_time(s).is_ok()).unwrap();
 
         writeln!(output, "").unwrap();
-        QueuedTweet {
-            author: uname,
-            time: DateTime::parse_from_rfc2822(&time)
-                .or_else(|_| DateTime::parse_from_rfc3339(&time))
-                .or_else(|_| {
-                    let now = Local::now();
-                    let now = now.with_timezone(now.offset());
-
-                    Ok(now + Duration::from_std(parse_relative_time(&time).unwrap()).unwrap()) as ParseResult<DateTime<FixedOffset>>
-                })
-                .unwrap(),
-            content: content,
-            time_posted: None,
-            id: None,
-        }
+        QueuedTweet::new(uname, parse_time(&time).unwrap(), content)
     })
 }
+
+/// Check whether the specified tweets can be queued, returning everything wrong with them if they can't.
+///
+/// Tweets whose content is longer than their action allows or with invalid polls can't be queued. Neither can tweets by
+/// authors other than the known ones, if those are specified.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::{QueuedTweet, Poll, queue_tweet};
+/// # use tweetr::util::mul_str;
+/// # use chrono::DateTime;
+/// # fn main() {
+/// let mut tweet = QueuedTweet {
+///     poll: Some(Poll {
+///         options: vec!["Tabs".to_string(), "Spaces".to_string()],
+///         duration_minutes: 60,
+///     }),
+///     ..QueuedTweet::new("tweetr_test", DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap(), "Tabs or spaces?")
+/// };
+/// assert!(queue_tweet::validate_tweets(&vec![tweet.clone()], None).is_ok());
+/// assert!(queue_tweet::validate_tweets(&vec![tweet.clone()], Some(&vec!["tweetr_test".to_string()])).is_ok());
+/// assert!(queue_tweet::validate_tweets(&vec![tweet.clone()], Some(&vec!["nabijaczleweli".to_string()])).is_err());
+///
+/// let mut long_tweet = tweet.clone();
+/// long_tweet.content = mul_str("Tabs or spaces? ", 20);
+/// assert!(queue_tweet::validate_tweets(&vec![long_tweet], None).is_err());
+///
+/// tweet.poll.as_mut().unwrap().options.push("Tabs for indentation, spaces for alignment".to_string());
+/// assert!(queue_tweet::validate_tweets(&vec![tweet], None).is_err());
+/// # }
+/// ```
+pub fn validate_tweets(tweets: &Vec<QueuedTweet>, known_authors: Option<&Vec<String>>) -> Result<(), Outcome> {
+    let errors: Vec<_> = tweets.iter()
+        .flat_map(|tweet| {
+            let author_error = known_authors.and_then(|ka| unknown_author(&tweet.author, ka));
+            let poll_error = match (&tweet.action, &tweet.poll) {
+                (_, &None) => None,
+                (&TweetAction::Tweet, &Some(ref poll)) |
+                (&TweetAction::Quote(_), &Some(ref poll)) => poll.validate().err(),
+                (action, &Some(_)) => Some(format!("{}s can't have polls", action.name())),
+            };
+
+            let length_error = tweet.action.max_content_length().and_then(|max| {
+                let length = tweet.action.content_length(&tweet.content);
+                if length > max {
+                    Some(format!("{} is {} characters long as counted by Twitter, over the limit of {}", tweet.action.name(), length, max))
+                } else {
+                    None
+                }
+            });
+
+            author_error.into_iter()
+                .chain(poll_error)
+                .chain(length_error)
+                .map(|err| format!("tweet \"{}\" scheduled for {:?} by {}: {}", tweet.content, tweet.time, tweet.author, err))
+                .collect::<Vec<_>>()
+        })
+        .collect();
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(Outcome::InvalidTweets(errors))
+    }
+}
+
+/// Get the names of all users and user groups, i.e. everything tweets can be queued by.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::{UserGroup, User, queue_tweet};
+/// assert_eq!(queue_tweet::known_authors(&vec![User::new("brand",
+///                                                       481,
+///                                                       "481-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4",
+///                                                       "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5")],
+///                                       &vec![UserGroup {
+///                                           name: "brand-accounts".to_string(),
+///                                           members: vec!["brand".to_string()],
+///                                           stagger_minutes: None,
+///                                       }]),
+///            vec!["brand".to_string(), "brand-accounts".to_string()]);
+/// ```
+pub fn known_authors(users: &Vec<User>, groups: &Vec<UserGroup>) -> Vec<String> {
+    users.iter().map(|u| u.name.clone()).chain(groups.iter().map(|g| g.name.clone())).collect()
+}
+
+/// Bind the specified tweet to its author's ID, if the author was added already, correcting the handle's case.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::{QueuedTweet, User, queue_tweet};
+/// # use chrono::DateTime;
+/// # fn main() {
+/// let mut tweet = QueuedTweet::new("TweetR_Test", DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap(), "Test tweet");
+///
+/// queue_tweet::bind_author(&mut tweet, &vec![User::new("tweetr_test",
+///                                                      481,
+///                                                      "481-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4",
+///                                                      "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5")]);
+/// assert_eq!(tweet.author, "tweetr_test");
+/// assert_eq!(tweet.author_id, Some(481));
+/// # }
+/// ```
+pub fn bind_author(tweet: &mut QueuedTweet, users: &Vec<User>) {
+    if let Some(user) = users.iter().find(|u| tweet.is_by(u)) {
+        tweet.author = user.name.clone();
+        tweet.author_id = Some(user.id);
+    }
+}
+
+/// Move the specified tweet by a pseudo-random amount of time within its or its author's jitter, if any.
+///
+/// The amount depends only on the tweet's author, content and time, so the same tweet is always moved the same way.
+///
+/// Tweets aren't moved forward if that'd put them on or past their `post_by` deadline or expiry time, since the daemon
+/// would then drop them instead of posting them.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::{QueuedTweet, queue_tweet};
+/// # use chrono::{DateTime, Duration};
+/// # fn main() {
+/// let time = DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap();
+/// let mut tweet = QueuedTweet {
+///     jitter_minutes: Some(7),
+///     ..QueuedTweet::new("tweetr_test", time, "Test tweet")
+/// };
+///
+/// let mut same_tweet = tweet.clone();
+/// queue_tweet::apply_jitter(&mut tweet, &vec![]);
+/// queue_tweet::apply_jitter(&mut same_tweet, &vec![]);
+///
+/// assert_eq!(tweet.time, same_tweet.time);
+/// assert!(tweet.time >= time - Duration::minutes(7) && tweet.time <= time + Duration::minutes(7));
+/// # }
+/// ```
+pub fn apply_jitter(tweet: &mut QueuedTweet, users: &Vec<User>) {
+    let jitter = tweet.jitter_minutes.or_else(|| users.iter().find(|u| tweet.is_by(u)).and_then(|u| u.jitter_minutes)).unwrap_or(0) as i64 * 60;
+    if jitter == 0 || tweet.after.is_some() {
+        return;
+    }
+
+    let hash = fnv1a(&[tweet.author.as_bytes(), tweet.content.as_bytes(), tweet.time.to_rfc3339().as_bytes()]);
+    let offset = (hash % (2 * jitter as u64 + 1)) as i64 - jitter;
+
+    let new_time = tweet.time + Duration::seconds(offset);
+    if offset <= 0 || tweet.post_by.into_iter().chain(tweet.expires).all(|deadline| new_time < deadline) {
+        tweet.time = new_time;
+    }
+}
+
+
+fn unknown_author(author: &str, known_authors: &Vec<String>) -> Option<String> {
+    if known_authors.iter().any(|ka| ka.eq_ignore_ascii_case(author)) {
+        None
+    } else {
+        Some(match closest_match(author, known_authors) {
+            Some(suggestion) => format!("unknown author \"{}\", did you mean \"{}\"?", author, suggestion),
+            None => format!("unknown author \"{}\"", author),
+        })
+    }
+}
+
+fn fnv1a(data: &[&[u8]]) -> u64 {
+    data.iter().flat_map(|d| d.iter()).fold(0xcbf29ce484222325, |hash, &b| (hash ^ b as u64).wrapping_mul(0x100000001b3))
+}
@@ -6,19 +6,26 @@
 //! Instead, we convert it to a `QueuedTweetForSerialisation`, which has `DateTime`s converted to a `String` in RFC-3339
 //! format and then serialise the `Vec` of them via `QueuedTweets` (classic trick).
 //!
+//! The `TweetAction` is flattened into its name, target tweet ID or `local_id`, profile changes and recipient handle or ID,
+//! with plain tweets having none of them, and empty content is omitted.
+//!
 //! We do the inverse for deserialisation and silently return `None` if the `DateTime` strings have invalid format (same thing
-//! we do with a `ParserError`, TODO: don't silent-ignore parser errors (applies to all serialisables))
+//! we do with a `ParserError`, TODO: don't silent-ignore parser errors (applies to all serialisables)), except in
+//! `QueuedTweet::read_with_lines()`, which reports what's wrong with each tweet separately.
 
 
-use chrono::{DateTime, FixedOffset, ParseError};
+use self::super::super::util::{create_private_file, weighted_tweet_length};
+use self::super::{read_toml_file, read_toml_table};
+use chrono::{DateTime, FixedOffset, Duration};
+use toml::{Decoder, Value, encode_str};
 use self::super::super::Outcome;
-use self::super::read_toml_file;
+use rustc_serialize::Decodable;
 use std::iter::FromIterator;
 use std::cmp::Ordering;
-use toml::encode_str;
+use self::super::User;
 use std::path::Path;
 use std::io::Write;
-use std::fs::File;
+use regex::Regex;
 
 
 /// The struct representing a queued tweet to post, posted or not.
@@ -27,12 +34,25 @@ pub struct QueuedTweet {
     /// The author to post on behalf of.
     ///
     /// Has to have been previously added via the `add-user` subsystem.
+    ///
+    /// Kept up to date with the author's handle when `author_id` is known.
     pub author: String,
+    /// The numeric ID of the author, if known.
+    ///
+    /// Bound when queueing the tweet, if the author was added already, or when posting it otherwise.
+    /// Takes precedence over `author` when finding the user to post on behalf of, since handles can change.
+    pub author_id: Option<i64>,
     /// The time to post the tweet at.
     pub time: DateTime<FixedOffset>,
 
     /// The string content of the tweet.
+    ///
+    /// For direct messages, this is the message's text.
+    ///
+    /// Unused for other actions than tweets, quotes and direct messages.
     pub content: String,
+    /// What to actually do with the tweet.
+    pub action: TweetAction,
 
     /// The time this tweet was posted.
     ///
@@ -42,19 +62,157 @@ pub struct QueuedTweet {
     ///
     /// The tweet is accessible via the standard `https://twitter.com/status/{author}/{id}` URL.
     ///
+    /// For likes, pins and unpins, this is the ID of the tweet acted upon, for profile updates, the ID of the author, for
+    /// direct messages, the ID of the sent message.
+    ///
     /// Becomes non-empty when posted.
     pub id: Option<i64>,
+
+    /// The time by which the tweet must be posted, if any.
+    ///
+    /// Overrides the daemon's catch-up policy: the tweet is posted however late it is up to this time and dropped
+    /// afterwards.
+    pub post_by: Option<DateTime<FixedOffset>>,
+    /// Why posting the tweet was given up on.
+    ///
+    /// Becomes non-empty when the tweet is dropped instead of being posted.
+    pub failed: Option<String>,
+    /// How many minutes to randomly move the tweet by in either direction when queueing it, if any.
+    ///
+    /// Overrides `User::jitter_minutes`.
+    pub jitter_minutes: Option<u64>,
+
+    /// The identifier other tweets can use to refer to this one in `after`, if any.
+    pub local_id: Option<String>,
+    /// The `local_id` of the tweet to post this one after, if any.
+    ///
+    /// When set, `time` is derived from the anchor tweet: it's `after_minutes` past its `time_posted` once it's posted,
+    /// or past its `time` until then, and this tweet isn't posted before the anchor.
+    pub after: Option<String>,
+    /// How many minutes after the anchor tweet to post this one. Default: `0`
+    pub after_minutes: Option<u64>,
+
+    /// The time after which the tweet must never be posted, if any.
+    ///
+    /// Unlike `post_by`, this applies regardless of the daemon's catch-up policy or why the tweet wasn't posted in time,
+    /// the tweet is dropped as expired as soon as it passes.
+    pub expires: Option<DateTime<FixedOffset>>,
+
+    /// How many minutes after posting to delete the tweet, if any.
+    pub delete_after_minutes: Option<u64>,
+    /// The time to delete the tweet at once posted, if any.
+    ///
+    /// If both this and `delete_after_minutes` are specified, the tweet is deleted at whichever comes first.
+    pub delete_at: Option<DateTime<FixedOffset>>,
+    /// The time this tweet was deleted.
+    ///
+    /// Becomes non-empty when deleted.
+    pub time_deleted: Option<DateTime<FixedOffset>>,
+
+    /// The poll to attach to the tweet, if any.
+    ///
+    /// Only tweets and quotes can have polls.
+    pub poll: Option<Poll>,
+    /// The ID of the card containing the tweet's poll.
+    ///
+    /// Becomes non-empty once the card is created, right before posting the tweet, and is reused if posting it fails.
+    pub card_id: Option<String>,
+}
+
+/// The action a queued tweet stands for, all of them are done on behalf of its author.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub enum TweetAction {
+    /// Post the content as a new tweet.
+    Tweet,
+    /// Retweet the specified tweet.
+    Retweet(TweetTarget),
+    /// Post the content as a new tweet quoting the specified tweet.
+    Quote(TweetTarget),
+    /// Like the specified tweet.
+    Like(TweetTarget),
+    /// Pin the specified tweet to the top of the author's profile.
+    Pin(TweetTarget),
+    /// Unpin the specified tweet from the top of the author's profile.
+    Unpin(TweetTarget),
+    /// Change the author's profile.
+    UpdateProfile(ProfileUpdate),
+    /// Send the content as a direct message to the specified user.
+    DirectMessage(Recipient),
+}
+
+/// The tweet an action is done to.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub enum TweetTarget {
+    /// The tweet with the specified ID.
+    Id(i64),
+    /// The tweet posted for the queued tweet with the specified `local_id`.
+    ///
+    /// Replaced with its ID by `QueuedTweet::resolve_anchors()` once it's posted.
+    Queued(String),
+}
+
+/// Changes to make to a user's profile, unspecified parts are left as-is.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub struct ProfileUpdate {
+    /// The new display name, if any.
+    pub name: Option<String>,
+    /// The new bio, if any.
+    pub description: Option<String>,
+}
+
+/// A poll attached to a tweet.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub struct Poll {
+    /// The options to vote for, 2 to 4 of them, each at most 25 characters long.
+    pub options: Vec<String>,
+    /// For how many minutes the poll is open, between 5 minutes and 7 days.
+    pub duration_minutes: u64,
+}
+
+/// The user a direct message is sent to.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub enum Recipient {
+    /// The user with the specified numeric ID.
+    Id(i64),
+    /// The user with the specified handle, without the leading `@`.
+    Handle(String),
 }
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq, RustcEncodable, RustcDecodable)]
 struct QueuedTweetForSerialisation {
     pub author: String,
+    pub author_id: Option<i64>,
     pub time: String,
 
-    pub content: String,
+    pub content: Option<String>,
+    pub action: Option<String>,
+    pub target: Option<i64>,
+    pub target_local_id: Option<String>,
+    pub profile_name: Option<String>,
+    pub profile_description: Option<String>,
+    pub recipient: Option<String>,
+    pub recipient_id: Option<i64>,
 
     pub time_posted: Option<String>,
     pub id: Option<i64>,
+
+    pub post_by: Option<String>,
+    pub failed: Option<String>,
+    pub jitter_minutes: Option<u64>,
+
+    pub local_id: Option<String>,
+    pub after: Option<String>,
+    pub after_minutes: Option<u64>,
+
+    pub expires: Option<String>,
+
+    pub delete_after_minutes: Option<u64>,
+    pub delete_at: Option<String>,
+    pub time_deleted: Option<String>,
+
+    pub poll_options: Option<Vec<String>>,
+    pub poll_duration_minutes: Option<u64>,
+    pub card_id: Option<String>,
 }
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq, RustcEncodable, RustcDecodable)]
@@ -64,19 +222,546 @@ struct QueuedTweets {
 
 
 impl QueuedTweet {
+    /// Create a plain unposted tweet by the specified author, to post at the specified time, without any extras.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate tweetr;
+    /// # extern crate chrono;
+    /// # use tweetr::ops::{QueuedTweet, TweetAction};
+    /// # use chrono::DateTime;
+    /// # fn main() {
+    /// let tweet = QueuedTweet::new("nabijaczleweli", DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap(), "Capitalism");
+    /// assert_eq!(tweet.action, TweetAction::Tweet);
+    /// assert_eq!(tweet.id, None);
+    /// # }
+    /// ```
+    pub fn new<A: Into<String>, C: Into<String>>(author: A, time: DateTime<FixedOffset>, content: C) -> QueuedTweet {
+        QueuedTweet {
+            author: author.into(),
+            author_id: None,
+            time: time,
+            content: content.into(),
+            action: TweetAction::Tweet,
+            time_posted: None,
+            id: None,
+            post_by: None,
+            failed: None,
+            jitter_minutes: None,
+            local_id: None,
+            after: None,
+            after_minutes: None,
+            expires: None,
+            delete_after_minutes: None,
+            delete_at: None,
+            time_deleted: None,
+            poll: None,
+            card_id: None,
+        }
+    }
+
     /// Read all queued tweets from the specified file.
     pub fn read(p: &Path) -> Result<Vec<QueuedTweet>, Option<Outcome>> {
         let queued_tweets: QueuedTweets = try!(read_toml_file(p, "queued tweets"));
         Result::from_iter(queued_tweets.tweet.into_iter().map(|qts| qts.into()).collect::<Vec<_>>()).map_err(|_| None)
     }
 
+    /// Read all queued tweets from the specified file, each separately, along with the line its `[[tweet]]` header is on.
+    ///
+    /// Unlike with `read()`, tweets that fail to deserialise don't fail the whole file, but are returned as why they failed.
+    ///
+    /// Headers are found line-by-line, skipping the insides of multi-line strings, so if the tweets don't all have their own
+    /// header, e.g. when they're written as an inline array, no lines are returned, since they couldn't be matched up.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::QueuedTweet;
+    /// # use std::env::temp_dir;
+    /// # use std::fs::{self, File};
+    /// # use std::io::Write;
+    /// let tf = temp_dir().join("tweetr-doctest").join("ops-queued_tweet-read_with_lines-0");
+    /// fs::create_dir_all(&tf).unwrap();
+    /// File::create(tf.join("tweets.toml")).unwrap().write_all(b"[[tweet]]\n\
+    ///                                                            author = \"nabijaczleweli\"\n\
+    ///                                                            time = \"2016-09-09T00:33:30+02:00\"\n\
+    ///                                                            \n\
+    ///                                                            [[tweet]]\n\
+    ///                                                            author = \"nabijaczleweli\"\n\
+    ///                                                            time = \"tomorrow\"\n").unwrap();
+    ///
+    /// let tweets = QueuedTweet::read_with_lines(&tf.join("tweets.toml")).unwrap();
+    /// assert_eq!(tweets[0].0, Some(1));
+    /// assert!(tweets[0].1.is_ok());
+    /// assert_eq!(tweets[1].0, Some(5));
+    /// assert!(tweets[1].1.as_ref().unwrap_err().starts_with("time \"tomorrow\" isn't a valid RFC3339 time"));
+    /// ```
+    pub fn read_with_lines(p: &Path) -> Result<Vec<(Option<usize>, Result<QueuedTweet, String>)>, Option<Outcome>> {
+        let (buf, mut table) = try!(read_toml_table(p, "queued tweets"));
+        let header_lines = tweet_header_lines(&buf);
+
+        let tweets = match table.remove("tweet") {
+            Some(Value::Array(tweets)) => tweets,
+            Some(_) => {
+                return Err(Some(Outcome::FileParsingFailed {
+                    desc: "queued tweets",
+                    errors: vec!["error: tweet isn't an array of tables".to_string()],
+                }))
+            }
+            None => vec![],
+        };
+        let header_lines = if header_lines.len() == tweets.len() {
+            header_lines
+        } else {
+            vec![]
+        };
+        Ok(tweets.into_iter()
+            .enumerate()
+            .map(|(i, tweet)| {
+                let tweet = QueuedTweetForSerialisation::decode(&mut Decoder::new(tweet)).map_err(|e| e.to_string()).and_then(Into::into);
+                (header_lines.get(i).cloned(), tweet)
+            })
+            .collect())
+    }
+
     /// Save all queued tweets to the specified file.
     pub fn write(queued_tweets: Vec<QueuedTweet>, p: &Path) {
-        File::create(p)
+        create_private_file(p)
             .unwrap()
             .write_all(encode_str(&QueuedTweets { tweet: queued_tweets.into_iter().map(QueuedTweetForSerialisation::from).collect() }).as_bytes())
             .unwrap();
     }
+
+    /// Check whether this tweet is to be posted on behalf of the specified user.
+    ///
+    /// Tweets with a known `author_id` are matched by it, other tweets by the handle, ignoring case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate tweetr;
+    /// # extern crate chrono;
+    /// # use tweetr::ops::{QueuedTweet, User};
+    /// # use chrono::DateTime;
+    /// # fn main() {
+    /// let user = User::new("nabijaczleweli", 0x81423, "529443-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4", "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5");
+    ///
+    /// let mut tweet = QueuedTweet::new("NabijaczLeweli", DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap(), "Capitalism");
+    /// assert!(tweet.is_by(&user));
+    ///
+    /// tweet.author = "old_handle".to_string();
+    /// tweet.author_id = Some(0x81423);
+    /// assert!(tweet.is_by(&user));
+    /// # }
+    /// ```
+    pub fn is_by(&self, user: &User) -> bool {
+        match self.author_id {
+            Some(id) => id == user.id,
+            None => self.author.eq_ignore_ascii_case(&user.name),
+        }
+    }
+
+    /// Get the time this tweet is to be deleted at, if it was posted and is to be deleted at all.
+    ///
+    /// Profile updates and unpins can't be undone, so they never are.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate tweetr;
+    /// # extern crate chrono;
+    /// # use tweetr::ops::QueuedTweet;
+    /// # use chrono::DateTime;
+    /// # fn main() {
+    /// let mut tweet = QueuedTweet {
+    ///     time_posted: Some(DateTime::parse_from_rfc3339("2016-09-09T00:33:31+02:00").unwrap()),
+    ///     id: Some(773264221226950656),
+    ///     delete_after_minutes: Some(60),
+    ///     delete_at: Some(DateTime::parse_from_rfc3339("2016-09-10T00:00:00+02:00").unwrap()),
+    ///     ..QueuedTweet::new("nabijaczleweli", DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap(), "Giveaway! RT to enter")
+    /// };
+    /// assert_eq!(tweet.deletion_time(), Some(DateTime::parse_from_rfc3339("2016-09-09T01:33:31+02:00").unwrap()));
+    ///
+    /// tweet.time_posted = None;
+    /// tweet.id = None;
+    /// assert_eq!(tweet.deletion_time(), None);
+    /// # }
+    /// ```
+    pub fn deletion_time(&self) -> Option<DateTime<FixedOffset>> {
+        match self.action {
+            TweetAction::Unpin(_) |
+            TweetAction::UpdateProfile(_) => return None,
+            _ => {}
+        }
+
+        self.time_posted.and_then(|tp| {
+            self.delete_after_minutes
+                .map(|dam| tp + Duration::minutes(dam as i64))
+                .into_iter()
+                .chain(self.delete_at)
+                .min()
+        })
+    }
+
+    /// Derive the posting times of tweets scheduled relative to other tweets from their anchors' times, and the IDs of queued
+    /// tweets actions are done to once they're posted.
+    ///
+    /// Tweets whose anchors or targets don't exist or were dropped are dropped as well, with a message written to the output.
+    /// So are unposted tweets whose `local_id` isn't unique, and ones whose anchors and targets lead back to themselves, since
+    /// they'd never be posted otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate tweetr;
+    /// # extern crate chrono;
+    /// # use tweetr::ops::QueuedTweet;
+    /// # use chrono::DateTime;
+    /// # fn main() {
+    /// let mut tweets = vec![
+    ///     QueuedTweet {
+    ///         time_posted: Some(DateTime::parse_from_rfc3339("2016-09-09T01:00:00+02:00").unwrap()),
+    ///         id: Some(773264221226950656),
+    ///         local_id: Some("giveaway".to_string()),
+    ///         ..QueuedTweet::new("nabijaczleweli", DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap(), "The giveaway starts now!")
+    ///     },
+    ///     QueuedTweet {
+    ///         after: Some("giveaway".to_string()),
+    ///         after_minutes: Some(45),
+    ///         ..QueuedTweet::new("nabijaczleweli", DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap(), "15 minutes left in the giveaway!")
+    ///     },
+    /// ];
+    ///
+    /// QueuedTweet::resolve_anchors(&mut tweets, &mut vec![]);
+    /// assert_eq!(tweets[1].time, DateTime::parse_from_rfc3339("2016-09-09T01:45:00+02:00").unwrap());
+    /// # }
+    /// ```
+    pub fn resolve_anchors<W: Write>(tweets: &mut Vec<QueuedTweet>, output: &mut W) {
+        let mut unresolvable = vec![];
+        for i in 0..tweets.len() {
+            if tweets[i].id.is_some() || tweets[i].failed.is_some() {
+                continue;
+            }
+
+            if let Some(ref local_id) = tweets[i].local_id {
+                if tweets.iter().enumerate().any(|(j, t)| j != i && t.failed.is_none() && t.local_id.as_ref() == Some(local_id)) {
+                    unresolvable.push((i, format!("local_id \"{}\" isn't unique", local_id)));
+                    continue;
+                }
+            }
+
+            let mut path = vec![];
+            if leads_back_to(tweets, i, i, &mut vec![false; tweets.len()], &mut path) {
+                let ids: Vec<_> = path.iter().map(|&j| tweets[j].local_id.as_ref().map(|l| &l[..]).unwrap_or("")).collect();
+                unresolvable.push((i, format!("anchors and targets form a cycle: {} -> {}", ids.join(" -> "), ids[0])));
+            }
+        }
+        for (i, reason) in unresolvable {
+            let tweet = &mut tweets[i];
+            writeln!(output, "Dropped tweet \"{}\" scheduled for {:?} by {}: {}", tweet.content, tweet.time, tweet.author, reason).unwrap();
+            tweet.failed = Some(reason);
+        }
+
+        // Anchors can be chained, so go at it until nothing changes, at most once per link
+        for _ in 0..tweets.len() {
+            let mut changed = false;
+
+            for i in 0..tweets.len() {
+                if tweets[i].id.is_some() || tweets[i].failed.is_some() {
+                    continue;
+                }
+
+                let resolved = match tweets[i].after {
+                    Some(ref after) => {
+                        match find_local(tweets, i, after) {
+                            Some(anchor) if anchor.failed.is_some() => Err(format!("anchor tweet \"{}\" was dropped", after)),
+                            Some(anchor) => {
+                                Ok(Some(anchor.time_posted.unwrap_or(anchor.time) + Duration::minutes(tweets[i].after_minutes.unwrap_or(0) as i64)))
+                            }
+                            None => Err(format!("anchor tweet \"{}\" doesn't exist", after)),
+                        }
+                    }
+                    None => Ok(None),
+                };
+                let target = match tweets[i].action.target() {
+                    Some(&TweetTarget::Queued(ref target)) => {
+                        match find_local(tweets, i, target) {
+                            Some(target_tweet) if target_tweet.failed.is_some() => Err(format!("target tweet \"{}\" was dropped", target)),
+                            Some(target_tweet) => Ok(target_tweet.id),
+                            None => Err(format!("target tweet \"{}\" doesn't exist", target)),
+                        }
+                    }
+                    _ => Ok(None),
+                };
+
+                let tweet = &mut tweets[i];
+                match resolved.and_then(|time| target.map(|id| (time, id))) {
+                    Ok((time, id)) => {
+                        if let Some(time) = time {
+                            if tweet.time != time {
+                                tweet.time = time;
+                                changed = true;
+                            }
+                        }
+                        if let Some(id) = id {
+                            *tweet.action.target_mut().unwrap() = TweetTarget::Id(id);
+                            changed = true;
+                        }
+                    }
+                    Err(reason) => {
+                        writeln!(output, "Dropped tweet \"{}\" scheduled for {:?} by {}: {}", tweet.content, tweet.time, tweet.author, reason).unwrap();
+                        tweet.failed = Some(reason);
+                        changed = true;
+                    }
+                }
+            }
+
+            if !changed {
+                break;
+            }
+        }
+    }
+}
+
+impl TweetAction {
+    /// Get the action corresponding to the specified name and target tweet, profile changes or recipient, as used in the tweet
+    /// queue file.
+    ///
+    /// All actions but `tweet`, `update-profile` and `direct-message` require a target, `update-profile` requires profile
+    /// changes and `direct-message` a recipient.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::{TweetAction, TweetTarget, ProfileUpdate, Recipient};
+    /// assert_eq!(TweetAction::from_parts("tweet", None, None, None), Some(TweetAction::Tweet));
+    /// assert_eq!(TweetAction::from_parts("retweet", Some(TweetTarget::Id(773264221226950656)), None, None),
+    ///            Some(TweetAction::Retweet(TweetTarget::Id(773264221226950656))));
+    /// assert_eq!(TweetAction::from_parts("pin", Some(TweetTarget::Queued("announcement".to_string())), None, None),
+    ///            Some(TweetAction::Pin(TweetTarget::Queued("announcement".to_string()))));
+    /// assert_eq!(TweetAction::from_parts("update-profile",
+    ///                                    None,
+    ///                                    Some(ProfileUpdate {
+    ///                                        name: Some("tweetr".to_string()),
+    ///                                        description: None,
+    ///                                    }),
+    ///                                    None),
+    ///            Some(TweetAction::UpdateProfile(ProfileUpdate {
+    ///                name: Some("tweetr".to_string()),
+    ///                description: None,
+    ///            })));
+    /// assert_eq!(TweetAction::from_parts("direct-message", None, None, Some(Recipient::Handle("nabijaczleweli".to_string()))),
+    ///            Some(TweetAction::DirectMessage(Recipient::Handle("nabijaczleweli".to_string()))));
+    ///
+    /// assert_eq!(TweetAction::from_parts("like", None, None, None), None);
+    /// assert_eq!(TweetAction::from_parts("update-profile", None, None, None), None);
+    /// assert_eq!(TweetAction::from_parts("direct-message", None, None, None), None);
+    /// assert_eq!(TweetAction::from_parts("follow", Some(TweetTarget::Id(773264221226950656)), None, None), None);
+    /// ```
+    pub fn from_parts(name: &str, target: Option<TweetTarget>, profile: Option<ProfileUpdate>, recipient: Option<Recipient>)
+                      -> Option<TweetAction> {
+        match (name, target, profile, recipient) {
+            ("tweet", _, _, _) => Some(TweetAction::Tweet),
+            ("retweet", Some(target), _, _) => Some(TweetAction::Retweet(target)),
+            ("quote", Some(target), _, _) => Some(TweetAction::Quote(target)),
+            ("like", Some(target), _, _) => Some(TweetAction::Like(target)),
+            ("pin", Some(target), _, _) => Some(TweetAction::Pin(target)),
+            ("unpin", Some(target), _, _) => Some(TweetAction::Unpin(target)),
+            ("update-profile", _, Some(profile), _) => Some(TweetAction::UpdateProfile(profile)),
+            ("direct-message", _, _, Some(recipient)) => Some(TweetAction::DirectMessage(recipient)),
+            _ => None,
+        }
+    }
+
+    /// Get the name of the action, as used in the tweet queue file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::{TweetAction, TweetTarget};
+    /// assert_eq!(TweetAction::Tweet.name(), "tweet");
+    /// assert_eq!(TweetAction::Retweet(TweetTarget::Id(773264221226950656)).name(), "retweet");
+    /// ```
+    pub fn name(&self) -> &'static str {
+        match *self {
+            TweetAction::Tweet => "tweet",
+            TweetAction::Retweet(_) => "retweet",
+            TweetAction::Quote(_) => "quote",
+            TweetAction::Like(_) => "like",
+            TweetAction::Pin(_) => "pin",
+            TweetAction::Unpin(_) => "unpin",
+            TweetAction::UpdateProfile(_) => "update-profile",
+            TweetAction::DirectMessage(_) => "direct-message",
+        }
+    }
+
+    /// Check whether the action posts a new tweet on the author's timeline, i.e. it's a tweet, a retweet or a quote.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::{TweetAction, TweetTarget};
+    /// assert!(TweetAction::Retweet(TweetTarget::Id(773264221226950656)).creates_tweet());
+    /// assert!(!TweetAction::Like(TweetTarget::Id(773264221226950656)).creates_tweet());
+    /// ```
+    pub fn creates_tweet(&self) -> bool {
+        match *self {
+            TweetAction::Tweet |
+            TweetAction::Retweet(_) |
+            TweetAction::Quote(_) => true,
+            TweetAction::Like(_) |
+            TweetAction::Pin(_) |
+            TweetAction::Unpin(_) |
+            TweetAction::UpdateProfile(_) |
+            TweetAction::DirectMessage(_) => false,
+        }
+    }
+
+    /// Get the tweet the action is done to, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::{TweetAction, TweetTarget};
+    /// assert_eq!(TweetAction::Tweet.target(), None);
+    /// assert_eq!(TweetAction::Like(TweetTarget::Id(773264221226950656)).target(), Some(&TweetTarget::Id(773264221226950656)));
+    /// ```
+    pub fn target(&self) -> Option<&TweetTarget> {
+        match *self {
+            TweetAction::Tweet |
+            TweetAction::UpdateProfile(_) |
+            TweetAction::DirectMessage(_) => None,
+            TweetAction::Retweet(ref target) |
+            TweetAction::Quote(ref target) |
+            TweetAction::Like(ref target) |
+            TweetAction::Pin(ref target) |
+            TweetAction::Unpin(ref target) => Some(target),
+        }
+    }
+
+    /// Get the ID of the tweet the action is done to, if any and if known.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::{TweetAction, TweetTarget};
+    /// assert_eq!(TweetAction::Like(TweetTarget::Id(773264221226950656)).target_id(), Some(773264221226950656));
+    /// assert_eq!(TweetAction::Like(TweetTarget::Queued("announcement".to_string())).target_id(), None);
+    /// ```
+    pub fn target_id(&self) -> Option<i64> {
+        match self.target() {
+            Some(&TweetTarget::Id(id)) => Some(id),
+            _ => None,
+        }
+    }
+
+    /// Get the maximum length of the content of a tweet with this action, as counted by `content_length()`, if it has
+    /// content.
+    ///
+    /// Quotes have less room, since the quoted tweet's URL is appended to them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::{TweetAction, TweetTarget};
+    /// assert_eq!(TweetAction::Tweet.max_content_length(), Some(280));
+    /// assert_eq!(TweetAction::Quote(TweetTarget::Id(773264221226950656)).max_content_length(), Some(256));
+    /// assert_eq!(TweetAction::Like(TweetTarget::Id(773264221226950656)).max_content_length(), None);
+    /// ```
+    pub fn max_content_length(&self) -> Option<usize> {
+        match *self {
+            TweetAction::Tweet => Some(280),
+            // A space and a t.co link
+            TweetAction::Quote(_) => Some(280 - 1 - 23),
+            TweetAction::DirectMessage(_) => Some(10000),
+            TweetAction::Retweet(_) |
+            TweetAction::Like(_) |
+            TweetAction::Pin(_) |
+            TweetAction::Unpin(_) |
+            TweetAction::UpdateProfile(_) => None,
+        }
+    }
+
+    /// Get the length of the specified content of a tweet with this action, to compare with `max_content_length()`.
+    ///
+    /// Tweets and quotes are counted like Twitter does (see `util::weighted_tweet_length()`), direct messages by
+    /// characters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::{TweetAction, Recipient};
+    /// assert_eq!(TweetAction::Tweet.content_length("資本主義 https://example.com/capitalism"), 8 + 1 + 23);
+    /// assert_eq!(TweetAction::DirectMessage(Recipient::Handle("nabijaczleweli".to_string())).content_length("資本主義"), 4);
+    /// ```
+    pub fn content_length(&self, content: &str) -> usize {
+        match *self {
+            TweetAction::DirectMessage(_) => content.chars().count(),
+            _ => weighted_tweet_length(content),
+        }
+    }
+
+    fn target_mut(&mut self) -> Option<&mut TweetTarget> {
+        match *self {
+            TweetAction::Tweet |
+            TweetAction::UpdateProfile(_) |
+            TweetAction::DirectMessage(_) => None,
+            TweetAction::Retweet(ref mut target) |
+            TweetAction::Quote(ref mut target) |
+            TweetAction::Like(ref mut target) |
+            TweetAction::Pin(ref mut target) |
+            TweetAction::Unpin(ref mut target) => Some(target),
+        }
+    }
+}
+
+impl Poll {
+    /// Check whether Twitter will accept the poll, returning what's wrong with it if it won't.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::Poll;
+    /// assert_eq!(Poll {
+    ///                options: vec!["Yes".to_string(), "No".to_string()],
+    ///                duration_minutes: 60 * 24,
+    ///            }
+    ///            .validate(),
+    ///            Ok(()));
+    ///
+    /// assert!(Poll {
+    ///             options: vec!["Yes".to_string()],
+    ///             duration_minutes: 60 * 24,
+    ///         }
+    ///         .validate()
+    ///         .is_err());
+    /// assert!(Poll {
+    ///             options: vec!["Yes".to_string(), "No, and I'm very sure of that".to_string()],
+    ///             duration_minutes: 60 * 24,
+    ///         }
+    ///         .validate()
+    ///         .is_err());
+    /// assert!(Poll {
+    ///             options: vec!["Yes".to_string(), "No".to_string()],
+    ///             duration_minutes: 60 * 24 * 8,
+    ///         }
+    ///         .validate()
+    ///         .is_err());
+    /// ```
+    pub fn validate(&self) -> Result<(), String> {
+        if self.options.len() < 2 || self.options.len() > 4 {
+            return Err(format!("polls need 2 to 4 options, not {}", self.options.len()));
+        }
+        if let Some(option) = self.options.iter().find(|o| o.trim().is_empty() || o.chars().count() > 25) {
+            return Err(format!("poll option \"{}\" is not 1 to 25 characters long", option));
+        }
+        if self.duration_minutes < 5 || self.duration_minutes > 60 * 24 * 7 {
+            return Err(format!("polls need to be open for 5 minutes to 7 days, not {} minutes", self.duration_minutes));
+        }
+
+        Ok(())
+    }
 }
 
 impl Ord for QueuedTweet {
@@ -96,25 +781,190 @@ impl From<QueuedTweet> for QueuedTweetForSerialisation {
     fn from(qt: QueuedTweet) -> QueuedTweetForSerialisation {
         QueuedTweetForSerialisation {
             author: qt.author,
+            author_id: qt.author_id,
             time: qt.time.to_rfc3339(),
-            content: qt.content,
+            content: if qt.content.is_empty() {
+                None
+            } else {
+                Some(qt.content)
+            },
+            action: match qt.action {
+                TweetAction::Tweet => None,
+                ref action => Some(action.name().to_string()),
+            },
+            target: qt.action.target_id(),
+            target_local_id: match qt.action.target() {
+                Some(&TweetTarget::Queued(ref lid)) => Some(lid.clone()),
+                _ => None,
+            },
+            profile_name: match qt.action {
+                TweetAction::UpdateProfile(ref pu) => pu.name.clone(),
+                _ => None,
+            },
+            profile_description: match qt.action {
+                TweetAction::UpdateProfile(ref pu) => pu.description.clone(),
+                _ => None,
+            },
+            recipient: match qt.action {
+                TweetAction::DirectMessage(Recipient::Handle(ref handle)) => Some(handle.clone()),
+                _ => None,
+            },
+            recipient_id: match qt.action {
+                TweetAction::DirectMessage(Recipient::Id(id)) => Some(id),
+                _ => None,
+            },
             time_posted: qt.time_posted.map(|dt| dt.to_rfc3339()),
             id: qt.id,
+            post_by: qt.post_by.map(|dt| dt.to_rfc3339()),
+            failed: qt.failed,
+            jitter_minutes: qt.jitter_minutes,
+            local_id: qt.local_id,
+            after: qt.after,
+            after_minutes: qt.after_minutes,
+            expires: qt.expires.map(|dt| dt.to_rfc3339()),
+            delete_after_minutes: qt.delete_after_minutes,
+            delete_at: qt.delete_at.map(|dt| dt.to_rfc3339()),
+            time_deleted: qt.time_deleted.map(|dt| dt.to_rfc3339()),
+            poll_options: qt.poll.as_ref().map(|p| p.options.clone()),
+            poll_duration_minutes: qt.poll.as_ref().map(|p| p.duration_minutes),
+            card_id: qt.card_id,
         }
     }
 }
 
-impl Into<Result<QueuedTweet, ParseError>> for QueuedTweetForSerialisation {
-    fn into(self) -> Result<QueuedTweet, ParseError> {
+impl Into<Result<QueuedTweet, String>> for QueuedTweetForSerialisation {
+    fn into(self) -> Result<QueuedTweet, String> {
+        let target = match (self.target, self.target_local_id) {
+            (Some(id), _) => Some(TweetTarget::Id(id)),
+            (None, Some(local_id)) => Some(TweetTarget::Queued(local_id)),
+            (None, None) => None,
+        };
+        let profile = match (self.profile_name, self.profile_description) {
+            (None, None) => None,
+            (name, description) => {
+                Some(ProfileUpdate {
+                    name: name,
+                    description: description,
+                })
+            }
+        };
+
+        let recipient = match (self.recipient_id, self.recipient) {
+            (Some(id), _) => Some(Recipient::Id(id)),
+            (None, Some(ref handle)) if handle.starts_with('@') => Some(Recipient::Handle(handle[1..].to_string())),
+            (None, Some(handle)) => Some(Recipient::Handle(handle)),
+            (None, None) => None,
+        };
+
+        let action = self.action.unwrap_or_else(|| "tweet".to_string());
         Ok(QueuedTweet {
             author: self.author,
-            time: try!(DateTime::parse_from_rfc3339(&self.time)),
-            content: self.content,
+            author_id: self.author_id,
+            time: try!(parse_field_time("time", &self.time)),
+            content: self.content.unwrap_or_default(),
+            action: try!(TweetAction::from_parts(&action, target, profile, recipient)
+                .ok_or_else(|| format!("action \"{}\" is unknown or is missing its target, profile update or recipient", action))),
             time_posted: match self.time_posted {
-                Some(dts) => Some(try!(DateTime::parse_from_rfc3339(&dts))),
+                Some(dts) => Some(try!(parse_field_time("time_posted", &dts))),
                 None => None,
             },
             id: self.id,
+            post_by: match self.post_by {
+                Some(dts) => Some(try!(parse_field_time("post_by", &dts))),
+                None => None,
+            },
+            failed: self.failed,
+            jitter_minutes: self.jitter_minutes,
+            local_id: self.local_id,
+            after: self.after,
+            after_minutes: self.after_minutes,
+            expires: match self.expires {
+                Some(dts) => Some(try!(parse_field_time("expires", &dts))),
+                None => None,
+            },
+            delete_after_minutes: self.delete_after_minutes,
+            delete_at: match self.delete_at {
+                Some(dts) => Some(try!(parse_field_time("delete_at", &dts))),
+                None => None,
+            },
+            time_deleted: match self.time_deleted {
+                Some(dts) => Some(try!(parse_field_time("time_deleted", &dts))),
+                None => None,
+            },
+            poll: match (self.poll_options, self.poll_duration_minutes) {
+                (Some(options), Some(duration_minutes)) => {
+                    Some(Poll {
+                        options: options,
+                        duration_minutes: duration_minutes,
+                    })
+                }
+                (None, None) => None,
+                _ => return Err("poll_options and poll_duration_minutes need to be specified together".to_string()),
+            },
+            card_id: self.card_id,
         })
     }
 }
+
+
+/// Get the 1-based numbers of the lines with `[[tweet]]` headers, allowing whitespace and quotes around the name, but not
+/// ones inside multi-line strings.
+fn tweet_header_lines(buf: &str) -> Vec<usize> {
+    lazy_static! {
+        static ref HEADER_REGEX: Regex = Regex::new(r#"^\s*\[\[\s*(tweet|"tweet"|'tweet')\s*\]\]\s*(#.*)?$"#).unwrap();
+    }
+
+    let mut in_string = None;
+    let mut lines = vec![];
+    for (i, line) in buf.lines().enumerate() {
+        match in_string {
+            Some(delim) => {
+                if line.contains(delim) {
+                    in_string = None;
+                }
+            }
+            None if HEADER_REGEX.is_match(line) => lines.push(i + 1),
+            None => {
+                // An odd amount of delimiters leaves a multi-line string open
+                in_string = ["\"\"\"", "'''"].iter().cloned().find(|delim| line.matches(delim).count() % 2 == 1);
+            }
+        }
+    }
+    lines
+}
+
+/// Find the tweet other than the `i`th with the specified `local_id`, preferring ones that weren't dropped.
+fn find_local<'t>(tweets: &'t Vec<QueuedTweet>, i: usize, local_id: &str) -> Option<&'t QueuedTweet> {
+    tweets.iter()
+        .enumerate()
+        .filter(|&(j, t)| j != i && t.local_id.as_ref().map(|l| &l[..]) == Some(local_id))
+        .map(|(_, t)| t)
+        .min_by_key(|t| t.failed.is_some())
+}
+
+/// Check whether following the `cur`th tweet's unposted anchor and queued target leads back to the `start`th, collecting the
+/// tweets on the way into `path`.
+fn leads_back_to(tweets: &Vec<QueuedTweet>, start: usize, cur: usize, visited: &mut Vec<bool>, path: &mut Vec<usize>) -> bool {
+    visited[cur] = true;
+    path.push(cur);
+
+    let dependencies = tweets[cur].after.iter().chain(match tweets[cur].action.target() {
+        Some(&TweetTarget::Queued(ref target)) => Some(target),
+        _ => None,
+    });
+    for dependency in dependencies {
+        let next = tweets.iter().position(|t| t.id.is_none() && t.failed.is_none() && t.local_id.as_ref() == Some(dependency));
+        match next {
+            Some(next) if next == start => return true,
+            Some(next) if !visited[next] && leads_back_to(tweets, start, next, visited, path) => return true,
+            _ => (),
+        }
+    }
+
+    path.pop();
+    false
+}
+
+fn parse_field_time(field: &str, dts: &str) -> Result<DateTime<FixedOffset>, String> {
+    DateTime::parse_from_rfc3339(dts).map_err(|e| format!("{} \"{}\" isn't a valid RFC3339 time: {}", field, dts, e))
+}
@@ -0,0 +1,97 @@
+//! This module contains the functions used only by the `reauth-user` subsystem.
+//!
+//! The flow of the `reauth-user` subsystem is as follows:
+//!
+//! ```plaintext
+//! Options::parse()
+//! |> ops::reauth_user::verify()
+//! |> ops::AppTokens::read()
+//! |> ops::User::read()
+//! |> ops::remove_user::find_user()
+//! |> ops::add_user::authorise()
+//! |> ops::reauth_user::replace_tokens()
+//! |> ops::User::write()
+//! ```
+
+
+use self::super::{User, verify_app_profiles, verify_file};
+use self::super::super::Outcome;
+use std::path::PathBuf;
+
+
+/// Verify if, given the current configuration, it's permitted to continue with the subsequent steps of the `reauth-user`
+/// subsystem.
+///
+/// The return value contains either the path to the file containing the global users data or why getting it or any app
+/// profile failed, the default one isn't required.
+///
+/// # Examples
+///
+/// Verifying when the global users data doesn't exist.
+///
+/// ```
+/// # use tweetr::ops::reauth_user;
+/// # use std::fs::{self, File};
+/// # use std::env::temp_dir;
+/// # use tweetr::Outcome;
+/// # use std::io::Write;
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-reauth-user-verify-0");
+/// fs::create_dir_all(&tf).unwrap();
+/// File::create(tf.join("app.toml")).unwrap().write(&[]).unwrap();
+///
+/// assert_eq!(reauth_user::verify(&("$TEMP/ops-reauth-user-verify-0".to_string(), tf)),
+///            Err(Outcome::RequiredFileFromSubsystemNonexistant {
+///                subsys: "add-user",
+///                fname: "$TEMP/ops-reauth-user-verify-0/users.toml".to_string(),
+///            }));
+/// ```
+pub fn verify(config_dir: &(String, PathBuf)) -> Result<PathBuf, Outcome> {
+    try!(verify_app_profiles(config_dir));
+    verify_file("users.toml", true, config_dir, false, "add-user")
+}
+
+/// Replace the specified user's access tokens and handle with the newly authorised ones, keeping its posting limits.
+///
+/// The user is unsuspended, if it was suspended.
+///
+/// If the user's access token secret is fetched via its `secret_command`, it can't be replaced here, and is returned
+/// instead, to be stored wherever the command gets it from.
+///
+/// Refuses to do so if a different user was authorised.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::{User, reauth_user};
+/// let mut user = User {
+///     min_minutes_between_posts: Some(30),
+///     ..User::new("nabijaczleweli_old", 0x81423, "529443-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4", "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5")
+/// };
+///
+/// assert_eq!(reauth_user::replace_tokens(&mut user, User::new("nabijaczleweli",
+///                                                             0x81423,
+///                                                             "529443-vHqE7HBgwoJLAKbTUuc1k4KmnaQHlZ2lbtGyHLW",
+///                                                             "P8B46wSaV1j0stxx94L2Z4dc3UnR0zMMdO4E4Pq3rsOVf")),
+///            Ok(None));
+/// assert_eq!(user.name, "nabijaczleweli");
+/// assert_eq!(user.access_token_key, "529443-vHqE7HBgwoJLAKbTUuc1k4KmnaQHlZ2lbtGyHLW");
+/// assert_eq!(user.min_minutes_between_posts, Some(30));
+/// ```
+pub fn replace_tokens(user: &mut User, authorised: User) -> Result<Option<String>, Outcome> {
+    if authorised.id != user.id {
+        return Err(Outcome::UserMismatch {
+            expected: format!("{}#{}", user.name, user.id),
+            authorised: format!("{}#{}", authorised.name, authorised.id),
+        });
+    }
+
+    user.name = authorised.name;
+    user.access_token_key = authorised.access_token_key;
+    user.suspended = None;
+    if user.secret_command.is_some() {
+        Ok(Some(authorised.access_token_secret))
+    } else {
+        user.access_token_secret = authorised.access_token_secret;
+        Ok(None)
+    }
+}
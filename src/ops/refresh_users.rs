@@ -0,0 +1,175 @@
+//! This module contains the functions used only by the `refresh-users` subsystem.
+//!
+//! The flow of the `refresh-users` subsystem is as follows:
+//!
+//! ```plaintext
+//! Options::parse()
+//! |> ops::refresh_users::verify()
+//! |> ops::AppTokens::read()
+//! |> ops::User::read()
+//! |> ops::UserGroup::read()
+//! |> ops::refresh_users::refresh_user()
+//! |> ops::refresh_users::rename_members()
+//! |> ops::User::write_with_groups()
+//! |> ops::QueuedTweet::read()
+//! |> ops::refresh_users::rename_authors()
+//! |> ops::QueuedTweet::write()
+//! ```
+
+
+use self::super::{QueuedTweet, UserGroup, User, verify_app_profiles, verify_file};
+use self::super::super::util::span_r;
+use egg_mode::{Token, verify_tokens};
+use self::super::super::Outcome;
+use std::path::PathBuf;
+use std::io::Write;
+
+
+/// Verify if, given the current configuration, it's permitted to continue with the subsequent steps of the `refresh-users`
+/// subsystem.
+///
+/// The return value contains either the path to the file containing the global users data or why getting it or any app
+/// profile failed, the default one isn't required.
+///
+/// # Examples
+///
+/// Verifying when the global users data doesn't exist.
+///
+/// ```
+/// # use tweetr::ops::refresh_users;
+/// # use std::fs::{self, File};
+/// # use std::env::temp_dir;
+/// # use tweetr::Outcome;
+/// # use std::io::Write;
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-refresh-users-verify-0");
+/// fs::create_dir_all(&tf).unwrap();
+/// File::create(tf.join("app.toml")).unwrap().write(&[]).unwrap();
+///
+/// assert_eq!(refresh_users::verify(&("$TEMP/ops-refresh-users-verify-0".to_string(), tf)),
+///            Err(Outcome::RequiredFileFromSubsystemNonexistant {
+///                subsys: "add-user",
+///                fname: "$TEMP/ops-refresh-users-verify-0/users.toml".to_string(),
+///            }));
+/// ```
+pub fn verify(config_dir: &(String, PathBuf)) -> Result<PathBuf, Outcome> {
+    try!(verify_app_profiles(config_dir));
+    verify_file("users.toml", true, config_dir, false, "add-user")
+}
+
+/// Update the specified user's handle to its current one on Twitter, optionally printing progress.
+///
+/// Renames are always printed.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use tweetr::ops::{AppTokens, User, refresh_users};
+/// let mut user = User::new("nabijaczleweli_old", 0x81423, "529443-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4", "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5");
+///
+/// let result = refresh_users::refresh_user(&mut user, &AppTokens {
+///     key: "qzuqpwr101q4RtK9mDorI9ndm".to_string(),
+///     secret: "HW4YG3Kdcap5ovcZ5fZfBJFedKR6GQe9MtZDS9Gm34hXiirkU5".to_string(),
+///     secret_command: None,
+/// }.into(), false, &mut vec![]);
+///
+/// assert_eq!(result.exit_value(), 0);
+/// assert_eq!(user.name, "nabijaczleweli");
+/// ```
+pub fn refresh_user<'a, W: Write>(user: &mut User, app: &Token<'a>, verbose: bool, output: &mut W) -> Outcome {
+    if verbose {
+        write!(output, "Refreshing user {} with ID {}...", user.name, user.id).unwrap();
+        output.flush().unwrap();
+    }
+
+    let access_token = match user.access_token() {
+        Ok(access_token) => access_token,
+        Err(out) => return out,
+    };
+    match span_r(|| verify_tokens(app, &access_token)) {
+        (dur, Ok(resp)) => {
+            if verbose {
+                writeln!(output, " {}ms", dur.num_milliseconds()).unwrap();
+            }
+
+            if resp.response.screen_name != user.name {
+                writeln!(output, "Renamed user {} with ID {} to {}", user.name, user.id, resp.response.screen_name).unwrap();
+                user.name = resp.response.screen_name;
+            }
+
+            Outcome::NoError
+        }
+        (_, Err(e)) => {
+            if verbose {
+                writeln!(output, " FAILED").unwrap();
+            }
+            Outcome::TwitterAPIError(e.to_string())
+        }
+    }
+}
+
+/// Update the authors of tweets bound to users' IDs to those users' current handles, returning how many were updated.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::{QueuedTweet, User, refresh_users};
+/// # use chrono::DateTime;
+/// # fn main() {
+/// let mut tweets = vec![QueuedTweet {
+///                           author_id: Some(0x81423),
+///                           ..QueuedTweet::new("nabijaczleweli_old", DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap(), "Capitalism")
+///                       }];
+///
+/// assert_eq!(refresh_users::rename_authors(&mut tweets, &vec![User::new("nabijaczleweli",
+///                                                                       0x81423,
+///                                                                       "529443-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4",
+///                                                                       "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5")]), 1);
+/// assert_eq!(tweets[0].author, "nabijaczleweli");
+/// # }
+/// ```
+pub fn rename_authors(tweets: &mut Vec<QueuedTweet>, users: &Vec<User>) -> usize {
+    let mut renamed = 0;
+    for tweet in tweets.iter_mut().filter(|t| t.author_id.is_some()) {
+        if let Some(user) = users.iter().find(|u| tweet.is_by(u)) {
+            if tweet.author != user.name {
+                tweet.author = user.name.clone();
+                renamed += 1;
+            }
+        }
+    }
+    renamed
+}
+
+/// Update the handles of group members to the current handles of the users with the same IDs, given the users before and
+/// after refreshing them, returning how many were updated.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::{User, UserGroup, refresh_users};
+/// let user = User::new("nabijaczleweli_old", 0x81423, "529443-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4", "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5");
+/// let mut groups = vec![UserGroup {
+///     name: "brand-accounts".to_string(),
+///     members: vec!["Nabijaczleweli_Old".to_string(), "danerangLP".to_string()],
+///     stagger_minutes: None,
+/// }];
+///
+/// assert_eq!(refresh_users::rename_members(&mut groups, &vec![user.clone()], &vec![User { name: "nabijaczleweli".to_string(), ..user }]), 1);
+/// assert_eq!(groups[0].members, vec!["nabijaczleweli".to_string(), "danerangLP".to_string()]);
+/// ```
+pub fn rename_members(groups: &mut Vec<UserGroup>, users_before: &Vec<User>, users: &Vec<User>) -> usize {
+    let mut renamed = 0;
+    for member in groups.iter_mut().flat_map(|g| g.members.iter_mut()) {
+        let before = match users_before.iter().find(|u| u.name.eq_ignore_ascii_case(member)) {
+            Some(before) => before,
+            None => continue,
+        };
+        if let Some(user) = users.iter().find(|u| u.id == before.id && u.name != before.name) {
+            *member = user.name.clone();
+            renamed += 1;
+        }
+    }
+    renamed
+}
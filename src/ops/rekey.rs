@@ -0,0 +1,131 @@
+//! This module contains the functions used only by the `rekey` subsystem.
+//!
+//! The flow of the `rekey` subsystem is as follows:
+//!
+//! ```plaintext
+//! Options::parse()
+//! |> ops::rekey::verify()
+//! |> ops::SecretKey::unlock()
+//! |> ops::SecretKey::new()
+//! |> ops::rekey::rekey()
+//! ```
+
+
+use self::super::{AppTokens, SecretKey, User, verify_file};
+use self::super::super::Outcome;
+use std::path::{PathBuf, Path};
+use std::io::Write;
+use std::fs;
+
+
+/// Verify if, given the current configuration, it's permitted to continue with the subsequent steps of the `rekey`
+/// subsystem.
+///
+/// The return value contains either the path to the file containing the secret key and the path to the file containing
+/// the global users data, neither of which need to exist, or why getting them failed.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::rekey;
+/// # use std::env::temp_dir;
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-rekey-verify-0");
+///
+/// assert_eq!(rekey::verify(&("$TEMP/ops-rekey-verify-0".to_string(), tf.clone())),
+///            Ok((tf.join("secrets.toml"), tf.join("users.toml"))));
+/// ```
+pub fn verify(config_dir: &(String, PathBuf)) -> Result<(PathBuf, PathBuf), Outcome> {
+    let secrets = try!(verify_file("secrets.toml", true, config_dir, true, ""));
+    let users = try!(verify_file("users.toml", true, config_dir, true, ""));
+
+    Ok((secrets, users))
+}
+
+/// Re-encrypt all app profiles' secrets and users' access token secrets in the specified configuration directory with the
+/// specified new key, or store them in plaintext if `None`, printing what was converted.
+///
+/// The old key needs to be `None` if the secrets aren't encrypted yet.
+///
+/// Everything is first written to `.new` files next to the originals, which are then renamed over them, the new key
+/// last, so that an interruption doesn't leave secrets encrypted with a key that was never saved.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::{AppTokens, SecretKey, rekey};
+/// # use std::env::temp_dir;
+/// # use std::fs;
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-rekey-rekey-0");
+/// let _ = fs::remove_dir_all(&tf);
+/// fs::create_dir_all(&tf).unwrap();
+///
+/// AppTokens {
+///     key: "qzuqpwr101q4RtK9mDorI9ndm".to_string(),
+///     secret: "HW4YG3Kdcap5ovcZ5fZfBJFedKR6GQe9MtZDS9Gm34hXiirkU5".to_string(),
+///     secret_command: None,
+/// }.write(&tf.join("app.toml"), None);
+///
+/// let key = SecretKey::derive("hunter2", b"tweetr-doctest".to_vec(), 4);
+/// let mut out = vec![];
+/// assert_eq!(rekey::rekey(&(tf.join("secrets.toml"), tf.join("users.toml")), None, Some(&key), &mut out), Ok(()));
+/// assert!(tf.join("secrets.toml").exists());
+/// assert_eq!(AppTokens::read(&tf.join("app.toml"), Some(&key)).unwrap().secret,
+///            "HW4YG3Kdcap5ovcZ5fZfBJFedKR6GQe9MtZDS9Gm34hXiirkU5");
+/// ```
+pub fn rekey<W: Write>(paths: &(PathBuf, PathBuf), old_key: Option<&SecretKey>, new_key: Option<&SecretKey>, output: &mut W) -> Result<(), Outcome> {
+    let &(ref secrets_path, ref users_path) = paths;
+    let config_dir = secrets_path.parent().unwrap();
+
+    let (apps, errors) = try!(AppTokens::read_profiles(config_dir, old_key));
+    if let Some(err) = errors.into_iter().next() {
+        return Err(err);
+    }
+    let users = if users_path.exists() {
+        Some(try!(User::read(users_path, old_key).map_err(Option::unwrap)))
+    } else {
+        None
+    };
+
+    let new_secrets_path = replacement_path(secrets_path);
+    if let Some(new_key) = new_key {
+        new_key.write(&new_secrets_path);
+    }
+
+    let mut replaced = vec![];
+    for (profile, app) in &apps {
+        let app_path = config_dir.join(AppTokens::profile_file_name(profile.as_ref().map(|p| &p[..])));
+        app.write(&replacement_path(&app_path), new_key);
+        replaced.push(app_path);
+    }
+    let users_len = users.as_ref().map(Vec::len).unwrap_or(0);
+    if let Some(users) = users {
+        // Copied first, so that the user groups are kept
+        fs::copy(users_path, replacement_path(users_path)).unwrap();
+        try!(User::write(users, &replacement_path(users_path), new_key));
+        replaced.push(users_path.clone());
+    }
+
+    for path in replaced {
+        fs::rename(replacement_path(&path), path).unwrap();
+    }
+    match new_key {
+        Some(_) => fs::rename(new_secrets_path, secrets_path).unwrap(),
+        None if secrets_path.exists() => fs::remove_file(secrets_path).unwrap(),
+        None => (),
+    }
+
+    writeln!(output,
+             "{} {} app profiles and {} users.",
+             if new_key.is_some() { "Encrypted" } else { "Decrypted" },
+             apps.len(),
+             users_len)
+        .unwrap();
+    Ok(())
+}
+
+
+fn replacement_path(p: &Path) -> PathBuf {
+    let mut fname = p.file_name().unwrap().to_os_string();
+    fname.push(".new");
+    p.with_file_name(fname)
+}
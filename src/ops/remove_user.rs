@@ -0,0 +1,169 @@
+//! This module contains the functions used only by the `remove-user` subsystem.
+//!
+//! The flow of the `remove-user` subsystem is as follows:
+//!
+//! ```plaintext
+//! Options::parse()
+//! |> ops::remove_user::verify()
+//! |> ops::User::read()
+//! |> ops::UserGroup::read()
+//! |> ops::remove_user::find_user()
+//! |> ops::QueuedTweet::read()
+//! |> ops::remove_user::orphaned_tweets()
+//! |> ops::remove_user::print_orphan_warning()
+//! |> ops::remove_user::remove_from_groups()
+//! |> ops::User::write_with_groups()
+//! ```
+
+
+use self::super::{QueuedTweet, UserGroup, User, verify_file};
+use self::super::super::Outcome;
+use std::path::PathBuf;
+use std::io::Write;
+
+
+/// Verify if, given the current configuration, it's permitted to continue with the subsequent steps of the `remove-user`
+/// subsystem.
+///
+/// The return value contains either the path to the file containing the global users data or why getting it failed.
+///
+/// # Examples
+///
+/// Verifying when the global users data doesn't exist.
+///
+/// ```
+/// # use tweetr::ops::remove_user;
+/// # use std::env::temp_dir;
+/// # use tweetr::Outcome;
+/// # use std::fs;
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-remove-user-verify-0");
+/// fs::create_dir_all(&tf).unwrap();
+///
+/// assert_eq!(remove_user::verify(&("$TEMP/ops-remove-user-verify-0".to_string(), tf)),
+///            Err(Outcome::RequiredFileFromSubsystemNonexistant {
+///                subsys: "add-user",
+///                fname: "$TEMP/ops-remove-user-verify-0/users.toml".to_string(),
+///            }));
+/// ```
+pub fn verify(config_dir: &(String, PathBuf)) -> Result<PathBuf, Outcome> {
+    verify_file("users.toml", true, config_dir, false, "add-user")
+}
+
+/// Find the index of the user with the specified name, ignoring case.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::{User, remove_user};
+/// # use tweetr::Outcome;
+/// let users = vec![User::new("nabijaczleweli", 0x81423, "529443-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4", "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5")];
+///
+/// assert_eq!(remove_user::find_user(&users, "Nabijaczleweli"), Ok(0));
+/// assert_eq!(remove_user::find_user(&users, "danerangLP"),
+///            Err(Outcome::RequiredDataFromSubsystemNonexistant {
+///                subsys: "add-user",
+///                desc: "add and authorise user with name \"danerangLP\"".to_string(),
+///            }));
+/// ```
+pub fn find_user(users: &Vec<User>, name: &str) -> Result<usize, Outcome> {
+    users.iter().position(|u| u.name.eq_ignore_ascii_case(name)).ok_or_else(|| {
+        Outcome::RequiredDataFromSubsystemNonexistant {
+            subsys: "add-user",
+            desc: format!("add and authorise user with name \"{}\"", name),
+        }
+    })
+}
+
+/// Get the tweets by the specified user that still need it, i.e. ones that weren't posted yet and posted ones that weren't
+/// deleted yet, but will be.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::{QueuedTweet, User, remove_user};
+/// # use chrono::DateTime;
+/// # fn main() {
+/// let tweets = vec![QueuedTweet::new("nabijaczleweli", DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap(), "Capitalism")];
+///
+/// let orphans = remove_user::orphaned_tweets(&tweets, &User::new("nabijaczleweli",
+///                                                                0x81423,
+///                                                                "529443-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4",
+///                                                                "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5"));
+/// assert_eq!(orphans, vec![&tweets[0]]);
+/// # }
+/// ```
+pub fn orphaned_tweets<'t>(tweets: &'t Vec<QueuedTweet>, user: &User) -> Vec<&'t QueuedTweet> {
+    tweets.iter()
+        .filter(|t| t.is_by(user))
+        .filter(|t| if t.id.is_none() {
+            t.failed.is_none()
+        } else {
+            t.time_deleted.is_none() && t.deletion_time().is_some()
+        })
+        .collect()
+}
+
+/// Print a warning listing the specified tweets, which won't be posted or deleted after their author is removed.
+///
+/// Nothing is printed if there are no such tweets.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::{QueuedTweet, remove_user};
+/// # use std::iter::FromIterator;
+/// # use chrono::DateTime;
+/// # fn main() {
+/// let tweet = QueuedTweet::new("nabijaczleweli", DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap(), "Capitalism");
+///
+/// let mut out = Vec::new();
+/// remove_user::print_orphan_warning(&mut out, "nabijaczleweli", &vec![&tweet]);
+/// assert_eq!(String::from_iter(out.iter().map(|&i| i as char)),
+///            "Warning: 1 queued tweets by nabijaczleweli will be orphaned:\n  \
+///               \"Capitalism\" scheduled for 2016-09-09T00:33:30+02:00\n");
+/// # }
+/// ```
+pub fn print_orphan_warning<W: Write>(output: &mut W, name: &str, orphans: &Vec<&QueuedTweet>) {
+    if orphans.is_empty() {
+        return;
+    }
+
+    writeln!(output, "Warning: {} queued tweets by {} will be orphaned:", orphans.len(), name).unwrap();
+    for tweet in orphans {
+        writeln!(output, "  \"{}\" scheduled for {}", tweet.content, tweet.time.to_rfc3339()).unwrap();
+    }
+}
+
+/// Remove the user with the specified name, matched ignoring case, from all groups it's a member of, returning the names of
+/// those groups.
+///
+/// Groups left without members are kept, tweets queued for them are dropped by the daemon.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::{UserGroup, remove_user};
+/// let mut groups = vec![UserGroup {
+///                           name: "everyone".to_string(),
+///                           members: vec!["nabijaczleweli".to_string(), "danerangLP".to_string()],
+///                           stagger_minutes: None,
+///                       }];
+///
+/// assert_eq!(remove_user::remove_from_groups(&mut groups, "Nabijaczleweli"), vec!["everyone".to_string()]);
+/// assert_eq!(groups[0].members, vec!["danerangLP".to_string()]);
+/// ```
+pub fn remove_from_groups(groups: &mut Vec<UserGroup>, name: &str) -> Vec<String> {
+    let mut left = vec![];
+    for group in groups {
+        let members = group.members.len();
+        group.members.retain(|m| !m.eq_ignore_ascii_case(name));
+        if group.members.len() != members {
+            left.push(group.name.clone());
+        }
+    }
+    left
+}
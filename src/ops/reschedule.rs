@@ -0,0 +1,74 @@
+//! This module contains the functions used only by the `reschedule` subsystem.
+//!
+//! The flow of the `reschedule` subsystem is as follows:
+//!
+//! ```plaintext
+//! Options::parse()
+//! |> ops::reschedule::verify()
+//! |> ops::QueuedTweet::read()
+//! |> ops::reschedule::shift()
+//! |> ops::QueuedTweet::write()
+//! ```
+
+
+use self::super::{TweetSelector, QueuedTweet, verify_file};
+use self::super::super::Outcome;
+use std::path::PathBuf;
+use chrono::Duration;
+
+
+/// Verify if, given the current configuration, it's permitted to continue with the subsequent steps of the `reschedule`
+/// subsystem.
+///
+/// The return value contains the path to the file containing the queued tweets.
+///
+/// # Examples
+///
+/// Verifying a directory without queued tweets.
+///
+/// ```
+/// # use std::env::temp_dir;
+/// # use tweetr::ops::reschedule::verify;
+/// # use tweetr::Outcome;
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-reschedule-verify");
+/// assert_eq!(verify(&("$TEMP/ops-reschedule-verify".to_string(), tf)),
+///            Err(Outcome::RequiredFileFromSubsystemNonexistant {
+///                subsys: "queue-tweet",
+///                fname: "$TEMP/ops-reschedule-verify/tweets.toml".to_string(),
+///            }));
+/// ```
+pub fn verify(config_dir: &(String, PathBuf)) -> Result<PathBuf, Outcome> {
+    verify_file("tweets.toml", true, config_dir, false, "queue-tweet")
+}
+
+/// Move all tweets picked by the specified selector by the specified amount of time, returning how many were moved.
+///
+/// The posting deadlines, expiry times and deletion times, if any, are moved along with the tweets, so relative order and
+/// spacing are retained.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::{TweetSelector, QueuedTweet};
+/// # use tweetr::ops::reschedule::shift;
+/// # use chrono::{DateTime, Duration};
+/// # fn main() {
+/// let mut tweets = vec![QueuedTweet::new("nabijaczleweli", DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap(), "Capitalism")];
+///
+/// assert_eq!(shift(&mut tweets, &TweetSelector::default(), Duration::hours(2)), 1);
+/// assert_eq!(tweets[0].time, DateTime::parse_from_rfc3339("2016-09-09T02:33:30+02:00").unwrap());
+/// # }
+/// ```
+pub fn shift(tweets: &mut Vec<QueuedTweet>, selector: &TweetSelector, by: Duration) -> usize {
+    let mut shifted = 0;
+    for tweet in tweets.iter_mut().filter(|t| selector.matches(t)) {
+        tweet.time = tweet.time + by;
+        tweet.post_by = tweet.post_by.map(|pb| pb + by);
+        tweet.expires = tweet.expires.map(|e| e + by);
+        tweet.delete_at = tweet.delete_at.map(|da| da + by);
+        shifted += 1;
+    }
+    shifted
+}
@@ -0,0 +1,92 @@
+//! This module contains the functions used only by the `respread` subsystem.
+//!
+//! The flow of the `respread` subsystem is as follows:
+//!
+//! ```plaintext
+//! Options::parse()
+//! |> ops::respread::verify()
+//! |> ops::QueuedTweet::read()
+//! |> ops::respread::respread()
+//! |> ops::QueuedTweet::write()
+//! ```
+
+
+use self::super::{TweetSelector, QueuedTweet, verify_file};
+use chrono::{DateTime, FixedOffset};
+use self::super::super::Outcome;
+use std::path::PathBuf;
+
+
+/// Verify if, given the current configuration, it's permitted to continue with the subsequent steps of the `respread`
+/// subsystem.
+///
+/// The return value contains the path to the file containing the queued tweets.
+///
+/// # Examples
+///
+/// Verifying a directory without queued tweets.
+///
+/// ```
+/// # use std::env::temp_dir;
+/// # use tweetr::ops::respread::verify;
+/// # use tweetr::Outcome;
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-respread-verify");
+/// assert_eq!(verify(&("$TEMP/ops-respread-verify".to_string(), tf)),
+///            Err(Outcome::RequiredFileFromSubsystemNonexistant {
+///                subsys: "queue-tweet",
+///                fname: "$TEMP/ops-respread-verify/tweets.toml".to_string(),
+///            }));
+/// ```
+pub fn verify(config_dir: &(String, PathBuf)) -> Result<PathBuf, Outcome> {
+    verify_file("tweets.toml", true, config_dir, false, "queue-tweet")
+}
+
+/// Distribute all tweets picked by the specified selector evenly between `start` and `end`, returning how many were
+/// moved.
+///
+/// The first tweet is moved to `start` and the last one to `end`, with the relative order of all of them retained.
+/// A single tweet is moved to `start`. A tweet's `post_by` deadline, expiry time and `delete_at` time are moved by as much as
+/// the tweet itself, like in `reschedule::shift()`.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::{TweetSelector, QueuedTweet};
+/// # use tweetr::ops::respread::respread;
+/// # use chrono::DateTime;
+/// # fn main() {
+/// let tweet = QueuedTweet::new("nabijaczleweli", DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap(), "Capitalism");
+/// let mut tweets = vec![tweet.clone(), tweet.clone(), tweet];
+///
+/// assert_eq!(respread(&mut tweets,
+///                     &TweetSelector::default(),
+///                     DateTime::parse_from_rfc3339("2016-09-10T12:00:00+02:00").unwrap(),
+///                     DateTime::parse_from_rfc3339("2016-09-10T14:00:00+02:00").unwrap()),
+///            3);
+/// assert_eq!(tweets[1].time, DateTime::parse_from_rfc3339("2016-09-10T13:00:00+02:00").unwrap());
+/// # }
+/// ```
+pub fn respread(tweets: &mut Vec<QueuedTweet>, selector: &TweetSelector, start: DateTime<FixedOffset>, end: DateTime<FixedOffset>) -> usize {
+    let mut indices: Vec<_> = tweets.iter().enumerate().filter(|&(_, t)| selector.matches(t)).map(|(i, _)| i).collect();
+    indices.sort_by_key(|&i| tweets[i].time);
+
+    let window = end - start;
+    let steps = if indices.len() > 1 {
+        indices.len() as i32 - 1
+    } else {
+        1
+    };
+    for (k, &i) in indices.iter().enumerate() {
+        let tweet = &mut tweets[i];
+        let new_time = start + window * k as i32 / steps;
+        let by = new_time - tweet.time;
+        tweet.post_by = tweet.post_by.map(|pb| pb + by);
+        tweet.expires = tweet.expires.map(|e| e + by);
+        tweet.delete_at = tweet.delete_at.map(|da| da + by);
+        tweet.time = new_time;
+    }
+
+    indices.len()
+}
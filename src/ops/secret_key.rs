@@ -0,0 +1,266 @@
+//! Passphrase-derived key used to encrypt secrets at rest
+//!
+//! Serialised via `SecretKeyForSerialisation`, which has the salt encoded as base64 and a known value encrypted with the
+//! key, so that a wrong passphrase can be told apart from a corrupted secret.
+
+
+use rustc_serialize::base64::{self, FromBase64, ToBase64};
+use self::super::super::util::create_private_file;
+use crypto::aead::{AeadEncryptor, AeadDecryptor};
+use crypto::chacha20poly1305::ChaCha20Poly1305;
+use crypto::scrypt::{ScryptParams, scrypt};
+use self::super::super::Outcome;
+use self::super::read_toml_file;
+use rand::{OsRng, Rng};
+use toml::encode_str;
+use std::path::Path;
+use std::io::Write;
+
+
+/// Prefix of encrypted secrets, secrets without it are stored in plaintext.
+pub static ENCRYPTED_PREFIX: &'static str = "enc:";
+
+/// Base-2 logarithm of the scrypt CPU/memory cost used for new keys.
+pub static DEFAULT_LOG_N: u8 = 15;
+
+static CHECK_VALUE: &'static str = "tweetr";
+static NONCE_LEN: usize = 8;
+static TAG_LEN: usize = 16;
+
+
+/// A key derived from a passphrase via scrypt, used to encrypt the app secrets and users' access token secrets.
+///
+/// Stored in `secrets.toml` in the configuration directory, whose presence means that the other configuration files'
+/// secrets are encrypted.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct SecretKey {
+    salt: Vec<u8>,
+    log_n: u8,
+    key: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+struct SecretKeyForSerialisation {
+    salt: String,
+    log_n: u8,
+    check: String,
+}
+
+
+impl SecretKey {
+    /// Derive a new key with a random salt from the specified passphrase.
+    pub fn new(passphrase: &str) -> SecretKey {
+        let mut salt = vec![0u8; 16];
+        OsRng::new().unwrap().fill_bytes(&mut salt);
+        SecretKey::derive(passphrase, salt, DEFAULT_LOG_N)
+    }
+
+    /// Derive the key from the specified passphrase with the specified salt and scrypt cost.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::SecretKey;
+    /// let key = SecretKey::derive("hunter2", b"tweetr-doctest".to_vec(), 4);
+    /// assert_eq!(key, SecretKey::derive("hunter2", b"tweetr-doctest".to_vec(), 4));
+    /// assert!(key != SecretKey::derive("hunter3", b"tweetr-doctest".to_vec(), 4));
+    /// ```
+    pub fn derive(passphrase: &str, salt: Vec<u8>, log_n: u8) -> SecretKey {
+        let mut key = vec![0u8; 32];
+        scrypt(passphrase.as_bytes(), &salt, &ScryptParams::new(log_n, 8, 1), &mut key);
+
+        SecretKey {
+            salt: salt,
+            log_n: log_n,
+            key: key,
+        }
+    }
+
+    /// Read the key from the `secrets.toml` file in the specified configuration directory with the specified passphrase, if
+    /// the secrets there are encrypted.
+    ///
+    /// Fails with `Outcome::InvalidCredentials` if they are, but there's no passphrase, or it's wrong, and with
+    /// `Outcome::FileParsingFailed` if the file can't be read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::SecretKey;
+    /// # use std::env::temp_dir;
+    /// # use tweetr::Outcome;
+    /// # use std::fs;
+    /// let tf = temp_dir().join("tweetr-doctest").join("ops-secret-key-unlock-0");
+    /// let _ = fs::remove_dir_all(&tf);
+    /// fs::create_dir_all(&tf).unwrap();
+    ///
+    /// assert_eq!(SecretKey::unlock(&tf, Some("hunter2")), Ok(None));
+    ///
+    /// let key = SecretKey::derive("hunter2", b"tweetr-doctest".to_vec(), 4);
+    /// key.write(&tf.join("secrets.toml"));
+    /// assert_eq!(SecretKey::unlock(&tf, Some("hunter2")), Ok(Some(key)));
+    /// assert!(SecretKey::unlock(&tf, None).is_err());
+    /// assert_eq!(SecretKey::unlock(&tf, Some("hunter3")),
+    ///            Err(Outcome::InvalidCredentials(vec!["wrong passphrase".to_string()])));
+    /// ```
+    pub fn unlock(config_dir: &Path, passphrase: Option<&str>) -> Result<Option<SecretKey>, Outcome> {
+        let secrets_path = config_dir.join("secrets.toml");
+        if !secrets_path.exists() {
+            return Ok(None);
+        }
+
+        match passphrase {
+            Some(passphrase) => {
+                SecretKey::read(&secrets_path, passphrase).map(Some).map_err(|e| {
+                    e.unwrap_or_else(|| {
+                        Outcome::FileParsingFailed {
+                            desc: "secret key",
+                            errors: vec![format!("couldn't read {}", secrets_path.display())],
+                        }
+                    })
+                })
+            }
+            None => Err(Outcome::InvalidCredentials(vec!["secrets are encrypted, but no passphrase was specified".to_string()])),
+        }
+    }
+
+    /// Read the key from the specified file, deriving it from the specified passphrase.
+    ///
+    /// Fails with `Outcome::InvalidCredentials` if the passphrase is wrong.
+    pub fn read(p: &Path, passphrase: &str) -> Result<SecretKey, Option<Outcome>> {
+        let serialised: SecretKeyForSerialisation = try!(read_toml_file(p, "secret key"));
+        let salt = try!(serialised.salt.from_base64().map_err(|e| {
+            Some(Outcome::FileParsingFailed {
+                desc: "secret key",
+                errors: vec![format!("salt \"{}\" isn't valid base64: {}", serialised.salt, e)],
+            })
+        }));
+
+        let key = SecretKey::derive(passphrase, salt, serialised.log_n);
+        match key.decrypt(&serialised.check) {
+            Ok(ref check) if check == CHECK_VALUE => Ok(key),
+            _ => Err(Some(Outcome::InvalidCredentials(vec!["wrong passphrase".to_string()]))),
+        }
+    }
+
+    /// Save the key's salt and cost to the specified file.
+    ///
+    /// The key itself isn't saved.
+    pub fn write(&self, p: &Path) {
+        create_private_file(p)
+            .unwrap()
+            .write_all(encode_str(&SecretKeyForSerialisation {
+                    salt: self.salt.to_base64(base64::STANDARD),
+                    log_n: self.log_n,
+                    check: self.encrypt(CHECK_VALUE),
+                })
+                .as_bytes())
+            .unwrap();
+    }
+
+    /// Check whether the specified secret is encrypted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::SecretKey;
+    /// assert!(SecretKey::is_encrypted("enc:GTxbSkRZ06Vi0OA8aZRvnSiLHa2mTuwdHXeT"));
+    /// assert!(!SecretKey::is_encrypted("HW4YG3Kdcap5ovcZ5fZfBJFedKR6GQe9MtZDS9Gm34hXiirkU5"));
+    /// ```
+    pub fn is_encrypted(secret: &str) -> bool {
+        secret.starts_with(ENCRYPTED_PREFIX)
+    }
+
+    /// Encrypt the specified secret with a random nonce.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::SecretKey;
+    /// let key = SecretKey::derive("hunter2", b"tweetr-doctest".to_vec(), 4);
+    /// let encrypted = key.encrypt("HW4YG3Kdcap5ovcZ5fZfBJFedKR6GQe9MtZDS9Gm34hXiirkU5");
+    ///
+    /// assert!(SecretKey::is_encrypted(&encrypted));
+    /// assert_eq!(key.decrypt(&encrypted), Ok("HW4YG3Kdcap5ovcZ5fZfBJFedKR6GQe9MtZDS9Gm34hXiirkU5".to_string()));
+    /// ```
+    pub fn encrypt(&self, secret: &str) -> String {
+        let mut data = vec![0u8; NONCE_LEN + TAG_LEN + secret.len()];
+        OsRng::new().unwrap().fill_bytes(&mut data[..NONCE_LEN]);
+
+        let (nonce, rest) = data.split_at_mut(NONCE_LEN);
+        let (tag, encrypted) = rest.split_at_mut(TAG_LEN);
+        ChaCha20Poly1305::new(&self.key, nonce, &[]).encrypt(secret.as_bytes(), encrypted, tag);
+
+        format!("{}{}", ENCRYPTED_PREFIX, data.to_base64(base64::STANDARD))
+    }
+
+    /// Decrypt the specified secret, plaintext secrets are returned as-is.
+    ///
+    /// Fails if the secret was encrypted with a different key or is corrupted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::SecretKey;
+    /// let key = SecretKey::derive("hunter2", b"tweetr-doctest".to_vec(), 4);
+    /// let encrypted = key.encrypt("HW4YG3Kdcap5ovcZ5fZfBJFedKR6GQe9MtZDS9Gm34hXiirkU5");
+    ///
+    /// assert_eq!(key.decrypt("HW4YG3Kdcap5ovcZ5fZfBJFedKR6GQe9MtZDS9Gm34hXiirkU5"),
+    ///            Ok("HW4YG3Kdcap5ovcZ5fZfBJFedKR6GQe9MtZDS9Gm34hXiirkU5".to_string()));
+    /// assert_eq!(SecretKey::derive("hunter3", b"tweetr-doctest".to_vec(), 4).decrypt(&encrypted), Err(()));
+    /// ```
+    pub fn decrypt(&self, secret: &str) -> Result<String, ()> {
+        if !SecretKey::is_encrypted(secret) {
+            return Ok(secret.to_string());
+        }
+
+        let data = try!(secret[ENCRYPTED_PREFIX.len()..].from_base64().map_err(|_| ()));
+        if data.len() < NONCE_LEN + TAG_LEN {
+            return Err(());
+        }
+
+        let (nonce, rest) = data.split_at(NONCE_LEN);
+        let (tag, encrypted) = rest.split_at(TAG_LEN);
+        let mut decrypted = vec![0u8; encrypted.len()];
+        if ChaCha20Poly1305::new(&self.key, nonce, &[]).decrypt(encrypted, &mut decrypted, tag) {
+            String::from_utf8(decrypted).map_err(|_| ())
+        } else {
+            Err(())
+        }
+    }
+
+    /// Decrypt the specified secret of the specified description with the specified key, if any.
+    ///
+    /// Without a key, the secret is returned as-is, so that encrypted secrets survive being read and written back by
+    /// subsystems that don't use them.
+    ///
+    /// Fails with `Outcome::InvalidCredentials` if the secret doesn't decrypt.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::SecretKey;
+    /// # use tweetr::Outcome;
+    /// let key = SecretKey::derive("hunter2", b"tweetr-doctest".to_vec(), 4);
+    /// let encrypted = key.encrypt("HW4YG3Kdcap5ovcZ5fZfBJFedKR6GQe9MtZDS9Gm34hXiirkU5");
+    ///
+    /// assert_eq!(SecretKey::reveal(Some(&key), &encrypted, "app secret"),
+    ///            Ok("HW4YG3Kdcap5ovcZ5fZfBJFedKR6GQe9MtZDS9Gm34hXiirkU5".to_string()));
+    /// assert_eq!(SecretKey::reveal(None, &encrypted, "app secret"), Ok(encrypted.clone()));
+    /// assert_eq!(SecretKey::reveal(Some(&SecretKey::derive("hunter3", b"tweetr-doctest".to_vec(), 4)), &encrypted, "app secret"),
+    ///            Err(Outcome::InvalidCredentials(vec!["failed to decrypt app secret".to_string()])));
+    /// ```
+    pub fn reveal(key: Option<&SecretKey>, secret: &str, desc: &str) -> Result<String, Outcome> {
+        match key {
+            Some(key) => key.decrypt(secret).map_err(|_| Outcome::InvalidCredentials(vec![format!("failed to decrypt {}", desc)])),
+            None => Ok(secret.to_string()),
+        }
+    }
+
+    /// Encrypt the specified secret with the specified key, if any, leaving it as-is otherwise.
+    pub fn conceal(key: Option<&SecretKey>, secret: String) -> String {
+        match key {
+            Some(key) => key.encrypt(&secret),
+            None => secret,
+        }
+    }
+}
@@ -7,6 +7,7 @@
 //! ```plaintext
 //! Options::parse()
 //! |> ops::start_daemon::verify()
+//! |> ops::start_daemon::check_permissions()
 //! |> ops::AppTokens::read()
 //! ```
 //!
@@ -16,28 +17,136 @@
 //! init_data
 //! |> ops::User::read()
 //! |> ops::QueuedTweet::read()
+//! |> ops::UserGroup::read()
+//! |> ops::start_daemon::expand_groups()
+//! |> ops::QueuedTweet::resolve_anchors()
+//! |> ops::start_daemon::expire_tweets()
+//! |> ops::start_daemon::apply_catch_up_policy()
 //! |> ops::start_daemon::tweet_indices_to_post()
 //! |> ops::start_daemon::find_user_index_for_tweet()
 //! |> ops::start_daemon::post_tweet()
 //! ```
+//!
+//! Then:
+//!
+//! ```plaintext
+//! init_data
+//! |> ops::start_daemon::tweet_indices_to_delete()
+//! |> ops::start_daemon::find_user_index_for_tweet()
+//! |> ops::start_daemon::delete_tweet()
+//! ```
+//!
+//! Then, if the evergreen pools file exists, for each pool:
+//!
+//! ```plaintext
+//! init_data
+//! |> ops::EvergreenPool::read()
+//! |> ops::start_daemon::evergreen_tweet_index_to_post()
+//! |> ops::start_daemon::evergreen_tweet_to_post()
+//! |> ops::start_daemon::find_user_index_for_tweet()
+//! |> ops::start_daemon::post_tweet()
+//! |> ops::EvergreenPool::write()
+//! ```
 
 
-use self::super::super::util::{TWEET_DATETIME_FORMAT, span_r};
-use self::super::{QueuedTweet, User, verify_file};
+use self::super::{EvergreenSelection, EvergreenPool, QueuedTweet, TweetAction, TweetTarget, Suspension, Recipient, UserGroup, User, Poll};
+use self::super::super::util::{TWEET_DATETIME_FORMAT, file_mode, span_r};
+use egg_mode::tweet::{DraftTweet, retweet, unlike, delete, like};
+use chrono::{DateTime, FixedOffset, Duration, Local};
+use self::super::{verify_app_profiles, verify_file};
+use egg_mode::error::Error::TwitterError;
 use self::super::super::Outcome;
-use egg_mode::tweet::DraftTweet;
-use chrono::{DateTime, Local};
-use std::path::PathBuf;
+use std::path::{PathBuf, Path};
+use self::super::super::api;
+use rand::{Rng, thread_rng};
+use egg_mode::error::Error;
+use std::str::FromStr;
 use egg_mode::Token;
 use std::io::Write;
+use std::cmp::max;
+use std::fs;
+
+
+/// What to do with tweets that are overdue, e.g. after the daemon was down for a while.
+///
+/// Tweets with a `post_by` deadline ignore this, being posted if it hasn't passed yet and dropped otherwise.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum CatchUpPolicy {
+    /// Post all overdue tweets.
+    PostAll,
+    /// Post only tweets late by less than the specified amount, skipping the rest.
+    ///
+    /// Skipped tweets stay queued and unposted, so they can still be moved with `reschedule` or `respread`.
+    SkipLate(Duration),
+    /// Post only tweets late by less than the specified amount, dropping the rest.
+    ///
+    /// Dropped tweets are marked as failed.
+    DropLate(Duration),
+    /// Spread tweets due at once after the daemon was down evenly over the specified amount of time, starting now.
+    ///
+    /// This is done only once, when the daemon starts.
+    Respread(Duration),
+}
+
+impl CatchUpPolicy {
+    /// Get the maximum lateness of a tweet to still be posted under this policy, if any.
+    pub fn max_lateness(&self) -> Option<Duration> {
+        match *self {
+            CatchUpPolicy::PostAll |
+            CatchUpPolicy::Respread(_) => None,
+            CatchUpPolicy::SkipLate(dur) |
+            CatchUpPolicy::DropLate(dur) => Some(dur),
+        }
+    }
+}
+
+impl FromStr for CatchUpPolicy {
+    type Err = ();
+
+    /// Parse a catch-up policy from `all` or `skip:`/`drop:`/`respread:` followed by a non-negative amount of minutes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate tweetr;
+    /// # extern crate chrono;
+    /// # use tweetr::ops::start_daemon::CatchUpPolicy;
+    /// # use std::str::FromStr;
+    /// # use chrono::Duration;
+    /// # fn main() {
+    /// assert_eq!(CatchUpPolicy::from_str("all"), Ok(CatchUpPolicy::PostAll));
+    /// assert_eq!(CatchUpPolicy::from_str("skip:10"), Ok(CatchUpPolicy::SkipLate(Duration::minutes(10))));
+    /// assert_eq!(CatchUpPolicy::from_str("drop:30"), Ok(CatchUpPolicy::DropLate(Duration::minutes(30))));
+    /// assert_eq!(CatchUpPolicy::from_str("respread:120"), Ok(CatchUpPolicy::Respread(Duration::minutes(120))));
+    ///
+    /// assert!(CatchUpPolicy::from_str("skip").is_err());
+    /// assert!(CatchUpPolicy::from_str("respread:soon").is_err());
+    /// assert!(CatchUpPolicy::from_str("skip:-5").is_err());
+    /// # }
+    /// ```
+    fn from_str(s: &str) -> Result<CatchUpPolicy, ()> {
+        if s == "all" {
+            return Ok(CatchUpPolicy::PostAll);
+        }
+
+        let mut parts = s.splitn(2, ':');
+        let policy = parts.next().unwrap();
+        let minutes = Duration::minutes(try!(parts.next().and_then(|m| u32::from_str(m).ok()).ok_or(())) as i64);
+        match policy {
+            "skip" => Ok(CatchUpPolicy::SkipLate(minutes)),
+            "drop" => Ok(CatchUpPolicy::DropLate(minutes)),
+            "respread" => Ok(CatchUpPolicy::Respread(minutes)),
+            _ => Err(()),
+        }
+    }
+}
 
 
 /// Verify if, given the current configuration, it's permitted to continue with the subsequent steps of the `start-daemon`
 /// subsystem.
 ///
-/// The return value contains either the path to the file containing the global app configuration, the path to the file
-/// containing the global users data and the path to the file containing the global queued tweets data or why getting them
-/// failed.
+/// The return value contains either the path to the file containing the global users data and the path to the file
+/// containing the global queued tweets data or why getting them or any app profile failed, the default one isn't required.
 ///
 /// # Examples
 ///
@@ -51,12 +160,12 @@ use std::io::Write;
 /// # use std::io::Write;
 /// let tf = temp_dir().join("tweetr-doctest").join("ops-start-daemon-verify-0");
 /// fs::create_dir_all(&tf).unwrap();
-/// File::create(tf.join("app.toml")).unwrap().write(&[]).unwrap();
+/// File::create(tf.join("app-staging.toml")).unwrap().write(&[]).unwrap();
 /// File::create(tf.join("users.toml")).unwrap().write(&[]).unwrap();
 /// File::create(tf.join("tweets.toml")).unwrap().write(&[]).unwrap();
 ///
 /// assert_eq!(start_daemon::verify(&("$TEMP/ops-start-daemon-verify-0".to_string(), tf.clone())),
-///            Ok((tf.join("app.toml"), tf.join("users.toml"), tf.join("tweets.toml"))));
+///            Ok((tf.join("users.toml"), tf.join("tweets.toml"))));
 /// ```
 ///
 /// Verifying with users data nonexistant.
@@ -78,73 +187,487 @@ use std::io::Write;
 ///                fname: "$TEMP/ops-start-daemon-verify-1/users.toml".to_string(),
 ///            }));
 /// ```
-pub fn verify(config_dir: &(String, PathBuf)) -> Result<(PathBuf, PathBuf, PathBuf), Outcome> {
-    let app = try!(verify_file("app.toml", true, config_dir, false, "init"));
+pub fn verify(config_dir: &(String, PathBuf)) -> Result<(PathBuf, PathBuf), Outcome> {
+    try!(verify_app_profiles(config_dir));
     let users = try!(verify_file("users.toml", true, config_dir, false, "add-user"));
     let tweets = try!(verify_file("tweets.toml", true, config_dir, false, "queue-tweet"));
 
-    Ok((app, users, tweets))
+    Ok((users, tweets))
 }
 
-/// Get the indices of tweets to post now from the provided batch based on whether thy've been posted already and the current
-/// time.
+/// Check whether the files containing secrets in the specified configuration directory, i.e. the app profiles and users
+/// data, are accessible only to their owner.
 ///
-/// All returned indices are guaranteed to be valid.
+/// Always succeeds on non-Unix platforms.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::util::create_private_file;
+/// # use tweetr::ops::start_daemon;
+/// # use std::env::temp_dir;
+/// # use tweetr::Outcome;
+/// # use std::fs;
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-start-daemon-check_permissions-0");
+/// let _ = fs::remove_dir_all(&tf);
+/// fs::create_dir_all(&tf).unwrap();
+/// create_private_file(&tf.join("app.toml")).unwrap();
+/// fs::File::create(tf.join("tweets.toml")).unwrap();
+///
+/// assert_eq!(start_daemon::check_permissions(&("$TEMP/ops-start-daemon-check_permissions-0".to_string(), tf)),
+///            Outcome::NoError);
+/// ```
+pub fn check_permissions(config_dir: &(String, PathBuf)) -> Outcome {
+    let mut insecure: Vec<_> = match fs::read_dir(&config_dir.1) {
+            Ok(entries) => entries.filter_map(|e| e.ok()).map(|e| e.file_name().to_string_lossy().into_owned()).collect(),
+            Err(_) => vec![],
+        }
+        .into_iter()
+        .filter(|fname| fname == "users.toml" || (fname.starts_with("app") && fname.ends_with(".toml")))
+        .filter_map(|fname| match file_mode(&config_dir.1.join(&fname)) {
+            Some(mode) if mode & 0o077 != 0 => {
+                Some(format!("{} (mode {:o})", PathBuf::from(&config_dir.0).join(&fname).to_str().unwrap().replace("\\", "/"), mode))
+            }
+            _ => None,
+        })
+        .collect();
+    insecure.sort();
+
+    if insecure.is_empty() {
+        Outcome::NoError
+    } else {
+        Outcome::InsecurePermissions(insecure)
+    }
+}
+
+/// Get the path to the file containing the global evergreen pools.
+///
+/// Unlike the other files, this one is optional.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::start_daemon;
+/// # use std::env::temp_dir;
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-start-daemon-evergreen_path-0");
+/// assert_eq!(start_daemon::evergreen_path(&tf), tf.join("evergreen.toml"));
+/// ```
+pub fn evergreen_path(config_dir: &Path) -> PathBuf {
+    config_dir.join("evergreen.toml")
+}
+
+/// Replace all unposted tweets by user groups with one tweet per member, each one staggered after the previous one,
+/// printing each expanded tweet.
+///
+/// The first member's tweet keeps the group tweet's `local_id`, so tweets anchored to it follow the first post.
+///
+/// Every member's tweet keeps the group tweet's `post_by` deadline, even if the stagger pushes it past it, in which case it's
+/// dropped like any other tweet that missed its deadline. Tweets by groups without members are dropped, as there's no-one
+/// to post them.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::{QueuedTweet, UserGroup, start_daemon};
+/// # use chrono::{DateTime, Duration};
+/// # fn main() {
+/// let time = DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap();
+/// let mut tweets = vec![
+///     QueuedTweet::new("brand-accounts", time, "We're launching today!"),
+/// ];
+///
+/// start_daemon::expand_groups(&mut tweets, &vec![UserGroup {
+///     name: "brand-accounts".to_string(),
+///     members: vec!["brand".to_string(), "brand_support".to_string(), "brand_dev".to_string()],
+///     stagger_minutes: Some(10),
+/// }], &mut vec![]);
+/// assert_eq!(tweets.iter().map(|t| &t.author[..]).collect::<Vec<_>>(), vec!["brand", "brand_support", "brand_dev"]);
+/// assert_eq!(tweets[2].time, time + Duration::minutes(20));
+/// # }
+/// ```
+pub fn expand_groups<W: Write>(tweets: &mut Vec<QueuedTweet>, groups: &Vec<UserGroup>, output: &mut W) {
+    let mut i = 0;
+    while i < tweets.len() {
+        let group = match groups.iter().find(|g| g.name == tweets[i].author) {
+            Some(group) if tweets[i].id.is_none() && tweets[i].failed.is_none() => group,
+            _ => {
+                i += 1;
+                continue;
+            }
+        };
+
+        if group.members.is_empty() {
+            let tweet = &mut tweets[i];
+            let reason = format!("group {} has no members", group.name);
+            writeln!(output, "Dropped tweet \"{}\" scheduled for {:?} by {}: {}", tweet.content, tweet.time, tweet.author, reason).unwrap();
+            tweet.failed = Some(reason);
+            i += 1;
+            continue;
+        }
+
+        let tweet = tweets.remove(i);
+        let stagger = Duration::minutes(group.stagger_minutes.unwrap_or(0) as i64);
+        writeln!(output,
+                 "Expanded tweet \"{}\" scheduled for {:?} by {} into {} tweets",
+                 tweet.content,
+                 tweet.time,
+                 group.name,
+                 group.members.len())
+            .unwrap();
+
+        for (k, member) in group.members.iter().enumerate() {
+            let offset = stagger * k as i32;
+            tweets.insert(i + k,
+                          QueuedTweet {
+                              author: member.clone(),
+                              author_id: None,
+                              time: tweet.time + offset,
+                              local_id: if k == 0 { tweet.local_id.clone() } else { None },
+                              after_minutes: tweet.after.as_ref().map(|_| tweet.after_minutes.unwrap_or(0) + offset.num_minutes() as u64),
+                              ..tweet.clone()
+                          });
+        }
+        i += group.members.len();
+    }
+}
+
+/// Drop all unposted tweets past their expiry time, printing each one.
+///
+/// Expired tweets are marked as such via `QueuedTweet::failed`.
 ///
 /// # Examples
 ///
 /// ```
 /// # extern crate tweetr;
 /// # extern crate chrono;
-/// # use tweetr::ops::{QueuedTweet, start_daemon};
+/// # use tweetr::ops::start_daemon;
+/// # use tweetr::ops::QueuedTweet;
 /// # use chrono::{Duration, Local};
 /// # fn main() {
 /// let now = Local::now();
 /// let now = now.with_timezone(now.offset());
 ///
-/// assert_eq!(start_daemon::tweet_indices_to_post(&vec![
+/// let mut tweets = vec![
 ///     QueuedTweet {
-///         author: "nabijaczleweli".to_string(),
-///         time: now + Duration::hours(1),
-///         content: "This tweet is not going to be posted (it's too early)".to_string(),
-///         time_posted: None,
-///         id: None,
+///         expires: Some(now - Duration::hours(1)),
+///         ..QueuedTweet::new("nabijaczleweli", now - Duration::hours(2), "The stream starts in 10 minutes!")
 ///     },
 ///     QueuedTweet {
-///         author: "nabijaczleweli".to_string(),
-///         time: now - Duration::hours(1),
-///         content: "This tweet is going to be posted".to_string(),
-///         time_posted: None,
-///         id: None,
+///         expires: Some(now + Duration::hours(1)),
+///         ..QueuedTweet::new("nabijaczleweli", now - Duration::hours(2), "The stream is on!")
 ///     },
+/// ];
+///
+/// let mut output = vec![];
+/// start_daemon::expire_tweets(&mut tweets, &mut output);
+/// assert!(tweets[0].failed.is_some());
+/// assert!(tweets[1].failed.is_none());
+/// assert!(!output.is_empty());
+/// # }
+/// ```
+pub fn expire_tweets<W: Write>(tweets: &mut Vec<QueuedTweet>, output: &mut W) {
+    let now = Local::now();
+    let now = now.with_timezone(now.offset());
+
+    for tweet in tweets.iter_mut().filter(|t| t.id.is_none() && t.failed.is_none()) {
+        if let Some(expires) = tweet.expires {
+            if expires <= now {
+                writeln!(output, "Expired tweet \"{}\" scheduled for {:?} by {}: not posted by {:?}", tweet.content, tweet.time, tweet.author, expires)
+                    .unwrap();
+                tweet.failed = Some(format!("expired at {:?}", expires));
+            }
+        }
+    }
+}
+
+/// Apply the specified catch-up policy to overdue tweets, dropping or rescheduling them as needed and printing what was done.
+///
+/// Dropped tweets are marked as such via `QueuedTweet::failed`. Skipped tweets are left alone, since
+/// `tweet_indices_to_post()` doesn't return them, and are only reported on the first pass.
+///
+/// Tweets are only respread on the first pass since the daemon started, and only if the earliest due one is late by more
+/// than `delay`, i.e. the time the daemon waits between passes, so that only tweets that piled up while it was down are
+/// moved. Tweets by suspended or unknown users aren't respread, and neither are ones that would be moved onto or past their
+/// `post_by` deadline or expiry time, which are posted right away instead.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::start_daemon::{self, CatchUpPolicy};
+/// # use tweetr::ops::QueuedTweet;
+/// # use chrono::{Duration, Local};
+/// # fn main() {
+/// let now = Local::now();
+/// let now = now.with_timezone(now.offset());
+///
+/// let mut tweets = vec![
+///     QueuedTweet::new("nabijaczleweli", now - Duration::days(1), "This tweet is going to be dropped (it's a day late)"),
+///     QueuedTweet {
+///         post_by: Some(now + Duration::hours(1)),
+///         ..QueuedTweet::new("nabijaczleweli", now - Duration::days(1), "This tweet is not going to be dropped (its deadline hasn't passed)")
+///     },
+///     QueuedTweet::new("nabijaczleweli", now - Duration::minutes(5), "This tweet is not going to be dropped (it's only a bit late)"),
+/// ];
+///
+/// start_daemon::apply_catch_up_policy(&mut tweets, &vec![], &CatchUpPolicy::DropLate(Duration::minutes(30)), Duration::minutes(1), true, &mut vec![]);
+/// assert!(tweets[0].failed.is_some());
+/// assert!(tweets[1].failed.is_none());
+/// assert!(tweets[2].failed.is_none());
+/// # }
+/// ```
+///
+/// Respreading tweets only after the daemon was down.
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::start_daemon::{self, CatchUpPolicy};
+/// # use tweetr::ops::{QueuedTweet, User};
+/// # use chrono::{Duration, Local};
+/// # fn main() {
+/// let now = Local::now();
+/// let now = now.with_timezone(now.offset());
+///
+/// let users = vec![User::new("nabijaczleweli", 0x4208142311, "key", "secret")];
+/// let mut tweets = vec![
+///     QueuedTweet::new("nabijaczleweli", now - Duration::hours(5), "This tweet is going to be posted now"),
+///     QueuedTweet::new("nabijaczleweli", now - Duration::hours(5), "This tweet is going to be posted later"),
+/// ];
+/// start_daemon::apply_catch_up_policy(&mut tweets, &users, &CatchUpPolicy::Respread(Duration::hours(1)), Duration::minutes(1), false, &mut vec![]);
+/// assert!(tweets[1].time < now);
+///
+/// start_daemon::apply_catch_up_policy(&mut tweets, &users, &CatchUpPolicy::Respread(Duration::hours(1)), Duration::minutes(1), true, &mut vec![]);
+/// assert!(tweets[0].time < now);
+/// assert!(tweets[1].time > now);
+/// # }
+/// ```
+pub fn apply_catch_up_policy<W: Write>(tweets: &mut Vec<QueuedTweet>, users: &Vec<User>, policy: &CatchUpPolicy, delay: Duration, first_pass: bool,
+                                       output: &mut W) {
+    let now = Local::now();
+    let now = now.with_timezone(now.offset());
+
+    let awaiting: Vec<_> = tweets.iter().map(|t| awaiting_anchor(t, tweets)).collect();
+    let mut due = vec![];
+    for (i, tweet) in tweets.iter_mut()
+        .enumerate()
+        .filter(|&(i, ref t)| t.id.is_none() && t.failed.is_none() && t.time <= now && !awaiting[i]) {
+        match (tweet.post_by, policy) {
+            (Some(post_by), _) if post_by < now => {
+                writeln!(output, "Dropped tweet \"{}\" scheduled for {:?} by {}: missed the deadline of {:?}", tweet.content, tweet.time, tweet.author, post_by)
+                    .unwrap();
+                tweet.failed = Some(format!("missed the deadline of {:?}", post_by));
+            }
+            (None, &CatchUpPolicy::DropLate(max_lateness)) if now - tweet.time > max_lateness => {
+                writeln!(output,
+                         "Dropped tweet \"{}\" scheduled for {:?} by {}: more than {} minutes late",
+                         tweet.content,
+                         tweet.time,
+                         tweet.author,
+                         max_lateness.num_minutes())
+                    .unwrap();
+                tweet.failed = Some(format!("more than {} minutes late", max_lateness.num_minutes()));
+            }
+            (None, &CatchUpPolicy::SkipLate(max_lateness)) if now - tweet.time > max_lateness => {
+                if first_pass {
+                    writeln!(output,
+                             "Skipped tweet \"{}\" scheduled for {:?} by {}: more than {} minutes late",
+                             tweet.content,
+                             tweet.time,
+                             tweet.author,
+                             max_lateness.num_minutes())
+                        .unwrap();
+                }
+            }
+            _ => {
+                if users.iter().any(|u| u.suspended.is_none() && tweet.is_by(u)) {
+                    due.push(i);
+                }
+            }
+        }
+    }
+
+    if let CatchUpPolicy::Respread(window) = *policy {
+        if !first_pass || due.iter().map(|&i| tweets[i].time).min().map(|earliest| now - earliest <= delay).unwrap_or(true) {
+            return;
+        }
+
+        let due_count = due.len() as i32;
+        for (n, i) in due.into_iter().enumerate().skip(1) {
+            let tweet = &mut tweets[i];
+
+            let new_time = now + window * n as i32 / due_count;
+            if tweet.post_by.into_iter().chain(tweet.expires).any(|deadline| new_time >= deadline) {
+                continue;
+            }
+
+            writeln!(output, "Rescheduled tweet \"{}\" scheduled for {:?} by {} to {:?}", tweet.content, tweet.time, tweet.author, new_time).unwrap();
+            tweet.time = new_time;
+        }
+    }
+}
+
+/// Get the indices of tweets to post now from the provided batch based on whether thy've been posted already and the current
+/// time.
+///
+/// Tweets scheduled after other tweets (see `QueuedTweet::after`) aren't posted before them, call
+/// `QueuedTweet::resolve_anchors()` beforehand to update their times.
+///
+/// Tweets late by more than the catch-up policy allows (and without a `post_by` deadline) are left alone, this is the
+/// counterpart to `apply_catch_up_policy()`, which should be called beforehand.
+///
+/// Tweets whose authors' posting limits (see `User::min_minutes_between_posts` and `User::max_posts_per_day`) would be
+/// broken by posting them now are deferred to the next allowed moment instead, which can leave the batch unsorted.
+/// Only actions creating tweets (see `TweetAction::creates_tweet()`) count towards and are held back by the limits.
+///
+/// Tweets by suspended users (see `User::suspended`) are left alone until they're re-authorised.
+///
+/// All returned indices are guaranteed to be valid.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::start_daemon::{self, CatchUpPolicy};
+/// # use tweetr::ops::QueuedTweet;
+/// # use chrono::{Duration, Local};
+/// # fn main() {
+/// let now = Local::now();
+/// let now = now.with_timezone(now.offset());
+///
+/// assert_eq!(start_daemon::tweet_indices_to_post(&mut vec![
+///     QueuedTweet::new("nabijaczleweli", now + Duration::hours(1), "This tweet is not going to be posted (it's too early)"),
+///     QueuedTweet::new("nabijaczleweli", now - Duration::hours(1), "This tweet is going to be posted"),
 ///     QueuedTweet {
-///         author: "nabijaczleweli".to_string(),
-///         time: now - Duration::hours(1),
-///         content: "This tweet is not going to be posted (it already was)".to_string(),
 ///         time_posted: Some(now - Duration::minutes(30)),
 ///         id: Some(6908265),
+///         ..QueuedTweet::new("nabijaczleweli", now - Duration::hours(1), "This tweet is not going to be posted (it already was)")
 ///     },
-/// ]), vec![1]);
+/// ], &vec![], &CatchUpPolicy::PostAll), vec![1]);
+/// # }
+/// ```
+///
+/// Deferring a tweet that would be posted too soon after another one.
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::start_daemon::{self, CatchUpPolicy};
+/// # use tweetr::ops::{QueuedTweet, User};
+/// # use chrono::{Duration, Local};
+/// # fn main() {
+/// let now = Local::now();
+/// let now = now.with_timezone(now.offset());
+///
+/// let mut tweets = vec![
+///     QueuedTweet::new("nabijaczleweli", now - Duration::hours(1), "This tweet is going to be posted"),
+///     QueuedTweet::new("nabijaczleweli", now - Duration::hours(1), "This tweet is going to be deferred"),
+/// ];
+///
+/// assert_eq!(start_daemon::tweet_indices_to_post(&mut tweets, &vec![User {
+///     min_minutes_between_posts: Some(15),
+///     ..User::new("nabijaczleweli", 0x4208142311, "key", "secret")
+/// }], &CatchUpPolicy::PostAll), vec![0]);
+/// assert!(tweets[1].time > now);
 /// # }
 /// ```
-pub fn tweet_indices_to_post(tweets: &Vec<QueuedTweet>) -> Vec<usize> {
+pub fn tweet_indices_to_post(tweets: &mut Vec<QueuedTweet>, users: &Vec<User>, policy: &CatchUpPolicy) -> Vec<usize> {
     let now = Local::now();
     let now = now.with_timezone(now.offset());
+    let max_lateness = policy.max_lateness();
 
-    tweets.iter()
-        .enumerate()
-        .flat_map(|(i, ref t)| if t.id.is_none() && t.time <= now {
-            Some(i)
-        } else {
-            None
-        })
-        .collect()
+    let mut to_post = vec![];
+    for i in 0..tweets.len() {
+        if tweets[i].id.is_some() || tweets[i].failed.is_some() || tweets[i].time > now || awaiting_anchor(&tweets[i], tweets) {
+            continue;
+        }
+        if users.iter().any(|u| u.suspended.is_some() && tweets[i].is_by(u)) {
+            continue;
+        }
+        if let Some(max_lateness) = max_lateness {
+            if tweets[i].post_by.is_none() && now - tweets[i].time > max_lateness {
+                continue;
+            }
+        }
+
+        let next_allowed = users.iter().find(|u| tweets[i].action.creates_tweet() && tweets[i].is_by(u)).and_then(|user| {
+            let mut post_times: Vec<_> = tweets.iter().filter(|t| t.action.creates_tweet() && t.is_by(user)).flat_map(|t| t.time_posted).collect();
+            post_times.extend(to_post.iter().filter(|&&pi: &&usize| tweets[pi].action.creates_tweet() && tweets[pi].is_by(user)).map(|_| now));
+            next_allowed_post_time(user, &post_times)
+        });
+
+        match next_allowed {
+            Some(next) if next > now => tweets[i].time = next,
+            _ => to_post.push(i),
+        }
+    }
+    to_post
+}
+
+fn awaiting_anchor(tweet: &QueuedTweet, tweets: &Vec<QueuedTweet>) -> bool {
+    let anchor_unposted = match tweet.after {
+        Some(ref after) => tweets.iter().find(|t| t.failed.is_none() && t.local_id.as_ref() == Some(after)).map(|t| t.id.is_none()).unwrap_or(true),
+        None => false,
+    };
+    let target_unposted = match tweet.action.target() {
+        Some(&TweetTarget::Queued(_)) => true,
+        _ => false,
+    };
+
+    anchor_unposted || target_unposted
+}
+
+/// Get the earliest time the specified user can post at without breaking its posting limits, given the times it posted at.
+///
+/// Returns `None` if the user has no posting limits or hasn't posted yet.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::{User, start_daemon};
+/// # use chrono::{Duration, Local};
+/// # fn main() {
+/// let now = Local::now();
+/// let now = now.with_timezone(now.offset());
+///
+/// let user = User {
+///     min_minutes_between_posts: Some(30),
+///     max_posts_per_day: Some(2),
+///     ..User::new("nabijaczleweli", 0x4208142311, "key", "secret")
+/// };
+///
+/// assert_eq!(start_daemon::next_allowed_post_time(&user, &vec![]), None);
+/// assert_eq!(start_daemon::next_allowed_post_time(&user, &vec![now - Duration::hours(5)]),
+///            Some(now - Duration::hours(5) + Duration::minutes(30)));
+/// assert_eq!(start_daemon::next_allowed_post_time(&user, &vec![now - Duration::hours(5), now - Duration::hours(3)]),
+///            Some(now - Duration::hours(5) + Duration::days(1)));
+/// # }
+/// ```
+pub fn next_allowed_post_time(user: &User, post_times: &Vec<DateTime<FixedOffset>>) -> Option<DateTime<FixedOffset>> {
+    let mut post_times = post_times.clone();
+    post_times.sort_by(|lhs, rhs| rhs.cmp(lhs));
+
+    let after_gap = user.min_minutes_between_posts.and_then(|gap| post_times.first().map(|last| *last + Duration::minutes(gap as i64)));
+    let after_cap = user.max_posts_per_day.and_then(|cap| post_times.get(cap.saturating_sub(1) as usize).map(|dropping_out| *dropping_out + Duration::days(1)));
+
+    match (after_gap, after_cap) {
+        (Some(gap), Some(cap)) => Some(max(gap, cap)),
+        (gap, cap) => gap.or(cap),
+    }
 }
 
 /// Try to get the index of the user to post the given tweet.
 ///
 /// This will fail iff there's no suitable user.
 ///
+/// Users are matched via `QueuedTweet::is_by()`, i.e. by ID if it's known and by handle, ignoring case, otherwise.
+///
 /// The returned index guaranteed to be valid.
 ///
 /// # Examples
@@ -160,21 +683,10 @@ pub fn tweet_indices_to_post(tweets: &Vec<QueuedTweet>) -> Vec<usize> {
 /// let now = Local::now();
 /// let now = now.with_timezone(now.offset());
 ///
-/// let tweet = QueuedTweet {
-///     author: "nabijaczleweli".to_string(),
-///     time: now,
-///     content: "dummy".to_string(),
-///     time_posted: None,
-///     id: None,
-/// };
+/// let tweet = QueuedTweet::new("nabijaczleweli", now, "dummy");
 ///
 /// assert!(start_daemon::find_user_index_for_tweet(&tweet, &vec![]).is_err());
-/// assert!(start_daemon::find_user_index_for_tweet(&tweet, &vec![User {
-///     name: "danerangLP".to_string(),
-///     id: 0x4208142311,
-///     access_token_key: "key".to_string(),
-///     access_token_secret: "secret".to_string(),
-/// }]).is_err());
+/// assert!(start_daemon::find_user_index_for_tweet(&tweet, &vec![User::new("danerangLP", 0x4208142311, "key", "secret")]).is_err());
 /// # }
 /// ```
 ///
@@ -189,22 +701,22 @@ pub fn tweet_indices_to_post(tweets: &Vec<QueuedTweet>) -> Vec<usize> {
 /// let now = Local::now();
 /// let now = now.with_timezone(now.offset());
 ///
-/// assert_eq!(start_daemon::find_user_index_for_tweet(&QueuedTweet {
-///     author: "danerangLP".to_string(),
-///     time: now,
-///     content: "dummy".to_string(),
-///     time_posted: None,
-///     id: None,
-/// }, &vec![User {
+/// assert_eq!(start_daemon::find_user_index_for_tweet(&QueuedTweet::new("danerangLP", now, "dummy"), &vec![User {
 ///     name: "danerangLP".to_string(),
 ///     id: 0x4208142311,
 ///     access_token_key: "key".to_string(),
 ///     access_token_secret: "secret".to_string(),
+///     secret_command: None,
+///     app: None,
+///     min_minutes_between_posts: None,
+///     max_posts_per_day: None,
+///     jitter_minutes: None,
+///     suspended: None,
 /// }]), Ok(0));
 /// # }
 /// ```
 pub fn find_user_index_for_tweet(tweet: &QueuedTweet, users: &Vec<User>) -> Result<usize, Outcome> {
-    match users.iter().enumerate().find(|&iu| iu.1.name == tweet.author).map(|iu| iu.0) {
+    match users.iter().enumerate().find(|&iu| tweet.is_by(iu.1)).map(|iu| iu.0) {
         Some(uid) => Ok(uid),
         None => {
             Err(Outcome::RequiredDataFromSubsystemNonexistant {
@@ -220,7 +732,17 @@ pub fn find_user_index_for_tweet(tweet: &QueuedTweet, users: &Vec<User>) -> Resu
 
 /// Post the specified tweet on behalf of the specified user and application, optionally printing progress.
 ///
-/// The tweet is updated with the data returned by the Twitter API.
+/// Retweets, quotes, likes, pins, unpins, profile updates and direct messages are done as specified by the tweet's action
+/// instead of posting its content verbatim.
+///
+/// Tweets and quotes with polls are posted with them attached, and the poll's ID is recorded.
+///
+/// Actions done to queued tweets that weren't posted yet aren't done at all.
+///
+/// The tweet is updated with the data returned by the Twitter API, and bound to the user's current handle and ID.
+///
+/// Nothing is done for suspended users. If the user's access tokens turn out not to work anymore, it's suspended, and the
+/// returned error tells to re-authorise it.
 ///
 /// # Examples
 ///
@@ -233,22 +755,15 @@ pub fn find_user_index_for_tweet(tweet: &QueuedTweet, users: &Vec<User>) -> Resu
 /// let now = Local::now();
 /// let now = now.with_timezone(now.offset());
 ///
-/// let mut tweet = QueuedTweet {
-///     author: "nabijaczleweli".to_string(),
-///     time: now,
-///     content: "This tweet will be posted, no matter the cost!".to_string(),
-///     time_posted: None,
-///     id: None,
-/// };
+/// let mut tweet = QueuedTweet::new("nabijaczleweli", now, "This tweet will be posted, no matter the cost!");
 ///
-/// let result = start_daemon::post_tweet(&mut tweet, &User {
-///     name: "nabijaczleweli".to_string(),
-///     id: 0x81423,
-///     access_token_key: "529443-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4".to_string(),
-///     access_token_secret: "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5".to_string(),
-/// }, &AppTokens {
+/// let result = start_daemon::post_tweet(&mut tweet, &mut User::new("nabijaczleweli",
+///                                                                  0x81423,
+///                                                                  "529443-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4",
+///                                                                  "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5"), &AppTokens {
 ///     key: "qzuqpwr101q4RtK9mDorI9ndm".to_string(),
 ///     secret: "HW4YG3Kdcap5ovcZ5fZfBJFedKR6GQe9MtZDS9Gm34hXiirkU5".to_string(),
+///     secret_command: None,
 /// }.into(), false, &mut vec![]);
 ///
 /// assert_eq!(result.exit_value(), 0);
@@ -256,29 +771,135 @@ pub fn find_user_index_for_tweet(tweet: &QueuedTweet, users: &Vec<User>) -> Resu
 /// assert!(tweet.id.is_some());
 /// # }
 /// ```
-pub fn post_tweet<'a, W: Write>(tweet: &mut QueuedTweet, on_behalf_of: &User, app: &Token<'a>, verbose: bool, output: &mut W) -> Outcome {
+pub fn post_tweet<'a, W: Write>(tweet: &mut QueuedTweet, on_behalf_of: &mut User, app: &Token<'a>, verbose: bool, output: &mut W) -> Outcome {
+    if on_behalf_of.suspended.is_some() {
+        return Outcome::NoError;
+    }
+
     if verbose {
-        write!(output, "Posting tweet scheduled for {:?}...", tweet.time).unwrap();
+        write!(output, "Posting {} scheduled for {:?}...", tweet.action.name(), tweet.time).unwrap();
         output.flush().unwrap();
     }
 
-    match span_r(|| DraftTweet::new(&tweet.content).send(app, &Token::new(&on_behalf_of.access_token_key[..], &on_behalf_of.access_token_secret[..]))) {
-        (dur, Ok(resp)) => {
+    tweet.author = on_behalf_of.name.clone();
+    tweet.author_id = Some(on_behalf_of.id);
+
+    let target = match tweet.action.target() {
+        Some(&TweetTarget::Queued(ref local_id)) => {
+            return Outcome::RequiredDataFromSubsystemNonexistant {
+                subsys: "start-daemon",
+                desc: format!("post tweet \"{}\" (required for {} scheduled for {:?} by {})",
+                              local_id,
+                              tweet.action.name(),
+                              tweet.time,
+                              tweet.author),
+            }
+        }
+        Some(&TweetTarget::Id(id)) => id,
+        None => 0,
+    };
+
+    // Only tweets, retweets and quotes create tweets to get the time and ID from
+    let access_token = match on_behalf_of.access_token() {
+        Ok(access_token) => access_token,
+        Err(out) => return out,
+    };
+    let QueuedTweet { ref action, ref content, ref poll, ref mut card_id, .. } = *tweet;
+    let result = span_r(|| match *action {
+        TweetAction::Tweet => send_status(content, poll.as_ref(), card_id, app, &access_token),
+        TweetAction::Retweet(_) => retweet(target, app, &access_token).map(|resp| (Some(resp.response.created_at), resp.response.id)),
+        TweetAction::Quote(_) => {
+            send_status(&format!("{} https://twitter.com/i/web/status/{}", content, target),
+                        poll.as_ref(),
+                        card_id,
+                        app,
+                        &access_token)
+        }
+        TweetAction::Like(_) => like(target, app, &access_token).map(|_| (None, target)),
+        TweetAction::Pin(_) => api::pin_tweet(target, app, &access_token).map(|_| (None, target)),
+        TweetAction::Unpin(_) => api::unpin_tweet(target, app, &access_token).map(|_| (None, target)),
+        TweetAction::UpdateProfile(ref pu) => {
+            api::update_profile(pu.name.as_ref().map(|n| &n[..]),
+                                pu.description.as_ref().map(|d| &d[..]),
+                                app,
+                                &access_token)
+                .map(|id| (None, id))
+        }
+        TweetAction::DirectMessage(ref recipient) => api::send_direct_message(recipient, content, app, &access_token).map(|id| (None, id)),
+    });
+
+    match result {
+        (dur, Ok((created_at, id))) => {
             if verbose {
                 writeln!(output, " {}ms", dur.num_milliseconds()).unwrap();
             }
 
-            tweet.time_posted = Some(DateTime::parse_from_str(&resp.response.created_at, TWEET_DATETIME_FORMAT).unwrap());
-            tweet.id = Some(resp.response.id);
+            tweet.time_posted = Some(match created_at {
+                Some(created_at) => DateTime::parse_from_str(&created_at, TWEET_DATETIME_FORMAT).unwrap(),
+                None => {
+                    let now = Local::now();
+                    now.with_timezone(now.offset())
+                }
+            });
+            tweet.id = Some(id);
 
-            writeln!(output,
-                     "Posted tweet \"{}\" scheduled for {:?} by {} at {:?} with ID {}",
-                     tweet.content,
-                     tweet.time,
-                     tweet.author,
-                     tweet.time_posted.as_ref().unwrap(),
-                     resp.response.id)
-                .unwrap();
+            match tweet.action {
+                TweetAction::Tweet |
+                TweetAction::Quote(_) => {
+                    writeln!(output,
+                             "Posted tweet \"{}\" scheduled for {:?} by {} at {:?} with ID {}",
+                             tweet.content,
+                             tweet.time,
+                             tweet.author,
+                             tweet.time_posted.as_ref().unwrap(),
+                             id)
+                }
+                TweetAction::Retweet(_) => {
+                    writeln!(output,
+                             "Retweeted tweet with ID {} scheduled for {:?} by {} at {:?} with ID {}",
+                             target,
+                             tweet.time,
+                             tweet.author,
+                             tweet.time_posted.as_ref().unwrap(),
+                             id)
+                }
+                TweetAction::Like(_) |
+                TweetAction::Pin(_) |
+                TweetAction::Unpin(_) => {
+                    writeln!(output,
+                             "{} tweet with ID {} scheduled for {:?} by {} at {:?}",
+                             match tweet.action {
+                                 TweetAction::Like(_) => "Liked",
+                                 TweetAction::Pin(_) => "Pinned",
+                                 _ => "Unpinned",
+                             },
+                             target,
+                             tweet.time,
+                             tweet.author,
+                             tweet.time_posted.as_ref().unwrap())
+                }
+                TweetAction::DirectMessage(ref recipient) => {
+                    writeln!(output,
+                             "Sent direct message \"{}\" scheduled for {:?} by {} to {} at {:?} with ID {}",
+                             tweet.content,
+                             tweet.time,
+                             tweet.author,
+                             match *recipient {
+                                 Recipient::Id(id) => format!("user with ID {}", id),
+                                 Recipient::Handle(ref handle) => format!("@{}", handle),
+                             },
+                             tweet.time_posted.as_ref().unwrap(),
+                             id)
+                }
+                TweetAction::UpdateProfile(_) => {
+                    writeln!(output,
+                             "Updated profile of {} scheduled for {:?} at {:?}",
+                             tweet.author,
+                             tweet.time,
+                             tweet.time_posted.as_ref().unwrap())
+                }
+            }
+            .unwrap();
 
             Outcome::NoError
         }
@@ -286,7 +907,295 @@ pub fn post_tweet<'a, W: Write>(tweet: &mut QueuedTweet, on_behalf_of: &User, ap
             if verbose {
                 writeln!(output, " FAILED").unwrap();
             }
-            Outcome::TwitterAPIError(e.to_string())
+            error_outcome(on_behalf_of, e)
+        }
+    }
+}
+
+fn error_outcome(user: &mut User, err: Error) -> Outcome {
+    if !api::is_authentication_error(&err) {
+        return Outcome::TwitterAPIError(err.to_string());
+    }
+
+    let now = Local::now();
+    user.suspended = Some(Suspension {
+        reason: err.to_string(),
+        time: now.with_timezone(now.offset()),
+    });
+    Outcome::RequiredDataFromSubsystemNonexistant {
+        subsys: "reauth-user",
+        desc: format!("re-authorise user {}#{}, which was suspended because its access tokens stopped working ({})",
+                      user.name,
+                      user.id,
+                      err),
+    }
+}
+
+fn send_status<'a>(status: &str, poll: Option<&Poll>, card_id: &mut Option<String>, app: &Token<'a>, access_token: &Token<'a>)
+                   -> Result<(Option<String>, i64), Error> {
+    match poll {
+        Some(poll) => {
+            // Store the card right away, so that retrying after the status fails to post doesn't create another one
+            if card_id.is_none() {
+                let card_uri = try!(api::create_poll(&poll.options, poll.duration_minutes, app, access_token));
+                *card_id = Some(card_uri.replace("card://", ""));
+            }
+            let (created_at, id) = try!(api::update_status_with_card(status,
+                                                                     &format!("card://{}", card_id.as_ref().unwrap()),
+                                                                     app,
+                                                                     access_token));
+            Ok((Some(created_at), id))
+        }
+        None => DraftTweet::new(status).send(app, access_token).map(|resp| (Some(resp.response.created_at), resp.response.id)),
+    }
+}
+
+/// Get the indices of posted tweets to delete now, based on their deletion times and whether they were deleted already.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::start_daemon;
+/// # use tweetr::ops::QueuedTweet;
+/// # use chrono::{Duration, Local};
+/// # fn main() {
+/// let now = Local::now();
+/// let now = now.with_timezone(now.offset());
+///
+/// assert_eq!(start_daemon::tweet_indices_to_delete(&vec![
+///     QueuedTweet {
+///         time_posted: Some(now - Duration::hours(2)),
+///         id: Some(6908265),
+///         delete_after_minutes: Some(180),
+///         ..QueuedTweet::new("nabijaczleweli", now - Duration::hours(2), "This tweet is not going to be deleted (it's not time yet)")
+///     },
+///     QueuedTweet {
+///         time_posted: Some(now - Duration::hours(2)),
+///         id: Some(6908266),
+///         delete_at: Some(now - Duration::minutes(1)),
+///         ..QueuedTweet::new("nabijaczleweli", now - Duration::hours(2), "This tweet is going to be deleted")
+///     },
+/// ]), vec![1]);
+/// # }
+/// ```
+pub fn tweet_indices_to_delete(tweets: &Vec<QueuedTweet>) -> Vec<usize> {
+    let now = Local::now();
+    let now = now.with_timezone(now.offset());
+
+    tweets.iter()
+        .enumerate()
+        .filter(|&(_, t)| t.id.is_some() && t.time_deleted.is_none() && t.deletion_time().map(|dt| dt <= now).unwrap_or(false))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Delete the specified posted tweet on behalf of the specified user and application, optionally printing progress.
+///
+/// Liked tweets are unliked and pinned tweets unpinned instead, direct messages are deleted the same way as tweets.
+///
+/// The tweet's deletion time is recorded, also if it turns out to have been deleted already.
+///
+/// Suspended users are treated the same way as by `post_tweet()`.
+///
+/// # Examples
+///
+/// ```no_run
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::{QueuedTweet, AppTokens, User, start_daemon};
+/// # use chrono::{Duration, Local};
+/// # fn main() {
+/// let now = Local::now();
+/// let now = now.with_timezone(now.offset());
+///
+/// let mut tweet = QueuedTweet {
+///     time_posted: Some(now - Duration::hours(1)),
+///     id: Some(773264221226950656),
+///     delete_after_minutes: Some(30),
+///     ..QueuedTweet::new("nabijaczleweli", now - Duration::hours(1), "This tweet will be deleted, no matter the cost!")
+/// };
+///
+/// let result = start_daemon::delete_tweet(&mut tweet, &mut User::new("nabijaczleweli",
+///                                                                    0x81423,
+///                                                                    "529443-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4",
+///                                                                    "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5"), &AppTokens {
+///     key: "qzuqpwr101q4RtK9mDorI9ndm".to_string(),
+///     secret: "HW4YG3Kdcap5ovcZ5fZfBJFedKR6GQe9MtZDS9Gm34hXiirkU5".to_string(),
+///     secret_command: None,
+/// }.into(), false, &mut vec![]);
+///
+/// assert_eq!(result.exit_value(), 0);
+/// assert!(tweet.time_deleted.is_some());
+/// # }
+/// ```
+pub fn delete_tweet<'a, W: Write>(tweet: &mut QueuedTweet, on_behalf_of: &mut User, app: &Token<'a>, verbose: bool, output: &mut W) -> Outcome {
+    if on_behalf_of.suspended.is_some() {
+        return Outcome::NoError;
+    }
+
+    let id = tweet.id.unwrap();
+    if verbose {
+        write!(output, "Deleting tweet with ID {}...", id).unwrap();
+        output.flush().unwrap();
+    }
+
+    let access_token = match on_behalf_of.access_token() {
+        Ok(access_token) => access_token,
+        Err(out) => return out,
+    };
+    let result = span_r(|| match tweet.action {
+        // Deleting the liked or pinned tweet instead of unliking or unpinning it would be *very* bad
+        TweetAction::Like(_) => unlike(id, app, &access_token).map(|_| ()),
+        TweetAction::Pin(_) => api::unpin_tweet(id, app, &access_token),
+        TweetAction::DirectMessage(_) => api::delete_direct_message(id, app, &access_token),
+        _ => delete(id, app, &access_token).map(|_| ()),
+    });
+    if verbose {
+        match result {
+            (dur, Ok(_)) => writeln!(output, " {}ms", dur.num_milliseconds()).unwrap(),
+            (_, Err(_)) => writeln!(output, " FAILED").unwrap(),
         }
     }
+
+    let already_deleted = match result.1 {
+        Ok(_) => false,
+        // "No status found with that ID."
+        Err(TwitterError(ref errs)) if errs.errors.iter().any(|e| e.code == 144) => true,
+        Err(e) => return error_outcome(on_behalf_of, e),
+    };
+
+    let now = Local::now();
+    tweet.time_deleted = Some(now.with_timezone(now.offset()));
+    writeln!(output,
+             "{} tweet \"{}\" by {} with ID {} at {:?}",
+             if already_deleted {
+                 "Already deleted"
+             } else {
+                 "Deleted"
+             },
+             tweet.content,
+             tweet.author,
+             id,
+             tweet.time_deleted.as_ref().unwrap())
+        .unwrap();
+
+    Outcome::NoError
+}
+
+/// Get the index of the tweet to recycle from the specified evergreen pool now, if any.
+///
+/// A tweet is only recycled if no unposted tweets by the pool's author are queued, the pool's daily cap hasn't been reached
+/// and the spacing it implies has passed since the last recycled tweet. Queued tweets are matched to the author via
+/// `QueuedTweet::is_by()` if it's one of the specified users, so ones bound to it before it was renamed count too.
+///
+/// Nothing is recycled for suspended authors (see `User::suspended`) until they're re-authorised, nor if it'd break the
+/// author's posting limits (see `next_allowed_post_time()`).
+///
+/// Only tweets that haven't been posted in the last `min_days_between_repeats` days are considered.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::ops::{EvergreenSelection, EvergreenTweet, EvergreenPool, start_daemon};
+/// # use chrono::{Duration, Local};
+/// # fn main() {
+/// let now = Local::now();
+/// let now = now.with_timezone(now.offset());
+///
+/// let pool = EvergreenPool {
+///     author: "nabijaczleweli".to_string(),
+///     min_days_between_repeats: 7,
+///     selection: EvergreenSelection::RoundRobin,
+///     max_per_day: 2,
+///     tweets: vec![EvergreenTweet {
+///                      content: "This tweet was reposted yesterday".to_string(),
+///                      last_posted: Some(now - Duration::days(1)),
+///                  },
+///                  EvergreenTweet {
+///                      content: "This tweet was reposted a month ago".to_string(),
+///                      last_posted: Some(now - Duration::days(30)),
+///                  }],
+/// };
+///
+/// assert_eq!(start_daemon::evergreen_tweet_index_to_post(&pool, &vec![], &vec![]), Some(1));
+/// # }
+/// ```
+pub fn evergreen_tweet_index_to_post(pool: &EvergreenPool, tweets: &Vec<QueuedTweet>, users: &Vec<User>) -> Option<usize> {
+    let user = users.iter().find(|u| u.name.eq_ignore_ascii_case(&pool.author));
+    if user.map(|u| u.suspended.is_some()).unwrap_or(false) {
+        return None;
+    }
+
+    let is_by_author = |t: &QueuedTweet| match user {
+        Some(user) => t.is_by(user),
+        None => t.author.eq_ignore_ascii_case(&pool.author),
+    };
+    if pool.max_per_day == 0 || tweets.iter().any(|t| is_by_author(t) && t.id.is_none() && t.failed.is_none()) {
+        return None;
+    }
+
+    let now = Local::now();
+    let now = now.with_timezone(now.offset());
+
+    if let Some(user) = user {
+        let post_times = tweets.iter().filter(|t| t.action.creates_tweet() && t.is_by(user)).flat_map(|t| t.time_posted).collect();
+        if next_allowed_post_time(user, &post_times).map(|next| next > now).unwrap_or(false) {
+            return None;
+        }
+    }
+
+    let day_ago = now - Duration::days(1);
+    let posted_today = pool.tweets.iter().filter(|t| t.last_posted.as_ref().map(|lp| lp > &day_ago).unwrap_or(false)).count() as u64;
+    let last_posted = pool.tweets.iter().flat_map(|t| t.last_posted).max();
+    if posted_today >= pool.max_per_day || last_posted.map(|lp| lp > now - Duration::seconds(24 * 60 * 60 / pool.max_per_day as i64)).unwrap_or(false) {
+        return None;
+    }
+
+    let repeat_cutoff = now - Duration::days(pool.min_days_between_repeats as i64);
+    let eligible: Vec<usize> = pool.tweets
+        .iter()
+        .enumerate()
+        .filter(|&(_, t)| t.last_posted.as_ref().map(|lp| lp <= &repeat_cutoff).unwrap_or(true))
+        .map(|(i, _)| i)
+        .collect();
+
+    match pool.selection {
+        EvergreenSelection::Random => thread_rng().choose(&eligible).cloned(),
+        EvergreenSelection::RoundRobin => eligible.into_iter().min_by_key(|&i| pool.tweets[i].last_posted),
+    }
+}
+
+/// Create a tweet to post now from the tweet at the specified index in the specified evergreen pool.
+///
+/// After posting it, record it in the pool with `EvergreenTweet::last_posted` and push it into the queue, so that it's kept
+/// in the posted history like all other tweets.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::{EvergreenSelection, EvergreenTweet, EvergreenPool, start_daemon};
+/// let pool = EvergreenPool {
+///     author: "nabijaczleweli".to_string(),
+///     min_days_between_repeats: 7,
+///     selection: EvergreenSelection::Random,
+///     max_per_day: 1,
+///     tweets: vec![EvergreenTweet {
+///                      content: "Evergreen content".to_string(),
+///                      last_posted: None,
+///                  }],
+/// };
+///
+/// let tweet = start_daemon::evergreen_tweet_to_post(&pool, 0);
+/// assert_eq!(tweet.author, "nabijaczleweli");
+/// assert_eq!(tweet.content, "Evergreen content");
+/// assert_eq!(tweet.id, None);
+/// ```
+pub fn evergreen_tweet_to_post(pool: &EvergreenPool, idx: usize) -> QueuedTweet {
+    let now = Local::now();
+
+    QueuedTweet::new(pool.author.clone(), now.with_timezone(now.offset()), pool.tweets[idx].content.clone())
 }
@@ -1,33 +1,264 @@
+use self::super::super::util::{create_private_file, run_secret_command};
+use self::super::{SecretKey, User, read_toml_file, verify_file};
 use self::super::super::Outcome;
-use self::super::read_toml_file;
+use std::path::{PathBuf, Path};
+use std::collections::BTreeMap;
 use toml::encode_str;
 use egg_mode::Token;
-use std::path::Path;
 use std::io::Write;
-use std::fs::File;
+use std::fs;
 
 
 /// The tokens needed to authenticate the app itself.
+///
+/// Several apps can be used at once as named profiles, the default one is stored in `app.toml` and named ones in
+/// `app-<name>.toml`.
 #[derive(Debug, Clone, Hash, PartialEq, Eq, RustcEncodable, RustcDecodable)]
 pub struct AppTokens {
     /// Key part of the authentication token
     pub key: String,
     /// Secret part of the authentication token
     pub secret: String,
+    /// Shell command printing the secret part of the authentication token, run instead of storing it, if any.
+    ///
+    /// The secret is empty if this is set, get the token via `token()`.
+    pub secret_command: Option<String>,
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+struct AppTokensForSerialisation {
+    key: String,
+    secret: Option<String>,
+    secret_command: Option<String>,
 }
 
 impl AppTokens {
-    /// Read the application tokens from the specified file.
-    pub fn read(p: &Path) -> Result<AppTokens, Option<Outcome>> {
-        read_toml_file(p, "application tokens")
+    /// Read the application tokens from the specified file, decrypting the secret with the specified key, if any.
+    pub fn read(p: &Path, key: Option<&SecretKey>) -> Result<AppTokens, Option<Outcome>> {
+        let tokens: AppTokensForSerialisation = try!(read_toml_file(p, "application tokens"));
+        let secret = match (tokens.secret, tokens.secret_command.is_some()) {
+            (Some(secret), _) => try!(SecretKey::reveal(key, &secret, "app secret").map_err(Some)),
+            (None, true) => String::new(),
+            (None, false) => {
+                return Err(Some(Outcome::FileParsingFailed {
+                    desc: "application tokens",
+                    errors: vec!["neither secret nor secret_command specified".to_string()],
+                }))
+            }
+        };
+
+        Ok(AppTokens {
+            key: tokens.key,
+            secret: secret,
+            secret_command: tokens.secret_command,
+        })
+    }
+
+    /// Save the application tokens to the specified file, encrypting the secret with the specified key, if any.
+    pub fn write(&self, p: &Path, key: Option<&SecretKey>) {
+        create_private_file(p)
+            .unwrap()
+            .write_all(encode_str(&AppTokensForSerialisation {
+                    key: self.key.clone(),
+                    secret: if self.secret_command.is_some() {
+                        None
+                    } else {
+                        Some(SecretKey::conceal(key, self.secret.clone()))
+                    },
+                    secret_command: self.secret_command.clone(),
+                })
+                .as_bytes())
+            .unwrap();
+    }
+
+    /// Check whether the specified app profile name is valid, i.e. non-empty and consisting only of ASCII letters, digits,
+    /// `-` and `_`, so that it can't refer to a file outside the configuration directory.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::AppTokens;
+    /// assert!(AppTokens::is_valid_profile_name("staging_2"));
+    /// assert!(!AppTokens::is_valid_profile_name("../staging"));
+    /// assert!(!AppTokens::is_valid_profile_name("żółw"));
+    /// assert!(!AppTokens::is_valid_profile_name(""));
+    /// ```
+    pub fn is_valid_profile_name(name: &str) -> bool {
+        !name.is_empty() && name.chars().all(|c| c.is_ascii() && (c.is_alphanumeric() || c == '-' || c == '_'))
+    }
+
+    /// Get the name of the app profile whose tokens the file with the specified name contains, `Some(None)` for the default
+    /// one, or `None` if it doesn't contain any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::AppTokens;
+    /// assert_eq!(AppTokens::profile_of_file_name("app.toml"), Some(None));
+    /// assert_eq!(AppTokens::profile_of_file_name("app-staging.toml"), Some(Some("staging".to_string())));
+    /// assert_eq!(AppTokens::profile_of_file_name("app-.toml"), None);
+    /// assert_eq!(AppTokens::profile_of_file_name("users.toml"), None);
+    /// ```
+    pub fn profile_of_file_name(fname: &str) -> Option<Option<String>> {
+        if fname == "app.toml" {
+            Some(None)
+        } else if fname.starts_with("app-") && fname.ends_with(".toml") && fname.len() > "app-.toml".len() {
+            let profile = &fname["app-".len()..fname.len() - ".toml".len()];
+            if AppTokens::is_valid_profile_name(profile) {
+                Some(Some(profile.to_string()))
+            } else {
+                None
+            }
+        } else {
+            None
+        }
+    }
+
+    /// Get the name of the file containing the specified app profile's tokens, the default one if `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::AppTokens;
+    /// assert_eq!(AppTokens::profile_file_name(None), "app.toml");
+    /// assert_eq!(AppTokens::profile_file_name(Some("staging")), "app-staging.toml");
+    /// ```
+    pub fn profile_file_name(profile: Option<&str>) -> String {
+        match profile {
+            Some(name) => format!("app-{}.toml", name),
+            None => "app.toml".to_string(),
+        }
+    }
+
+    /// Get the path to the file containing the specified app profile's tokens, or why it doesn't exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::AppTokens;
+    /// # use std::env::temp_dir;
+    /// # use tweetr::Outcome;
+    /// let tf = temp_dir().join("tweetr-doctest").join("ops-token-profile_path-0");
+    /// assert_eq!(AppTokens::profile_path(&("$TEMP/ops-token-profile_path-0".to_string(), tf), Some("staging")),
+    ///            Err(Outcome::RequiredFileFromSubsystemNonexistant {
+    ///                subsys: "init",
+    ///                fname: "$TEMP/ops-token-profile_path-0/app-staging.toml".to_string(),
+    ///            }));
+    /// ```
+    pub fn profile_path(config_dir: &(String, PathBuf), profile: Option<&str>) -> Result<PathBuf, Outcome> {
+        verify_file(&AppTokens::profile_file_name(profile), true, config_dir, false, "init")
+    }
+
+    /// Read the tokens of all app profiles in the specified configuration directory, keyed by their names, decrypting the
+    /// secrets with the specified key, if any.
+    ///
+    /// Profiles that fail to read don't fail the others, but are returned as why they failed, naming their files, alongside
+    /// the ones that didn't. Only failing to list the directory fails outright.
+    pub fn read_profiles(config_dir: &Path, key: Option<&SecretKey>) -> Result<(BTreeMap<Option<String>, AppTokens>, Vec<Outcome>), Outcome> {
+        let entries = try!(fs::read_dir(config_dir).map_err(|e| {
+            Outcome::FileParsingFailed {
+                desc: "application tokens",
+                errors: vec![format!("listing {} failed: {}", config_dir.display(), e)],
+            }
+        }));
+
+        let mut profiles = BTreeMap::new();
+        let mut errors = vec![];
+        for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+            let (fname, profile) = match path.file_name().and_then(|f| f.to_str()).and_then(|f| AppTokens::profile_of_file_name(f).map(|p| (f, p))) {
+                Some(fp) => fp,
+                None => continue,
+            };
+
+            match AppTokens::read(&path, key) {
+                Ok(app) => {
+                    profiles.insert(profile, app);
+                }
+                Err(Some(Outcome::FileParsingFailed { desc, errors: parse_errors })) => {
+                    errors.push(Outcome::FileParsingFailed {
+                        desc: desc,
+                        errors: if parse_errors.is_empty() {
+                            vec![fname.to_string()]
+                        } else {
+                            parse_errors.into_iter().map(|e| format!("{}: {}", fname, e)).collect()
+                        },
+                    })
+                }
+                Err(Some(out)) => errors.push(out),
+                Err(None) => {
+                    errors.push(Outcome::FileParsingFailed {
+                        desc: "application tokens",
+                        errors: vec![format!("{}: couldn't be read", fname)],
+                    })
+                }
+            }
+        }
+        Ok((profiles, errors))
     }
 
-    /// Save the application tokens to the specified file.
-    pub fn write(&self, p: &Path) {
-        File::create(p).unwrap().write_all(encode_str(&self).as_bytes()).unwrap();
+    /// Get the tokens of the app the specified user was authorised for out of the specified profiles.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::{AppTokens, User};
+    /// # use std::collections::BTreeMap;
+    /// let mut profiles = BTreeMap::new();
+    /// profiles.insert(Some("staging".to_string()), AppTokens {
+    ///     key: "qzuqpwr101q4RtK9mDorI9ndm".to_string(),
+    ///     secret: "HW4YG3Kdcap5ovcZ5fZfBJFedKR6GQe9MtZDS9Gm34hXiirkU5".to_string(),
+    ///     secret_command: None,
+    /// });
+    ///
+    /// let mut user = User {
+    ///     app: Some("staging".to_string()),
+    ///     ..User::new("nabijaczleweli", 0x81423, "529443-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4", "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5")
+    /// };
+    /// assert_eq!(AppTokens::for_user(&profiles, &user).unwrap().key, "qzuqpwr101q4RtK9mDorI9ndm");
+    ///
+    /// user.app = None;
+    /// assert!(AppTokens::for_user(&profiles, &user).is_err());
+    /// ```
+    pub fn for_user<'a>(profiles: &'a BTreeMap<Option<String>, AppTokens>, user: &User) -> Result<&'a AppTokens, Outcome> {
+        profiles.get(&user.app).ok_or_else(|| {
+            Outcome::RequiredDataFromSubsystemNonexistant {
+                subsys: "init",
+                desc: format!("initialise app {} (required for user {}#{})",
+                              match user.app {
+                                  Some(ref name) => format!("\"{}\"", name),
+                                  None => "data".to_string(),
+                              },
+                              user.name,
+                              user.id),
+            }
+        })
+    }
+
+    /// Get the app's token, running the `secret_command` to get the secret, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::AppTokens;
+    /// let tokens = AppTokens {
+    ///     key: "qzuqpwr101q4RtK9mDorI9ndm".to_string(),
+    ///     secret: String::new(),
+    ///     secret_command: Some("echo HW4YG3Kdcap5ovcZ5fZfBJFedKR6GQe9MtZDS9Gm34hXiirkU5".to_string()),
+    /// };
+    /// assert_eq!(tokens.token().unwrap().secret, "HW4YG3Kdcap5ovcZ5fZfBJFedKR6GQe9MtZDS9Gm34hXiirkU5");
+    /// ```
+    pub fn token(&self) -> Result<Token<'static>, Outcome> {
+        let secret = match self.secret_command {
+            Some(ref command) => try!(run_secret_command(command).map_err(|e| Outcome::InvalidCredentials(vec![format!("getting app secret failed: {}", e)]))),
+            None => self.secret.clone(),
+        };
+
+        Ok(Token::new(self.key.clone(), secret))
     }
 
     /// Borrows the current key and secret into an `egg_mode::Token`.
+    ///
+    /// The secret is empty if it's fetched via a command, use `token()` instead in that case.
     pub fn raw_token<'a>(&'a self) -> Token<'a> {
         Token::new(&self.key[..], &self.secret[..])
     }
@@ -44,6 +275,7 @@ impl<'a> From<Token<'a>> for AppTokens {
         AppTokens {
             key: tkn.key.into_owned().to_string(),
             secret: tkn.secret.into_owned().to_string(),
+            secret_command: None,
         }
     }
 }
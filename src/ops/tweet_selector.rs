@@ -0,0 +1,56 @@
+//! Filters for picking queued tweets to move
+//!
+//! Built from the `--author`, `--from` and `--to` options shared by the `reschedule` and `respread` subsystems, never
+//! serialised.
+
+
+use chrono::{DateTime, FixedOffset};
+use self::super::QueuedTweet;
+
+
+/// A filter for picking unposted queued tweets to operate on.
+///
+/// Every specified criterion has to match, so the default one picks all unposted tweets.
+///
+/// Tweets scheduled after other tweets are never picked, since their times follow their anchors'.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Default)]
+pub struct TweetSelector {
+    /// Only pick tweets by this author, if any.
+    pub author: Option<String>,
+    /// Only pick tweets scheduled at or after this time, if any.
+    pub from: Option<DateTime<FixedOffset>>,
+    /// Only pick tweets scheduled at or before this time, if any.
+    pub to: Option<DateTime<FixedOffset>>,
+}
+
+impl TweetSelector {
+    /// Check whether the specified tweet is unposted, not scheduled after another one and matches all criteria.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate tweetr;
+    /// # extern crate chrono;
+    /// # use tweetr::ops::{TweetSelector, QueuedTweet};
+    /// # use chrono::DateTime;
+    /// # fn main() {
+    /// let tweet = QueuedTweet::new("nabijaczleweli", DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap(), "Capitalism");
+    ///
+    /// assert!(TweetSelector::default().matches(&tweet));
+    /// assert!(TweetSelector {
+    ///     author: Some("nabijaczleweli".to_string()),
+    ///     from: Some(DateTime::parse_from_rfc3339("2016-09-09T00:00:00+02:00").unwrap()),
+    ///     to: None,
+    /// }.matches(&tweet));
+    /// assert!(!TweetSelector {
+    ///     author: Some("danerangLP".to_string()),
+    ///     from: None,
+    ///     to: None,
+    /// }.matches(&tweet));
+    /// # }
+    /// ```
+    pub fn matches(&self, tweet: &QueuedTweet) -> bool {
+        tweet.id.is_none() && tweet.failed.is_none() && tweet.after.is_none() &&
+        self.author.as_ref().map(|a| a.eq_ignore_ascii_case(&tweet.author)).unwrap_or(true) && self.from.map(|f| tweet.time >= f).unwrap_or(true) && self.to.map(|t| tweet.time <= t).unwrap_or(true)
+    }
+}
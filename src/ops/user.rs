@@ -1,15 +1,22 @@
+//! Users to post on behalf of
+//!
+//! Serialised via `UserForSerialisation`s, which have the suspension flattened into its reason and its time converted to
+//! an RFC-3339 `String`, the same way `QueuedTweet`s are, and no access token secret if it's fetched via a command.
+
+
+use self::super::super::util::{create_private_file, run_secret_command};
+use self::super::{AppTokens, SecretKey, read_toml_file};
+use chrono::{DateTime, FixedOffset};
 use self::super::super::Outcome;
-use self::super::read_toml_file;
 use std::cmp::Ordering;
 use toml::encode_str;
 use egg_mode::Token;
 use std::path::Path;
 use std::io::Write;
-use std::fs::File;
 
 
 /// All user data required to connect to the Twitter API.
-#[derive(Debug, Clone, Hash, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub struct User {
     /// The user's name (not display name)
     pub name: String,
@@ -19,15 +26,115 @@ pub struct User {
     pub access_token_key: String,
     /// The key part of the access token (the one actually used to access the API)
     pub access_token_secret: String,
+    /// Shell command printing the access token secret, run instead of storing it, if any.
+    ///
+    /// The access token secret is empty if this is set, get the access token via `access_token()`.
+    pub secret_command: Option<String>,
+    /// The name of the app profile the access token belongs to, if not the default one.
+    pub app: Option<String>,
+
+    /// Minimum amount of minutes between two posts by this user, if any.
+    pub min_minutes_between_posts: Option<u64>,
+    /// Maximum amount of posts by this user in any 24 hours, if any.
+    ///
+    /// Must be at least 1, `User::read()` rejects users with 0.
+    pub max_posts_per_day: Option<u64>,
+    /// How many minutes to randomly move this user's tweets by in either direction when queueing them, if any.
+    pub jitter_minutes: Option<u64>,
+
+    /// Why and since when the user's tweets aren't posted, if they aren't.
+    pub suspended: Option<Suspension>,
+}
+
+/// Why a user was suspended, e.g. because its access tokens were revoked, and when.
+///
+/// Suspended users' tweets aren't posted until the user is re-authorised.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct Suspension {
+    /// The error that caused the suspension.
+    pub reason: String,
+    /// When the user was suspended.
+    pub time: DateTime<FixedOffset>,
+}
+
+/// A named group of users to post the same tweets on behalf of, one after another.
+///
+/// Stored in the same file as the users, tweets whose `author` is a group's name are posted by all of its members.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub struct UserGroup {
+    /// The group's name, can't be the same as any user's.
+    pub name: String,
+    /// The names of users in the group, in posting order.
+    pub members: Vec<String>,
+    /// How many minutes to wait between posts by consecutive members, if any.
+    pub stagger_minutes: Option<u64>,
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+struct UserForSerialisation {
+    pub name: String,
+    pub id: i64,
+    pub access_token_key: String,
+    pub access_token_secret: Option<String>,
+    pub secret_command: Option<String>,
+    pub app: Option<String>,
+
+    pub min_minutes_between_posts: Option<u64>,
+    pub max_posts_per_day: Option<u64>,
+    pub jitter_minutes: Option<u64>,
+
+    pub suspended: Option<String>,
+    pub time_suspended: Option<String>,
 }
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq, RustcEncodable, RustcDecodable)]
 struct Users {
-    user: Vec<User>,
+    user: Vec<UserForSerialisation>,
+    group: Option<Vec<UserGroup>>,
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+struct UserGroups {
+    user: Option<Vec<UserName>>,
+    group: Option<Vec<UserGroup>>,
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+struct UserName {
+    name: String,
 }
 
 
 impl User {
+    /// Create a user with the specified access token, using the default app profile, without limits, jitter, or suspension.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::User;
+    /// let user = User::new("nabijaczleweli",
+    ///                      0x81423,
+    ///                      "529443-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4",
+    ///                      "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5");
+    /// assert_eq!(user.name, "nabijaczleweli");
+    /// assert_eq!(user.app, None);
+    /// assert_eq!(user.suspended, None);
+    /// ```
+    pub fn new<N: Into<String>, K: Into<String>, S: Into<String>>(name: N, id: i64, access_token_key: K, access_token_secret: S) -> User {
+        User {
+            name: name.into(),
+            id: id,
+            access_token_key: access_token_key.into(),
+            access_token_secret: access_token_secret.into(),
+            secret_command: None,
+            app: None,
+            min_minutes_between_posts: None,
+            max_posts_per_day: None,
+            jitter_minutes: None,
+            suspended: None,
+        }
+    }
+
     /// Create a `User` instance straight from the return value of `egg_mode::access_token()`
     pub fn from_raw_access_token<'t>(raw: (Token<'t>, i64, String)) -> User {
         let (access_token, user_id, username) = raw;
@@ -36,17 +143,124 @@ impl User {
             id: user_id,
             access_token_key: access_token.key.to_owned().to_string(),
             access_token_secret: access_token.secret.to_owned().to_string(),
+            secret_command: None,
+            app: None,
+            min_minutes_between_posts: None,
+            max_posts_per_day: None,
+            jitter_minutes: None,
+            suspended: None,
         }
     }
 
-    /// Read all user data from the specified file.
-    pub fn read(p: &Path) -> Result<Vec<User>, Option<Outcome>> {
-        read_toml_file(p, "users").map(|us: Users| us.user)
+    /// Get the user's access token, running its `secret_command` to get the secret, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::ops::User;
+    /// let mut user = User::new("nabijaczleweli", 0x81423, "529443-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4", "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5");
+    /// assert_eq!(user.access_token().unwrap().secret, "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5");
+    ///
+    /// user.access_token_secret = String::new();
+    /// user.secret_command = Some("echo 0FPMXpBeCAWrpmTfvmfbfPK7kfTBDUuBzy0a8qT2hgUMP".to_string());
+    /// assert_eq!(user.access_token().unwrap().secret, "0FPMXpBeCAWrpmTfvmfbfPK7kfTBDUuBzy0a8qT2hgUMP");
+    /// ```
+    pub fn access_token(&self) -> Result<Token<'static>, Outcome> {
+        let secret = match self.secret_command {
+            Some(ref command) => {
+                try!(run_secret_command(command)
+                    .map_err(|e| Outcome::InvalidCredentials(vec![format!("getting access token secret of user {} failed: {}", self.name, e)])))
+            }
+            None => self.access_token_secret.clone(),
+        };
+
+        Ok(Token::new(self.access_token_key.clone(), secret))
+    }
+
+    /// Read all user data from the specified file, decrypting the access token secrets with the specified key, if any.
+    ///
+    /// Users without a secret or with half of a suspension make the whole file fail to read, with every such user named.
+    pub fn read(p: &Path, key: Option<&SecretKey>) -> Result<Vec<User>, Option<Outcome>> {
+        let users: Users = try!(read_toml_file(p, "users"));
+        let (users, errors): (Vec<_>, Vec<_>) = users.user.into_iter().map(|u| u.into()).partition(Result::is_ok);
+        if !errors.is_empty() {
+            return Err(Some(Outcome::FileParsingFailed {
+                desc: "users",
+                errors: errors.into_iter().map(Result::unwrap_err).collect(),
+            }));
+        }
+        let mut users: Vec<User> = users.into_iter().map(Result::unwrap).collect();
+        for user in &mut users {
+            user.access_token_secret = try!(SecretKey::reveal(key, &user.access_token_secret, &format!("access token secret of user {}", user.name))
+                .map_err(Some));
+        }
+        Ok(users)
+    }
+
+    /// Save all user data to the specified file, encrypting the access token secrets with the specified key, if any.
+    ///
+    /// User groups already in the file are kept, and nothing is written if they can't be read.
+    pub fn write(users: Vec<User>, p: &Path, key: Option<&SecretKey>) -> Result<(), Outcome> {
+        let groups = if p.exists() {
+            try!(UserGroup::read(p).map_err(|e| {
+                e.unwrap_or_else(|| {
+                    Outcome::FileParsingFailed {
+                        desc: "user groups",
+                        errors: vec![],
+                    }
+                })
+            }))
+        } else {
+            vec![]
+        };
+
+        User::write_with_groups(users, groups, p, key);
+        Ok(())
+    }
+
+    /// Save all user data and the specified user groups to the specified file, encrypting the access token secrets with the
+    /// specified key, if any.
+    ///
+    /// User groups already in the file are replaced.
+    pub fn write_with_groups(users: Vec<User>, groups: Vec<UserGroup>, p: &Path, key: Option<&SecretKey>) {
+        create_private_file(p)
+            .unwrap()
+            .write_all(encode_str(&Users {
+                    user: users.into_iter()
+                        .map(|mut u| {
+                            u.access_token_secret = SecretKey::conceal(key, u.access_token_secret);
+                            UserForSerialisation::from(u)
+                        })
+                        .collect(),
+                    group: if groups.is_empty() { None } else { Some(groups) },
+                })
+                .as_bytes())
+            .unwrap();
     }
+}
+
+impl UserGroup {
+    /// Read all user groups from the specified users file.
+    ///
+    /// The file not having any groups is fine, but groups named the same as a user, ignoring case, aren't, as it'd be
+    /// ambiguous which one tweets by that name are by.
+    pub fn read(p: &Path) -> Result<Vec<UserGroup>, Option<Outcome>> {
+        let gs: UserGroups = try!(read_toml_file(p, "user groups"));
+        let users = gs.user.unwrap_or_default();
+        let groups = gs.group.unwrap_or_default();
 
-    /// Save all user data to the specified file.
-    pub fn write(users: Vec<User>, p: &Path) {
-        File::create(p).unwrap().write_all(encode_str(&Users { user: users }).as_bytes()).unwrap();
+        let errors: Vec<_> = groups.iter()
+            .filter(|g| users.iter().any(|u| u.name.eq_ignore_ascii_case(&g.name)))
+            .map(|g| format!("group {} is named the same as a user", g.name))
+            .collect();
+        if errors.is_empty() {
+            Ok(groups)
+        } else {
+            Err(Some(Outcome::FileParsingFailed {
+                desc: "user groups",
+                errors: errors,
+            }))
+        }
     }
 }
 
@@ -61,3 +275,70 @@ impl PartialOrd for User {
         self.name.partial_cmp(&other.name)
     }
 }
+
+impl From<User> for UserForSerialisation {
+    fn from(u: User) -> UserForSerialisation {
+        UserForSerialisation {
+            name: u.name,
+            id: u.id,
+            access_token_key: u.access_token_key,
+            access_token_secret: if u.secret_command.is_some() {
+                None
+            } else {
+                Some(u.access_token_secret)
+            },
+            secret_command: u.secret_command,
+            app: u.app,
+            min_minutes_between_posts: u.min_minutes_between_posts,
+            max_posts_per_day: u.max_posts_per_day,
+            jitter_minutes: u.jitter_minutes,
+            time_suspended: u.suspended.as_ref().map(|s| s.time.to_rfc3339()),
+            suspended: u.suspended.map(|s| s.reason),
+        }
+    }
+}
+
+impl Into<Result<User, String>> for UserForSerialisation {
+    fn into(self) -> Result<User, String> {
+        let access_token_secret = match (self.access_token_secret, self.secret_command.is_some()) {
+            (Some(secret), _) => secret,
+            (None, true) => String::new(),
+            (None, false) => return Err(format!("user {}: neither access_token_secret nor secret_command specified", self.name)),
+        };
+        if let Some(ref app) = self.app {
+            if !AppTokens::is_valid_profile_name(app) {
+                return Err(format!("user {}: app \"{}\" isn't a valid app profile name", self.name, app));
+            }
+        }
+        if self.max_posts_per_day == Some(0) {
+            return Err(format!("user {}: max_posts_per_day is 0, remove it or the user instead", self.name));
+        }
+        let suspended = match (self.suspended, self.time_suspended) {
+            (Some(reason), Some(dts)) => {
+                Some(Suspension {
+                    reason: reason,
+                    time: match DateTime::parse_from_rfc3339(&dts) {
+                        Ok(time) => time,
+                        Err(e) => return Err(format!("user {}: time_suspended \"{}\" isn't a valid RFC3339 time: {}", self.name, dts, e)),
+                    },
+                })
+            }
+            (None, None) => None,
+            (Some(_), None) => return Err(format!("user {}: suspended without time_suspended", self.name)),
+            (None, Some(_)) => return Err(format!("user {}: time_suspended without suspended", self.name)),
+        };
+
+        Ok(User {
+            name: self.name,
+            id: self.id,
+            access_token_key: self.access_token_key,
+            access_token_secret: access_token_secret,
+            secret_command: self.secret_command,
+            app: self.app,
+            min_minutes_between_posts: self.min_minutes_between_posts,
+            max_posts_per_day: self.max_posts_per_day,
+            jitter_minutes: self.jitter_minutes,
+            suspended: suspended,
+        })
+    }
+}
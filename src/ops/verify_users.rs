@@ -0,0 +1,132 @@
+//! This module contains the functions used only by the `verify-users` subsystem.
+//!
+//! The flow of the `verify-users` subsystem is as follows:
+//!
+//! ```plaintext
+//! Options::parse()
+//! |> ops::verify_users::verify()
+//! |> ops::AppTokens::read()
+//! |> ops::User::read()
+//! |> ops::verify_users::verify_user()
+//! |> ops::verify_users::revoked_outcome()
+//! ```
+
+
+use self::super::super::api::is_authentication_error;
+use self::super::super::util::span_r;
+use egg_mode::{Token, verify_tokens};
+use self::super::{User, verify_app_profiles, verify_file};
+use self::super::super::Outcome;
+use std::path::PathBuf;
+use std::io::Write;
+
+
+/// Verify if, given the current configuration, it's permitted to continue with the subsequent steps of the `verify-users`
+/// subsystem.
+///
+/// The return value contains either the path to the file containing the global users data or why getting it or any app
+/// profile failed, the default one isn't required.
+///
+/// # Examples
+///
+/// Verifying when the global users data doesn't exist.
+///
+/// ```
+/// # use tweetr::ops::verify_users;
+/// # use std::fs::{self, File};
+/// # use std::env::temp_dir;
+/// # use tweetr::Outcome;
+/// # use std::io::Write;
+/// let tf = temp_dir().join("tweetr-doctest").join("ops-verify-users-verify-0");
+/// fs::create_dir_all(&tf).unwrap();
+/// File::create(tf.join("app.toml")).unwrap().write(&[]).unwrap();
+///
+/// assert_eq!(verify_users::verify(&("$TEMP/ops-verify-users-verify-0".to_string(), tf)),
+///            Err(Outcome::RequiredFileFromSubsystemNonexistant {
+///                subsys: "add-user",
+///                fname: "$TEMP/ops-verify-users-verify-0/users.toml".to_string(),
+///            }));
+/// ```
+pub fn verify(config_dir: &(String, PathBuf)) -> Result<PathBuf, Outcome> {
+    try!(verify_app_profiles(config_dir));
+    verify_file("users.toml", true, config_dir, false, "add-user")
+}
+
+/// Check whether the specified user's access tokens still work, printing the result and optionally progress.
+///
+/// Returns `Ok(false)` if the tokens were revoked or expired, and `Err()` if checking them failed for another reason.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use tweetr::ops::{AppTokens, User, verify_users};
+/// let result = verify_users::verify_user(&User::new("nabijaczleweli",
+///                                                   0x81423,
+///                                                   "529443-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4",
+///                                                   "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5"),
+///                                        &AppTokens {
+///                                                key: "qzuqpwr101q4RtK9mDorI9ndm".to_string(),
+///                                                secret: "HW4YG3Kdcap5ovcZ5fZfBJFedKR6GQe9MtZDS9Gm34hXiirkU5".to_string(),
+///                                                secret_command: None,
+///                                            }
+///                                            .into(),
+///                                        false,
+///                                        &mut vec![]);
+/// assert_eq!(result, Ok(true));
+/// ```
+pub fn verify_user<'a, W: Write>(user: &User, app: &Token<'a>, verbose: bool, output: &mut W) -> Result<bool, Outcome> {
+    write!(output, "{}#{}...", user.name, user.id).unwrap();
+    output.flush().unwrap();
+
+    let access_token = try!(user.access_token());
+    let (dur, res) = span_r(|| verify_tokens(app, &access_token));
+    if verbose {
+        write!(output, " {}ms", dur.num_milliseconds()).unwrap();
+    }
+
+    match res {
+        Ok(_) => {
+            writeln!(output, " OK").unwrap();
+            Ok(true)
+        }
+        Err(ref e) if is_authentication_error(e) => {
+            writeln!(output, " REVOKED").unwrap();
+            Ok(false)
+        }
+        Err(e) => {
+            writeln!(output, " FAILED").unwrap();
+            Err(Outcome::TwitterAPIError(e.to_string()))
+        }
+    }
+}
+
+/// Get the error to report for the specified users whose tokens were revoked and the specified amount of users whose tokens
+/// couldn't be checked, if any.
+///
+/// Revoked tokens take precedence, since the errors of the failed checks are printed as they happen.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::ops::verify_users;
+/// # use tweetr::Outcome;
+/// assert_eq!(verify_users::revoked_outcome(&vec![], 0), Outcome::NoError);
+/// assert_eq!(verify_users::revoked_outcome(&vec!["nabijaczleweli".to_string(), "danerangLP".to_string()], 1),
+///            Outcome::RequiredDataFromSubsystemNonexistant {
+///                subsys: "reauth-user",
+///                desc: "re-authorise users nabijaczleweli, danerangLP".to_string(),
+///            });
+/// assert_eq!(verify_users::revoked_outcome(&vec![], 2), Outcome::ConfigurationProblems(2));
+/// ```
+pub fn revoked_outcome(revoked: &Vec<String>, failed: usize) -> Outcome {
+    if !revoked.is_empty() {
+        Outcome::RequiredDataFromSubsystemNonexistant {
+            subsys: "reauth-user",
+            desc: format!("re-authorise users {}", revoked.join(", ")),
+        }
+    } else if failed != 0 {
+        Outcome::ConfigurationProblems(failed)
+    } else {
+        Outcome::NoError
+    }
+}
@@ -11,12 +11,19 @@
 //! ```
 
 
-use clap::{self, App, SubCommand, Arg, AppSettings};
+use self::super::util::{create_private_dir, parse_shift, parse_time};
+use clap::{self, App, SubCommand, Arg, ArgMatches, AppSettings};
+use chrono::{DateTime, FixedOffset, Duration as ChronoDuration};
+use self::super::ops::start_daemon::CatchUpPolicy;
+use self::super::ops::{AppTokens, TweetSelector};
+use std::env::{self, home_dir};
+use std::io::{Read, stderr};
+use std::fs::{self, File};
+use self::super::Outcome;
 use std::time::Duration;
 use std::path::PathBuf;
-use std::env::home_dir;
+use std::process::exit;
 use std::str::FromStr;
-use std::fs;
 
 
 /// All possible subsystems, think `cargo`'s or `git`'s.
@@ -26,16 +33,42 @@ pub enum Subsystem {
     Init {
         /// Whether to override current app configuration. Default: `false`
         force: bool,
+        /// App key and secret to use instead of prompting for them, from `--key` and `--secret` or `$TWEETR_APP_KEY` and
+        /// `$TWEETR_APP_SECRET`. Default: `None`
+        tokens: Option<(String, String)>,
+        /// Whether to read the app key and secret as JSON from stdin instead of prompting for them. Default: `false`
+        json: bool,
+        /// Name of the app profile to initialise, if not the default one. Default: `None`
+        app: Option<String>,
     },
     /// Add and authorise a user
     AddUser {
         /// Whether to print more user data. Default: `false`
         verbose: bool,
+        /// Whether to get the OAuth verifier via a local callback instead of a PIN. Default: `false`
+        callback: bool,
+        /// Existing access token key and secret to add the user with instead of authorising it, from `--import-token` or
+        /// `$TWEETR_ACCESS_TOKEN_KEY` and `$TWEETR_ACCESS_TOKEN_SECRET`. Default: `None`
+        import_token: Option<(String, String)>,
+        /// Whether to read an existing access token key and secret as JSON from stdin instead of authorising the user.
+        /// Default: `false`
+        import_json: bool,
+        /// Name of the app profile to authorise the user for, if not the default one. Default: `None`
+        app: Option<String>,
     },
     /// Add a tweet to the queue
     QueueTweet {
         /// File to load tweets from, if any. Default: `None`
         file_to_load: Option<PathBuf>,
+        /// Whether to queue tweets by users that weren't added yet. Default: `false`
+        allow_unknown_authors: bool,
+    },
+    /// Check tweets to be queued from a file without queueing them
+    Check {
+        /// File to check. Default: N/A
+        file: PathBuf,
+        /// Whether to allow tweets by users that weren't added yet. Default: `false`
+        allow_unknown_authors: bool,
     },
     /// Start the tweet-posting daemon.
     StartDaemon {
@@ -43,6 +76,63 @@ pub enum Subsystem {
         delay: Duration,
         /// Whether to log all network requests. Default: `false`
         verbose: bool,
+        /// What to do with overdue tweets. Default: `CatchUpPolicy::PostAll`
+        catch_up: CatchUpPolicy,
+    },
+    /// Move a block of queued tweets by a fixed amount of time
+    Reschedule {
+        /// How much to move the tweets by.
+        shift: ChronoDuration,
+        /// Which tweets to move. Default: all unposted ones
+        selector: TweetSelector,
+    },
+    /// Distribute a block of queued tweets evenly across a window
+    Respread {
+        /// Where to put the first tweet.
+        start: DateTime<FixedOffset>,
+        /// Where to put the last tweet.
+        end: DateTime<FixedOffset>,
+        /// Which tweets to distribute. Default: all unposted ones
+        selector: TweetSelector,
+    },
+    /// Update stored users' handles to their current ones
+    RefreshUsers {
+        /// Whether to log all network requests. Default: `false`
+        verbose: bool,
+    },
+    /// List added users and user groups
+    ListUsers,
+    /// Remove an added user
+    RemoveUser {
+        /// The name of the user to remove.
+        name: String,
+    },
+    /// Re-authorise an added user, replacing its access tokens
+    ReauthUser {
+        /// The name of the user to re-authorise.
+        name: String,
+        /// Whether to print more user data. Default: `false`
+        verbose: bool,
+        /// Whether to get the OAuth verifier via a local callback instead of a PIN. Default: `false`
+        callback: bool,
+    },
+    /// Check whether added users' access tokens still work
+    VerifyUsers {
+        /// Whether to log all network requests. Default: `false`
+        verbose: bool,
+    },
+    /// Check the whole configuration for problems
+    Doctor {
+        /// Whether to also check whether the users' access tokens work. Default: `false`
+        online: bool,
+        /// Whether to log all network requests. Default: `false`
+        verbose: bool,
+    },
+    /// Change the passphrase the secrets are encrypted with, encrypting them if they weren't
+    Rekey {
+        /// The passphrase to encrypt the secrets with, from `--new-passphrase-file` or `$TWEETR_NEW_PASSPHRASE`, or `None` to
+        /// decrypt them.
+        new_passphrase: Option<String>,
     },
 }
 
@@ -52,6 +142,8 @@ pub enum Subsystem {
 pub struct Options {
     /// Directory containing configuration. Default: `"$HOME/.tweetr"`
     pub config_dir: (String, PathBuf),
+    /// Passphrase the secrets are encrypted with, from `--passphrase-file` or `$TWEETR_PASSPHRASE`. Default: `None`
+    pub passphrase: Option<String>,
     /// The specified subsystem.
     pub subsystem: Subsystem,
 }
@@ -70,21 +162,96 @@ impl Options {
                     amount of $$$ for posting them automatically")
             .arg(Arg::from_usage("-c --config-dir=[CONFIG_DIR] 'Directory containing configuration. Default: $HOME/.tweetr'")
                 .validator(Options::config_dir_validator))
+            .arg(Arg::from_usage("--passphrase-file=[PASSPHRASE_FILE] 'File containing the passphrase the secrets are encrypted with. Default: \
+                                  $TWEETR_PASSPHRASE'")
+                .validator(Options::passphrase_file_validator))
             .subcommand(SubCommand::with_name("init")
                 .about("Initialise global app data")
-                .arg(Arg::from_usage("-f --force 'Override current app configuration'")))
+                .args(&[Arg::from_usage("-f --force 'Override current app configuration'"),
+                        Arg::from_usage("--key=[key] 'App key to use instead of prompting for it. Default: $TWEETR_APP_KEY'").requires("secret"),
+                        Arg::from_usage("--secret=[secret] 'App secret to use instead of prompting for it. Default: $TWEETR_APP_SECRET'").requires("key"),
+                        Arg::from_usage("--json 'Read {\"key\": ..., \"secret\": ...} from stdin instead of prompting'")
+                            .conflicts_with_all(&["key", "secret"]),
+                        Arg::from_usage("--app=[app] 'Initialise the named app profile instead of the default one'").validator(Options::app_validator)]))
             .subcommand(SubCommand::with_name("add-user")
                 .about("Add and authorise a user")
-                .arg(Arg::from_usage("-v --verbose 'Print more user data'")))
+                .args(&[Arg::from_usage("-v --verbose 'Print more user data'"),
+                        Arg::from_usage("--callback 'Authorise via a local callback instead of a PIN, if there's a display'"),
+                        Arg::with_name("import-token")
+                            .long("import-token")
+                            .help("Add the user with an existing access token instead of authorising it. Default: $TWEETR_ACCESS_TOKEN_KEY \
+                                   and $TWEETR_ACCESS_TOKEN_SECRET")
+                            .number_of_values(2)
+                            .value_names(&["key", "secret"])
+                            .conflicts_with("callback"),
+                        Arg::from_usage("--import-json 'Read an existing access token's {\"key\": ..., \"secret\": ...} from stdin instead of \
+                                         authorising the user'")
+                            .conflicts_with_all(&["import-token", "callback"]),
+                        Arg::from_usage("--app=[app] 'Authorise the user for the named app profile instead of the default one'")
+                            .validator(Options::app_validator)]))
             .subcommand(SubCommand::with_name("queue-tweet")
                 .about("Add a tweet to the queue")
-                .arg(Arg::from_usage("-f --file=[file] 'Load tweets from the specified file'").validator(Options::tweets_file_validator)))
+                .args(&[Arg::from_usage("-f --file=[file] 'Load tweets from the specified file'").validator(Options::tweets_file_validator),
+                        Arg::from_usage("--allow-unknown-authors 'Queue tweets by users that weren't added yet'")]))
+            .subcommand(SubCommand::with_name("check")
+                .about("Check tweets to be queued from a file without queueing them")
+                .args(&[Arg::from_usage("<FILE> 'File with the tweets to check'").validator(Options::tweets_file_validator),
+                        Arg::from_usage("--allow-unknown-authors 'Allow tweets by users that weren't added yet'")]))
             .subcommand(SubCommand::with_name("start-daemon")
                 .about("Start the tweet-posting daemon")
                 .args(&[Arg::from_usage("-v --verbose 'Log all network requests'"),
                         Arg::from_usage("--delay=<delay> 'How long to wait between trying to post again [ms]'")
                             .default_value("60000")
-                            .validator(Options::duration_validator)]))
+                            .validator(Options::duration_validator),
+                        Arg::from_usage("--catch-up=<policy> 'What to do with overdue tweets: all, skip:<minutes>, drop:<minutes> or \
+                                         respread:<minutes>'")
+                            .default_value("all")
+                            .validator(Options::catch_up_policy_validator)]))
+            .subcommand(SubCommand::with_name("reschedule")
+                .about("Move a block of queued tweets by a fixed amount of time")
+                .args(&[Arg::from_usage("--shift=<shift> 'How much to move the tweets by, e.g. +2h, -30m, +1d'")
+                            .allow_hyphen_values(true)
+                            .validator(Options::shift_validator),
+                        Arg::from_usage("--author=[author] 'Only move tweets by this author'"),
+                        Arg::from_usage("--from=[time] 'Only move tweets scheduled at or after this time'").validator(Options::time_validator),
+                        Arg::from_usage("--to=[time] 'Only move tweets scheduled at or before this time'").validator(Options::time_validator)]))
+            .subcommand(SubCommand::with_name("respread")
+                .about("Distribute a block of queued tweets evenly across a window")
+                .args(&[Arg::with_name("between")
+                            .long("between")
+                            .help("Window to distribute the tweets across")
+                            .required(true)
+                            .number_of_values(2)
+                            .value_names(&["start", "end"])
+                            .validator(Options::time_validator),
+                        Arg::from_usage("--author=[author] 'Only distribute tweets by this author'"),
+                        Arg::from_usage("--from=[time] 'Only distribute tweets scheduled at or after this time'").validator(Options::time_validator),
+                        Arg::from_usage("--to=[time] 'Only distribute tweets scheduled at or before this time'").validator(Options::time_validator)]))
+            .subcommand(SubCommand::with_name("refresh-users")
+                .about("Update stored users' handles to their current ones")
+                .arg(Arg::from_usage("-v --verbose 'Log all network requests'")))
+            .subcommand(SubCommand::with_name("list-users").about("List added users and user groups"))
+            .subcommand(SubCommand::with_name("remove-user")
+                .about("Remove an added user")
+                .arg(Arg::from_usage("<name> 'The user to remove'")))
+            .subcommand(SubCommand::with_name("reauth-user")
+                .about("Re-authorise an added user, replacing its access tokens")
+                .args(&[Arg::from_usage("<name> 'The user to re-authorise'"),
+                        Arg::from_usage("-v --verbose 'Print more user data'"),
+                        Arg::from_usage("--callback 'Authorise via a local callback instead of a PIN, if there's a display'")]))
+            .subcommand(SubCommand::with_name("verify-users")
+                .about("Check whether added users' access tokens still work")
+                .arg(Arg::from_usage("-v --verbose 'Log all network requests'")))
+            .subcommand(SubCommand::with_name("doctor")
+                .about("Check the whole configuration for problems")
+                .args(&[Arg::from_usage("--online 'Also check whether the users' access tokens work'"),
+                        Arg::from_usage("-v --verbose 'Log all network requests'")]))
+            .subcommand(SubCommand::with_name("rekey")
+                .about("Change the passphrase the secrets are encrypted with, encrypting them if they weren't")
+                .args(&[Arg::from_usage("--new-passphrase-file=[NEW_PASSPHRASE_FILE] 'File containing the new passphrase. Default: \
+                                         $TWEETR_NEW_PASSPHRASE'")
+                            .validator(Options::passphrase_file_validator),
+                        Arg::from_usage("--decrypt 'Store the secrets in plaintext instead'").conflicts_with("new-passphrase-file")]))
             .get_matches();
 
         Options {
@@ -96,7 +263,7 @@ impl Options {
                             hd = hd.canonicalize().unwrap();
                             hd.push(".tweetr");
 
-                            fs::create_dir_all(&hd).unwrap();
+                            create_private_dir(&hd).unwrap();
                             ("$HOME/.tweetr".to_string(), hd)
                         }
                         None => {
@@ -110,16 +277,112 @@ impl Options {
                     }
                 }
             },
+            passphrase: Options::passphrase(matches.value_of("passphrase-file"), "TWEETR_PASSPHRASE").unwrap_or_else(Options::exit_with),
             subsystem: match matches.subcommand() {
-                ("init", Some(init_matches)) => Subsystem::Init { force: init_matches.is_present("force") },
-                ("add-user", Some(add_user_matches)) => Subsystem::AddUser { verbose: add_user_matches.is_present("verbose") },
+                ("init", Some(init_matches)) => {
+                    let tokens = Options::token_pair(init_matches.value_of("key").and_then(|k| init_matches.value_of("secret").map(|s| (k, s))),
+                                                     !init_matches.is_present("json"),
+                                                     "TWEETR_APP_KEY",
+                                                     "TWEETR_APP_SECRET");
+
+                    Subsystem::Init {
+                        force: init_matches.is_present("force"),
+                        tokens: tokens,
+                        json: init_matches.is_present("json"),
+                        app: init_matches.value_of("app").map(str::to_string),
+                    }
+                }
+                ("add-user", Some(add_user_matches)) => {
+                    let import_token = Options::token_pair(add_user_matches.values_of("import-token").map(|mut kv| (kv.next().unwrap(), kv.next().unwrap())),
+                                                           !(add_user_matches.is_present("import-json") || add_user_matches.is_present("callback")),
+                                                           "TWEETR_ACCESS_TOKEN_KEY",
+                                                           "TWEETR_ACCESS_TOKEN_SECRET");
+
+                    Subsystem::AddUser {
+                        verbose: add_user_matches.is_present("verbose"),
+                        callback: add_user_matches.is_present("callback"),
+                        import_token: import_token,
+                        import_json: add_user_matches.is_present("import-json"),
+                        app: add_user_matches.value_of("app").map(str::to_string),
+                    }
+                }
                 ("queue-tweet", Some(queue_tweet_matches)) => {
-                    Subsystem::QueueTweet { file_to_load: queue_tweet_matches.value_of("file").map(fs::canonicalize).map(Result::unwrap) }
+                    Subsystem::QueueTweet {
+                        file_to_load: queue_tweet_matches.value_of("file").map(fs::canonicalize).map(Result::unwrap),
+                        allow_unknown_authors: queue_tweet_matches.is_present("allow-unknown-authors"),
+                    }
+                }
+                ("check", Some(check_matches)) => {
+                    Subsystem::Check {
+                        file: fs::canonicalize(check_matches.value_of("FILE").unwrap()).unwrap(),
+                        allow_unknown_authors: check_matches.is_present("allow-unknown-authors"),
+                    }
                 }
                 ("start-daemon", Some(start_daemon_matches)) => {
                     Subsystem::StartDaemon {
                         delay: Duration::from_millis(u64::from_str(start_daemon_matches.value_of("delay").unwrap()).unwrap()),
                         verbose: start_daemon_matches.is_present("verbose"),
+                        catch_up: CatchUpPolicy::from_str(start_daemon_matches.value_of("catch-up").unwrap()).unwrap(),
+                    }
+                }
+                ("reschedule", Some(reschedule_matches)) => {
+                    Subsystem::Reschedule {
+                        shift: parse_shift(reschedule_matches.value_of("shift").unwrap()).unwrap(),
+                        selector: Options::tweet_selector(reschedule_matches),
+                    }
+                }
+                ("respread", Some(respread_matches)) => {
+                    let between: Vec<_> = respread_matches.values_of("between").unwrap().map(|t| parse_time(t).unwrap()).collect();
+                    if between[0] > between[1] {
+                        clap::Error {
+                                message: "The start of the --between window can't be after its end".to_string(),
+                                kind: clap::ErrorKind::ValueValidation,
+                                info: None,
+                            }
+                            .exit()
+                    }
+                    Subsystem::Respread {
+                        start: between[0],
+                        end: between[1],
+                        selector: Options::tweet_selector(respread_matches),
+                    }
+                }
+                ("refresh-users", Some(refresh_users_matches)) => Subsystem::RefreshUsers { verbose: refresh_users_matches.is_present("verbose") },
+                ("list-users", Some(_)) => Subsystem::ListUsers,
+                ("remove-user", Some(remove_user_matches)) => Subsystem::RemoveUser { name: remove_user_matches.value_of("name").unwrap().to_string() },
+                ("reauth-user", Some(reauth_user_matches)) => {
+                    Subsystem::ReauthUser {
+                        name: reauth_user_matches.value_of("name").unwrap().to_string(),
+                        verbose: reauth_user_matches.is_present("verbose"),
+                        callback: reauth_user_matches.is_present("callback"),
+                    }
+                }
+                ("verify-users", Some(verify_users_matches)) => Subsystem::VerifyUsers { verbose: verify_users_matches.is_present("verbose") },
+                ("doctor", Some(doctor_matches)) => {
+                    Subsystem::Doctor {
+                        online: doctor_matches.is_present("online"),
+                        verbose: doctor_matches.is_present("verbose"),
+                    }
+                }
+                ("rekey", Some(rekey_matches)) => {
+                    Subsystem::Rekey {
+                        new_passphrase: if rekey_matches.is_present("decrypt") {
+                            None
+                        } else {
+                            match Options::passphrase(rekey_matches.value_of("new-passphrase-file"), "TWEETR_NEW_PASSPHRASE")
+                                .unwrap_or_else(Options::exit_with) {
+                                Some(passphrase) => Some(passphrase),
+                                None => {
+                                    clap::Error {
+                                            message: "No new passphrase specified, use --new-passphrase-file, $TWEETR_NEW_PASSPHRASE or --decrypt"
+                                                .to_string(),
+                                            kind: clap::ErrorKind::MissingRequiredArgument,
+                                            info: None,
+                                        }
+                                        .exit()
+                                }
+                            }
+                        },
                     }
                 }
                 _ => panic!("No subcommand passed"),
@@ -127,10 +390,107 @@ impl Options {
         }
     }
 
+    fn tweet_selector(matches: &ArgMatches) -> TweetSelector {
+        TweetSelector {
+            author: matches.value_of("author").map(str::to_string),
+            from: matches.value_of("from").map(|t| parse_time(t).unwrap()),
+            to: matches.value_of("to").map(|t| parse_time(t).unwrap()),
+        }
+    }
+
+    /// Get a key and secret pair from the specified command-line values, if any, or from the specified environment variables
+    /// otherwise, unless the pair is to be acquired some other way.
+    ///
+    /// The environment is only used if both variables are set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::options::Options;
+    /// # use std::env;
+    /// env::set_var("TWEETR_DOCTEST_TOKEN_KEY", "qdPD7N8CcPYDKiNv81QWNWaHK");
+    /// env::set_var("TWEETR_DOCTEST_TOKEN_SECRET", "U9A5CM1LzwNliBiHGPIJyx6tFYAGVr3bCMbVkWKu8Zb13kHD4p");
+    /// assert_eq!(Options::token_pair(Some(("GeVFiYk7q8DhUmgMXE0iODrFa", "bH3VIvYEwwVmMXkTnXB8N3HEQf4ShOf2Z4e1dkaqSJNGorK2pe")),
+    ///                                true,
+    ///                                "TWEETR_DOCTEST_TOKEN_KEY",
+    ///                                "TWEETR_DOCTEST_TOKEN_SECRET"),
+    ///            Some(("GeVFiYk7q8DhUmgMXE0iODrFa".to_string(), "bH3VIvYEwwVmMXkTnXB8N3HEQf4ShOf2Z4e1dkaqSJNGorK2pe".to_string())));
+    /// assert_eq!(Options::token_pair(None, true, "TWEETR_DOCTEST_TOKEN_KEY", "TWEETR_DOCTEST_TOKEN_SECRET"),
+    ///            Some(("qdPD7N8CcPYDKiNv81QWNWaHK".to_string(), "U9A5CM1LzwNliBiHGPIJyx6tFYAGVr3bCMbVkWKu8Zb13kHD4p".to_string())));
+    /// assert_eq!(Options::token_pair(None, false, "TWEETR_DOCTEST_TOKEN_KEY", "TWEETR_DOCTEST_TOKEN_SECRET"), None);
+    /// ```
+    pub fn token_pair(given: Option<(&str, &str)>, use_env: bool, key_var: &str, secret_var: &str) -> Option<(String, String)> {
+        match given {
+            Some((key, secret)) => Some((key.to_string(), secret.to_string())),
+            None if !use_env => None,
+            None => {
+                match (env::var(key_var), env::var(secret_var)) {
+                    (Ok(key), Ok(secret)) => Some((key, secret)),
+                    _ => None,
+                }
+            }
+        }
+    }
+
+    /// Get the passphrase from the first line of the specified file, if any, or from the specified environment variable
+    /// otherwise.
+    ///
+    /// Fails with `Outcome::InvalidCredentials` if the file can't be read or isn't UTF-8.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tweetr::options::Options;
+    /// # use std::env::{self, temp_dir};
+    /// # use std::fs::{self, File};
+    /// # use std::io::Write;
+    /// let tf = temp_dir().join("tweetr-doctest").join("options-passphrase-0");
+    /// fs::create_dir_all(&tf).unwrap();
+    /// File::create(tf.join("passphrase")).unwrap().write_all(b"hunter2\nhunter3\n").unwrap();
+    ///
+    /// env::set_var("TWEETR_DOCTEST_PASSPHRASE", "hunter4");
+    /// assert_eq!(Options::passphrase(Some(tf.join("passphrase").to_str().unwrap()), "TWEETR_DOCTEST_PASSPHRASE"),
+    ///            Ok(Some("hunter2".to_string())));
+    /// assert_eq!(Options::passphrase(None, "TWEETR_DOCTEST_PASSPHRASE"), Ok(Some("hunter4".to_string())));
+    /// assert_eq!(Options::passphrase(None, "TWEETR_DOCTEST_NONEXISTANT_PASSPHRASE"), Ok(None));
+    ///
+    /// File::create(tf.join("passphrase")).unwrap().write_all(b"\xFFhunter2\n").unwrap();
+    /// assert!(Options::passphrase(Some(tf.join("passphrase").to_str().unwrap()), "TWEETR_DOCTEST_PASSPHRASE").is_err());
+    /// ```
+    pub fn passphrase(file: Option<&str>, var: &str) -> Result<Option<String>, Outcome> {
+        match file {
+            Some(file) => {
+                let mut passphrase = String::new();
+                try!(File::open(file)
+                    .and_then(|mut f| f.read_to_string(&mut passphrase))
+                    .map_err(|e| Outcome::InvalidCredentials(vec![format!("couldn't read passphrase file \"{}\": {}", file, e)])));
+                Ok(Some(passphrase.lines().next().unwrap_or("").to_string()))
+            }
+            None => Ok(env::var(var).ok()),
+        }
+    }
+
+    fn exit_with<T>(out: Outcome) -> T {
+        out.print_error(&mut stderr());
+        exit(out.exit_value())
+    }
+
     fn config_dir_validator(s: String) -> Result<(), String> {
         fs::canonicalize(&s).map(|_| ()).map_err(|_| format!("Configuration directory \"{}\" not found", s))
     }
 
+    fn app_validator(s: String) -> Result<(), String> {
+        if AppTokens::is_valid_profile_name(&s) {
+            Ok(())
+        } else {
+            Err(format!("\"{}\" is not a valid app profile name, use letters, digits, - and _", s))
+        }
+    }
+
+    fn passphrase_file_validator(s: String) -> Result<(), String> {
+        fs::canonicalize(&s).map(|_| ()).map_err(|_| format!("Passphrase file \"{}\" not found", s))
+    }
+
     fn tweets_file_validator(s: String) -> Result<(), String> {
         fs::canonicalize(&s).map(|_| ()).map_err(|_| format!("File with tweets \"{}\" not found", s))
     }
@@ -138,4 +498,16 @@ impl Options {
     fn duration_validator(s: String) -> Result<(), String> {
         u64::from_str(&s).map(|_| ()).map_err(|_| format!("\"{}\" is not a valid amount of milliseconds", s))
     }
+
+    fn catch_up_policy_validator(s: String) -> Result<(), String> {
+        CatchUpPolicy::from_str(&s).map(|_| ()).map_err(|_| format!("\"{}\" is not a valid catch-up policy", s))
+    }
+
+    fn shift_validator(s: String) -> Result<(), String> {
+        parse_shift(&s).map(|_| ()).map_err(|_| format!("\"{}\" is not a valid shift", s))
+    }
+
+    fn time_validator(s: String) -> Result<(), String> {
+        parse_time(&s).map(|_| ()).map_err(|_| format!("\"{}\" is not a valid RFC2822, RFC3339 or relative time", s))
+    }
 }
@@ -31,6 +31,21 @@ pub enum Outcome {
         /// The parsing errors that occured.
         errors: Vec<String>,
     },
+    /// The tweets to queue were invalid for the specified reasons.
+    InvalidTweets(Vec<String>),
+    /// A different user was authorised than the one that was supposed to be.
+    UserMismatch {
+        /// The user that was supposed to be authorised, as `name#id`.
+        expected: String,
+        /// The user that actually was authorised, as `name#id`.
+        authorised: String,
+    },
+    /// The specified credentials were invalid for the specified reasons.
+    InvalidCredentials(Vec<String>),
+    /// The specified files containing secrets are accessible to users other than their owner.
+    InsecurePermissions(Vec<String>),
+    /// The specified amount of problems was found in the configuration.
+    ConfigurationProblems(usize),
 }
 
 impl Outcome {
@@ -67,6 +82,29 @@ impl Outcome {
                     writeln!(err_out, "  {}", err).unwrap()
                 }
             }
+            Outcome::InvalidTweets(ref errors) => {
+                writeln!(err_out, "Refusing to queue invalid tweets:").unwrap();
+                for err in errors {
+                    writeln!(err_out, "  {}", err).unwrap()
+                }
+            }
+            Outcome::UserMismatch { ref expected, ref authorised } => {
+                writeln!(err_out, "Authorised user {} instead of {}, not replacing its tokens.", authorised, expected).unwrap()
+            }
+            Outcome::InvalidCredentials(ref errors) => {
+                writeln!(err_out, "Invalid credentials:").unwrap();
+                for err in errors {
+                    writeln!(err_out, "  {}", err).unwrap()
+                }
+            }
+            Outcome::InsecurePermissions(ref files) => {
+                writeln!(err_out, "Refusing to use secrets accessible to other users in:").unwrap();
+                for file in files {
+                    writeln!(err_out, "  {}", file).unwrap()
+                }
+                writeln!(err_out, "Restrict access to them with chmod 600.").unwrap();
+            }
+            Outcome::ConfigurationProblems(problems) => writeln!(err_out, "Found {} problems, fix them as suggested above.", problems).unwrap(),
         }
     }
 
@@ -87,6 +125,11 @@ impl Outcome {
             Outcome::RequiredDataFromSubsystemNonexistant { .. } => 2,
             Outcome::TwitterAPIError(_) => 3,
             Outcome::FileParsingFailed { .. } => 4,
+            Outcome::InvalidTweets(_) => 5,
+            Outcome::UserMismatch { .. } => 6,
+            Outcome::InvalidCredentials(_) => 7,
+            Outcome::InsecurePermissions(_) => 8,
+            Outcome::ConfigurationProblems(_) => 9,
         }
     }
 }
@@ -1,10 +1,16 @@
 //! Module containing various utility functions.
 
 
+#[cfg(unix)]
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt, PermissionsExt};
+use chrono::{Duration as ChronoDuration, FixedOffset, DateTime, Local};
 use std::io::{BufRead, Write, Result as IoResult, Error, ErrorKind};
-use chrono::{Duration as ChronoDuration};
+use std::fs::{self, DirBuilder, File, OpenOptions, Permissions};
+use std::process::{Command, Stdio};
+use std::cmp::{max, min};
 use std::time::Duration;
 use std::str::FromStr;
+use std::path::Path;
 use regex::Regex;
 use std::iter;
 
@@ -64,6 +70,58 @@ pub fn mul_str(what: &str, n: usize) -> String {
     iter::repeat(what).take(n).collect()
 }
 
+/// Get the Levenshtein distance between the two specified strings, ignoring ASCII case.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::util::levenshtein;
+/// assert_eq!(levenshtein("nabijaczleweli", "nabijaczleweli"), 0);
+/// assert_eq!(levenshtein("nabijaczleweli", "NabijaczLeweli"), 0);
+/// assert_eq!(levenshtein("nabijaczleweli", "nabijaczlewel"), 1);
+/// assert_eq!(levenshtein("kitten", "sitting"), 3);
+/// ```
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<_> = b.chars().map(|c| c.to_ascii_lowercase()).collect();
+    let mut row: Vec<_> = (0..b.len() + 1).collect();
+
+    for (i, ca) in a.chars().map(|c| c.to_ascii_lowercase()).enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+
+        for j in 0..b.len() {
+            let cur = row[j + 1];
+            row[j + 1] = if ca == b[j] {
+                prev
+            } else {
+                1 + min(prev, min(row[j], row[j + 1]))
+            };
+            prev = cur;
+        }
+    }
+
+    row[b.len()]
+}
+
+/// Find the specified candidate closest to the specified string, if any is close enough to be a likely typo.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::util::closest_match;
+/// let users = vec!["nabijaczleweli".to_string(), "danerangLP".to_string()];
+/// assert_eq!(closest_match("nabijaczlewli", &users), Some("nabijaczleweli"));
+/// assert_eq!(closest_match("daneranglp", &users), Some("danerangLP"));
+/// assert_eq!(closest_match("someone_else", &users), None);
+/// ```
+pub fn closest_match<'c>(what: &str, candidates: &'c Vec<String>) -> Option<&'c str> {
+    candidates.iter()
+        .map(|c| (levenshtein(what, c), c))
+        .filter(|&(dist, c)| dist <= max(2, c.len() / 3))
+        .min_by_key(|&(dist, _)| dist)
+        .map(|(_, c)| &c[..])
+}
+
 /// Parse a relative datetime into a `Duration`.
 ///
 /// This has the form of
@@ -115,6 +173,136 @@ pub fn parse_relative_time(delta: &str) -> Result<Duration, ()> {
     }
 }
 
+/// Parse a datetime in RFC2822, RFC3339 or relative (see `parse_relative_time()`) format.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::util::parse_time;
+/// # use chrono::DateTime;
+/// # fn main() {
+/// assert_eq!(parse_time("Fri, 9 Sep 2016 00:33:30 +0200"), Ok(DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap()));
+/// assert_eq!(parse_time("2016-09-09T00:33:30+02:00"), Ok(DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap()));
+/// assert!(parse_time("in 2 days").is_ok());
+///
+/// assert!(parse_time("tomorrow").is_err());
+/// # }
+/// ```
+pub fn parse_time(s: &str) -> Result<DateTime<FixedOffset>, ()> {
+    DateTime::parse_from_rfc2822(s).or_else(|_| DateTime::parse_from_rfc3339(s)).or_else(|_| {
+        let delta = try!(parse_relative_time(s));
+
+        let now = Local::now();
+        let now = now.with_timezone(now.offset());
+        Ok(now + ChronoDuration::from_std(delta).unwrap())
+    })
+}
+
+/// Parse a signed amount of time to move something by into a `Duration`.
+///
+/// This has the form of [`+`|`-`]*n*[`s`|`m`|`h`|`d`|`w`], for seconds, minutes, hours, days and weeks respectively.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate tweetr;
+/// # extern crate chrono;
+/// # use tweetr::util::parse_shift;
+/// # use chrono::Duration;
+/// # fn main() {
+/// assert_eq!(parse_shift("+2h"), Ok(Duration::hours(2)));
+/// assert_eq!(parse_shift("-30m"), Ok(Duration::minutes(-30)));
+/// assert_eq!(parse_shift("+1d"), Ok(Duration::days(1)));
+/// assert_eq!(parse_shift("-1w"), Ok(Duration::weeks(-1)));
+/// assert_eq!(parse_shift("+45s"), Ok(Duration::seconds(45)));
+///
+/// assert!(parse_shift("2h").is_err());
+/// assert!(parse_shift("+2 hours").is_err());
+/// # }
+/// ```
+pub fn parse_shift(shift: &str) -> Result<ChronoDuration, ()> {
+    lazy_static! {
+        static ref SHIFT_REGEX: Regex = Regex::new(r"^([+-])(\d+)([smhdw])$").unwrap();
+    }
+
+    let capts = try!(SHIFT_REGEX.captures(shift).ok_or(()));
+    let n = try!(i64::from_str(capts.at(2).unwrap()).map_err(|_| ()));
+    let n = if capts.at(1).unwrap() == "-" { -n } else { n };
+    Ok(match capts.at(3).unwrap() {
+        "s" => ChronoDuration::seconds(n),
+        "m" => ChronoDuration::minutes(n),
+        "h" => ChronoDuration::hours(n),
+        "d" => ChronoDuration::days(n),
+        "w" => ChronoDuration::weeks(n),
+        _ => unreachable!(),
+    })
+}
+
+/// Get the length of the specified tweet content the way Twitter counts it.
+///
+/// URLs are shortened to t.co links, which are always 23 characters long, and characters outside Latin, punctuation and
+/// a few other ranges, e.g. CJK characters and emoji, count as two.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::util::weighted_tweet_length;
+/// assert_eq!(weighted_tweet_length("Capitalism"), 10);
+/// assert_eq!(weighted_tweet_length("Read this: https://github.com/nabijaczleweli/tweetr/blob/master/README.md"), 11 + 23);
+/// assert_eq!(weighted_tweet_length("資本主義"), 8);
+/// ```
+pub fn weighted_tweet_length(content: &str) -> usize {
+    static SINGLE_WEIGHT_RANGES: [(u32, u32); 4] = [(0, 4351), (8192, 8205), (8208, 8223), (8242, 8247)];
+
+    lazy_static! {
+        static ref URL_REGEX: Regex = Regex::new(r"https?://[^\s]+").unwrap();
+    }
+
+    let urls = URL_REGEX.find_iter(content).count();
+    URL_REGEX.replace_all(content, "")
+        .chars()
+        .map(|c| if SINGLE_WEIGHT_RANGES.iter().any(|&(from, to)| from <= c as u32 && c as u32 <= to) {
+            1
+        } else {
+            2
+        })
+        .sum::<usize>() + urls * 23
+}
+
+/// Decode a percent-encoded URL query component, also turning `+`es into spaces.
+///
+/// Returns `None` if an escape is invalid or the result isn't UTF-8.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::util::percent_decode;
+/// assert_eq!(percent_decode("lWkMiB9vG7sBbHS2"), Some("lWkMiB9vG7sBbHS2".to_string()));
+/// assert_eq!(percent_decode("a%2Fb%3D%3D+c"), Some("a/b== c".to_string()));
+/// assert_eq!(percent_decode("%C5%BC%C3%B3%C5%82w"), Some("żółw".to_string()));
+/// assert_eq!(percent_decode("100%"), None);
+/// ```
+pub fn percent_decode(s: &str) -> Option<String> {
+    let mut bytes = s.bytes();
+    let mut decoded = vec![];
+    while let Some(b) = bytes.next() {
+        decoded.push(match b {
+            b'%' => {
+                let hex: Vec<_> = bytes.by_ref().take(2).map(|h| (h as char).to_digit(16)).collect();
+                match (hex.get(0), hex.get(1)) {
+                    (Some(&Some(hi)), Some(&Some(lo))) => (hi * 16 + lo) as u8,
+                    _ => return None,
+                }
+            }
+            b'+' => b' ',
+            b => b,
+        });
+    }
+    String::from_utf8(decoded).ok()
+}
+
 /// Ask the user to input a string of the exact length of `desired_len`, (re)prompting as necessary.
 ///
 /// # Examples
@@ -265,6 +453,72 @@ pub fn prompt_any_len<R, W, F>(input: &mut R, output: &mut W, prompt_s: &str, ve
     }
 }
 
+/// Ask the user to input a string of any length, suggesting the specified candidates starting with it if it's entered with a
+/// trailing tab.
+///
+/// This isn't as-you-type completion: the input is only seen after Enter, so the user types `<prefix>`, Tab, then Enter.
+/// If exactly one candidate starts with the prefix (ignoring ASCII case) it's taken, otherwise all matching ones are listed
+/// and the user is reprompted, as they are if none do.
+///
+/// Will return `None` if the string is empty.
+///
+/// # Examples
+///
+/// ```
+/// # use std::io::Cursor;
+/// # use tweetr::util::prompt_suggesting;
+/// let users = vec!["nabijaczleweli".to_string(), "nabijaczleweli_bot".to_string(), "danerangLP".to_string()];
+/// assert_eq!(prompt_suggesting(&mut Cursor::new(b"dane\t\n"),
+///                              &mut Vec::new(),
+///                              "User",
+///                              &users).unwrap(),
+///            Some("danerangLP".to_string()));
+/// assert_eq!(prompt_suggesting(&mut Cursor::new(b"nabi\t\nnabijaczleweli_\t\n"),
+///                              &mut Vec::new(),
+///                              "User",
+///                              &users).unwrap(),
+///            Some("nabijaczleweli_bot".to_string()));
+/// assert_eq!(prompt_suggesting(&mut Cursor::new(b"someone_else\n"),
+///                              &mut Vec::new(),
+///                              "User",
+///                              &users).unwrap(),
+///            Some("someone_else".to_string()));
+/// ```
+pub fn prompt_suggesting<R, W>(input: &mut R, output: &mut W, prompt_s: &str, candidates: &Vec<String>) -> IoResult<Option<String>>
+    where R: BufRead,
+          W: Write
+{
+    let mut out = String::new();
+    loop {
+        try!(write!(output, "{}: ", prompt_s));
+        try!(output.flush());
+
+        out.clear();
+        try!(input.read_line(&mut out));
+        if !out.trim_matches(|c| c == '\r' || c == '\n').ends_with('\t') {
+            break;
+        }
+
+        let prefix = out.trim().to_ascii_lowercase();
+        let matching: Vec<_> = candidates.iter().filter(|c| c.to_ascii_lowercase().starts_with(&prefix)).collect();
+        match matching.len() {
+            0 => try!(writeln!(output, "Nothing starts with \"{}\".", out.trim())),
+            1 => {
+                try!(writeln!(output, "{}: {}", prompt_s, matching[0]));
+                return Ok(Some(matching[0].clone()));
+            }
+            _ => try!(writeln!(output, "{}", matching.iter().map(|c| &c[..]).collect::<Vec<_>>().join("  "))),
+        }
+    }
+
+    let out = out.trim();
+    if out.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(out.to_string()))
+    }
+}
+
 /// Ask the user to input a multiline string, (re)prompting as necessary.
 ///
 /// Each line is separated by a `\`, but can be escaped by `\\`, e.g.
@@ -352,6 +606,113 @@ pub fn prompt_multiline<R, W, F>(input: &mut R, output: &mut W, prompt_s: &str,
     Ok(buf)
 }
 
+/// Create or truncate the specified file for writing, making it accessible only to its owner.
+///
+/// The permissions of files that already exist are restricted as well, so rewriting them fixes ones loosened by hand.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::util::{create_private_file, file_mode};
+/// # use std::env::temp_dir;
+/// # use std::io::Write;
+/// # use std::fs;
+/// let tf = temp_dir().join("tweetr-doctest").join("util-create_private_file-0");
+/// fs::create_dir_all(&tf).unwrap();
+/// let tf = tf.join("secrets");
+/// let _ = fs::remove_file(&tf);
+///
+/// create_private_file(&tf).unwrap().write_all(b"hunter2").unwrap();
+/// if cfg!(unix) {
+///     assert_eq!(file_mode(&tf), Some(0o600));
+/// }
+/// ```
+pub fn create_private_file(p: &Path) -> IoResult<File> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    private_file_mode(&mut options);
+    let file = try!(options.open(p));
+    try!(restrict_file(&file));
+    Ok(file)
+}
+
+/// Create the specified directory and its parents, making the ones created accessible only to their owner.
+pub fn create_private_dir(p: &Path) -> IoResult<()> {
+    let mut builder = DirBuilder::new();
+    builder.recursive(true);
+    private_dir_mode(&mut builder);
+    builder.create(p)
+}
+
+/// Get the Unix permission bits of the specified file, or `None` if it doesn't exist or this isn't Unix.
+pub fn file_mode(p: &Path) -> Option<u32> {
+    fs::metadata(p).ok().and_then(|m| permissions_mode(&m.permissions()))
+}
+
+/// Run the specified shell command and get the first line of its output, e.g. a secret from a password manager.
+///
+/// Fails with a description of why if the command couldn't be run, exited unsuccessfully or printed nothing.
+///
+/// # Examples
+///
+/// ```
+/// # use tweetr::util::run_secret_command;
+/// assert_eq!(run_secret_command("echo hunter2"), Ok("hunter2".to_string()));
+/// assert!(run_secret_command("exit 1").is_err());
+/// ```
+pub fn run_secret_command(command: &str) -> Result<String, String> {
+    let output = if cfg!(windows) {
+        Command::new("cmd").arg("/C").arg(command).stdin(Stdio::inherit()).stderr(Stdio::inherit()).output()
+    } else {
+        Command::new("sh").arg("-c").arg(command).stdin(Stdio::inherit()).stderr(Stdio::inherit()).output()
+    };
+    let output = try!(output.map_err(|e| format!("couldn't run \"{}\": {}", command, e)));
+
+    if !output.status.success() {
+        return Err(format!("\"{}\" failed with {}", command, output.status));
+    }
+    match String::from_utf8_lossy(&output.stdout).lines().next().map(str::trim) {
+        Some(line) if !line.is_empty() => Ok(line.to_string()),
+        _ => Err(format!("\"{}\" printed nothing", command)),
+    }
+}
+
+#[cfg(unix)]
+fn private_file_mode(options: &mut OpenOptions) {
+    options.mode(0o600);
+}
+
+#[cfg(not(unix))]
+fn private_file_mode(_: &mut OpenOptions) {}
+
+#[cfg(unix)]
+fn restrict_file(file: &File) -> IoResult<()> {
+    file.set_permissions(Permissions::from_mode(0o600))
+}
+
+#[cfg(not(unix))]
+fn restrict_file(_: &File) -> IoResult<()> {
+    Ok(())
+}
+
+#[cfg(unix)]
+fn private_dir_mode(builder: &mut DirBuilder) {
+    builder.mode(0o700);
+}
+
+#[cfg(not(unix))]
+fn private_dir_mode(_: &mut DirBuilder) {}
+
+#[cfg(unix)]
+fn permissions_mode(permissions: &Permissions) -> Option<u32> {
+    Some(permissions.mode() & 0o777)
+}
+
+#[cfg(not(unix))]
+fn permissions_mode(_: &Permissions) -> Option<u32> {
+    None
+}
+
 fn prompt<R, W, F>(input: &mut R, output: &mut W, prompt_s: &str, verifier: &F, allow_empty: bool, colon: bool, out: &mut String) -> IoResult<()>
     where R: BufRead,
           W: Write,
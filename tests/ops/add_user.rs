@@ -0,0 +1,103 @@
+extern crate tweetr;
+
+use self::tweetr::options::Options;
+use self::tweetr::ops::add_user;
+use self::tweetr::Outcome;
+use std::env;
+
+
+static TOKEN: &'static str = "JJm6VwAAAAAAxW8MAAABV1QSjb4";
+
+
+#[test]
+fn verifier() {
+    assert_eq!(add_user::callback_verifier("GET /callback?oauth_token=JJm6VwAAAAAAxW8MAAABV1QSjb4&oauth_verifier=lWkMiB9vG7sBbHS2 HTTP/1.1",
+                                           TOKEN),
+               Some(Ok("lWkMiB9vG7sBbHS2".to_string())));
+    assert_eq!(add_user::callback_verifier("GET /callback?oauth_verifier=lWkMiB9vG7sBbHS2&oauth_token=JJm6VwAAAAAAxW8MAAABV1QSjb4 HTTP/1.1",
+                                           TOKEN),
+               Some(Ok("lWkMiB9vG7sBbHS2".to_string())));
+}
+
+#[test]
+fn denied() {
+    assert_eq!(add_user::callback_verifier("GET /callback?denied=JJm6VwAAAAAAxW8MAAABV1QSjb4 HTTP/1.1", TOKEN),
+               Some(Err(Outcome::TwitterAPIError("authorisation denied".to_string()))));
+}
+
+#[test]
+fn other_token() {
+    assert_eq!(add_user::callback_verifier("GET /callback?oauth_token=7588892AAAAAAxW8MAAABV1QSjb4&oauth_verifier=lWkMiB9vG7sBbHS2 HTTP/1.1",
+                                           TOKEN),
+               None);
+    assert_eq!(add_user::callback_verifier("GET /callback?denied=7588892AAAAAAxW8MAAABV1QSjb4 HTTP/1.1", TOKEN), None);
+}
+
+#[test]
+fn unrelated() {
+    assert_eq!(add_user::callback_verifier("GET /favicon.ico HTTP/1.1", TOKEN), None);
+    assert_eq!(add_user::callback_verifier("POST /callback?oauth_token=JJm6VwAAAAAAxW8MAAABV1QSjb4&oauth_verifier=lWkMiB9vG7sBbHS2 HTTP/1.1",
+                                           TOKEN),
+               None);
+    assert_eq!(add_user::callback_verifier("GET /callback?oauth_token=JJm6VwAAAAAAxW8MAAABV1QSjb4 HTTP/1.1", TOKEN), None);
+    assert_eq!(add_user::callback_verifier("", TOKEN), None);
+}
+
+#[test]
+fn token_from_json() {
+    assert_eq!(add_user::token_from_json(&mut &br#"{"key": "529443-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4",
+                                                    "secret": "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5",
+                                                    "screen_name": "nabijaczleweli"}"#[..]),
+               Ok(("529443-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4".to_string(), "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5".to_string())));
+}
+
+#[test]
+fn token_from_json_malformed() {
+    for json in &[&b""[..], &b"529443-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4"[..], &br#"{"key": "529443-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4","#[..],
+                  &br#"["529443-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4", "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5"]"#[..]] {
+        match add_user::token_from_json(&mut &json[..]) {
+            Err(Outcome::FileParsingFailed { desc, errors }) => {
+                assert_eq!(desc, "access token JSON");
+                assert_eq!(errors.len(), 1);
+            }
+            res => panic!("{:?} parsed to {:?}", String::from_utf8_lossy(json), res),
+        }
+    }
+}
+
+#[test]
+fn token_from_json_missing_fields() {
+    for json in &[&br#"{}"#[..],
+                  &br#"{"key": "529443-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4"}"#[..],
+                  &br#"{"secret": "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5"}"#[..],
+                  &br#"{"key": 529443, "secret": "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5"}"#[..]] {
+        match add_user::token_from_json(&mut &json[..]) {
+            Err(Outcome::FileParsingFailed { desc, .. }) => assert_eq!(desc, "access token JSON"),
+            res => panic!("{:?} parsed to {:?}", String::from_utf8_lossy(json), res),
+        }
+    }
+}
+
+#[test]
+fn import_token_precedence() {
+    env::set_var("TWEETR_TEST_ADD_USER_KEY", "40423221609-Y0klmK9nWNRAScBuumWvAtSOzmIvBIBLJpc3Ept");
+    env::set_var("TWEETR_TEST_ADD_USER_SECRET", "zFYbEO5wQtST3eK84pGuzSmmEByZbQ0EVY8uAS4BCM1mx");
+
+    assert_eq!(Options::token_pair(Some(("529443-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4", "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5")),
+                                   true,
+                                   "TWEETR_TEST_ADD_USER_KEY",
+                                   "TWEETR_TEST_ADD_USER_SECRET"),
+               Some(("529443-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4".to_string(), "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5".to_string())));
+    assert_eq!(Options::token_pair(None, true, "TWEETR_TEST_ADD_USER_KEY", "TWEETR_TEST_ADD_USER_SECRET"),
+               Some(("40423221609-Y0klmK9nWNRAScBuumWvAtSOzmIvBIBLJpc3Ept".to_string(), "zFYbEO5wQtST3eK84pGuzSmmEByZbQ0EVY8uAS4BCM1mx".to_string())));
+    // --import-json or --callback
+    assert_eq!(Options::token_pair(None, false, "TWEETR_TEST_ADD_USER_KEY", "TWEETR_TEST_ADD_USER_SECRET"), None);
+}
+
+#[test]
+fn import_token_partial_env() {
+    env::set_var("TWEETR_TEST_ADD_USER_PARTIAL_KEY", "40423221609-Y0klmK9nWNRAScBuumWvAtSOzmIvBIBLJpc3Ept");
+    env::remove_var("TWEETR_TEST_ADD_USER_PARTIAL_SECRET");
+
+    assert_eq!(Options::token_pair(None, true, "TWEETR_TEST_ADD_USER_PARTIAL_KEY", "TWEETR_TEST_ADD_USER_PARTIAL_SECRET"), None);
+}
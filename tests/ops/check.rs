@@ -0,0 +1,63 @@
+extern crate tweetr;
+extern crate chrono;
+
+use self::tweetr::ops::{QueuedTweet, TweetAction, TweetTarget, check};
+use self::chrono::{DateTime, FixedOffset};
+use self::tweetr::Outcome;
+
+
+#[test]
+fn valid() {
+    let tweets = vec![(Some(1), Ok(tweet("nabijaczleweli", "Capitalism"))), (Some(6), Ok(tweet("nabijaczleweli", "Communism")))];
+    assert_eq!(check::check_tweets(&tweets, Some(&vec!["nabijaczleweli".to_string()]), &vec![], now()), Ok(()));
+}
+
+#[test]
+fn unparseable() {
+    let tweets = vec![(Some(1), Err("time \"tomorrow\" isn't a valid RFC3339 time".to_string()))];
+    assert_eq!(check::check_tweets(&tweets, None, &vec![], now()),
+               Err(Outcome::InvalidTweets(vec!["line 1: time \"tomorrow\" isn't a valid RFC3339 time".to_string()])));
+}
+
+#[test]
+fn unknown_author() {
+    let tweets = vec![(Some(1), Ok(tweet("nabijaczIeweli", "Capitalism")))];
+    assert_eq!(check::check_tweets(&tweets, Some(&vec!["nabijaczleweli".to_string()]), &vec![], now()),
+               Err(Outcome::InvalidTweets(vec!["line 1: tweet \"Capitalism\" scheduled for 2016-09-09T00:33:30+02:00 by nabijaczIeweli: \
+                                                unknown author \"nabijaczIeweli\", did you mean \"nabijaczleweli\"?"
+                                                   .to_string()])));
+}
+
+#[test]
+fn duplicate_in_file() {
+    let tweets = vec![(None, Ok(tweet("nabijaczleweli", "Capitalism"))), (None, Ok(tweet("NABIJACZLEWELI", "Capitalism")))];
+    assert_eq!(check::check_tweets(&tweets, None, &vec![], now()),
+               Err(Outcome::InvalidTweets(vec!["tweet 2: tweet \"Capitalism\" scheduled for 2016-09-09T00:33:30+02:00 by NABIJACZLEWELI: \
+                                                duplicate of a previous tweet in the file"
+                                                   .to_string()])));
+}
+
+#[test]
+fn different_action_not_duplicate() {
+    let mut quote = tweet("nabijaczleweli", "Capitalism");
+    quote.action = TweetAction::Quote(TweetTarget::Id(773264221226950656));
+    assert_eq!(check::check_tweets(&vec![(Some(1), Ok(quote))], None, &vec![tweet("nabijaczleweli", "Capitalism")], now()), Ok(()));
+}
+
+#[test]
+fn past() {
+    let tweets = vec![(Some(1), Ok(tweet("nabijaczleweli", "Capitalism")))];
+    assert_eq!(check::check_tweets(&tweets, None, &vec![], DateTime::parse_from_rfc3339("2016-09-10T00:33:30+02:00").unwrap()),
+               Err(Outcome::InvalidTweets(vec!["line 1: tweet \"Capitalism\" scheduled for 2016-09-09T00:33:30+02:00 by nabijaczleweli: \
+                                                scheduled in the past"
+                                                   .to_string()])));
+}
+
+
+fn now() -> DateTime<FixedOffset> {
+    DateTime::parse_from_rfc3339("2016-09-08T00:33:30+02:00").unwrap()
+}
+
+fn tweet(author: &str, content: &str) -> QueuedTweet {
+    QueuedTweet::new(author, DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap(), content)
+}
@@ -0,0 +1,87 @@
+extern crate tweetr;
+extern crate chrono;
+
+use self::tweetr::ops::doctor::{self, Finding, Severity};
+use self::chrono::{DateTime, FixedOffset};
+use self::tweetr::ops::QueuedTweet;
+use std::fs::{self, File};
+use std::env::temp_dir;
+use std::path::PathBuf;
+use std::io::Write;
+
+
+#[test]
+fn healthy() {
+    let config_dir = scaffold("healthy");
+    QueuedTweet::write(vec![tweet("nabijaczleweli", "Capitalism", None)], &config_dir.1.join("tweets.toml"));
+
+    let findings = doctor::check_tweets(&config_dir, Some(&vec!["nabijaczleweli".to_string()]), now());
+    assert_eq!(findings, vec![Finding::new(Severity::Ok, "tweets.toml", "1 tweets, 1 unposted")]);
+}
+
+#[test]
+fn unknown_author() {
+    let config_dir = scaffold("unknown_author");
+    QueuedTweet::write(vec![tweet("nabijaczleweli", "Capitalism", None)], &config_dir.1.join("tweets.toml"));
+
+    let findings = doctor::check_tweets(&config_dir, Some(&vec!["danerangLP".to_string()]), now());
+    assert_eq!(findings.len(), 2);
+    assert_eq!(findings[1].severity, Severity::Error);
+    assert_eq!(findings[1].details.len(), 1);
+}
+
+#[test]
+fn too_long() {
+    let config_dir = scaffold("too_long");
+    QueuedTweet::write(vec![tweet("nabijaczleweli", &tweetr::util::mul_str("Capitalism ", 30), None)],
+                       &config_dir.1.join("tweets.toml"));
+
+    let findings = doctor::check_tweets(&config_dir, None, now());
+    assert_eq!(findings.len(), 2);
+    assert_eq!(findings[1].severity, Severity::Error);
+}
+
+#[test]
+fn overdue() {
+    let config_dir = scaffold("overdue");
+    QueuedTweet::write(vec![tweet("nabijaczleweli", "Capitalism", None), tweet("nabijaczleweli", "Communism", Some(0x2A))],
+                       &config_dir.1.join("tweets.toml"));
+
+    let findings = doctor::check_tweets(&config_dir, None, DateTime::parse_from_rfc3339("2016-09-10T00:33:30+02:00").unwrap());
+    assert_eq!(findings.len(), 2);
+    assert_eq!(findings[1].severity, Severity::Warning);
+    assert_eq!(findings[1].details,
+               vec!["\"Capitalism\" scheduled for 2016-09-09T00:33:30+02:00 by nabijaczleweli".to_string()]);
+}
+
+#[test]
+fn unparseable() {
+    let config_dir = scaffold("unparseable");
+    File::create(config_dir.1.join("tweets.toml")).unwrap().write_all(b"[[tweet]]\nauthor = 1\n").unwrap();
+
+    let findings = doctor::check_tweets(&config_dir, None, now());
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0].severity, Severity::Error);
+    assert_eq!(doctor::outcome(&findings), tweetr::Outcome::ConfigurationProblems(1));
+}
+
+
+fn now() -> DateTime<FixedOffset> {
+    DateTime::parse_from_rfc3339("2016-09-08T00:33:30+02:00").unwrap()
+}
+
+fn scaffold(name: &str) -> (String, PathBuf) {
+    let td = temp_dir().join("tweetr-test").join(format!("ops-doctor-{}", name));
+    let _ = fs::remove_dir_all(&td);
+    fs::create_dir_all(&td).unwrap();
+
+    (format!("$TEMP/ops-doctor-{}", name), td)
+}
+
+fn tweet(author: &str, content: &str, id: Option<i64>) -> QueuedTweet {
+    QueuedTweet {
+        time_posted: id.map(|_| DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap()),
+        id: id,
+        ..QueuedTweet::new(author, DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap(), content)
+    }
+}
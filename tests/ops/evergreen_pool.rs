@@ -0,0 +1,54 @@
+extern crate tweetr;
+extern crate chrono;
+
+use self::tweetr::ops::{EvergreenSelection, EvergreenTweet, EvergreenPool};
+use self::chrono::DateTime;
+use std::env::temp_dir;
+use std::fs;
+
+
+#[test]
+fn empty_trans_eq() {
+    trans_scaffold("empty_trans_eq", vec![]);
+}
+
+#[test]
+fn single_trans_eq() {
+    trans_scaffold("single_trans_eq", vec![pool(EvergreenSelection::Random)]);
+}
+
+#[test]
+fn multi_trans_eq() {
+    trans_scaffold("multi_trans_eq", vec![pool(EvergreenSelection::Random), pool(EvergreenSelection::RoundRobin)]);
+}
+
+
+fn trans_scaffold(name: &str, pools: Vec<EvergreenPool>) {
+    let td = temp_dir().join("tweetr-test").join(format!("ops-evergreen_pool-{}", name));
+    fs::create_dir_all(&td).unwrap();
+
+    let tf = td.join("evergreen.toml");
+    let _ = fs::remove_file(&tf);
+
+    EvergreenPool::write(pools.clone(), &tf);
+    let read_pools = EvergreenPool::read(&tf).unwrap();
+
+    assert_eq!(pools, read_pools);
+}
+
+fn pool(selection: EvergreenSelection) -> EvergreenPool {
+    EvergreenPool {
+        author: "nabijaczleweli".to_string(),
+        min_days_between_repeats: 14,
+        selection: selection,
+        max_per_day: 3,
+        tweets: vec![EvergreenTweet {
+                         content: "This evergreen tweet was never posted".to_string(),
+                         last_posted: None,
+                     },
+                     EvergreenTweet {
+                         content: "This evergreen tweet was posted ages ago".to_string(),
+                         last_posted: Some(DateTime::parse_from_rfc2822("Sat, 1 Jul 2000 15:12:57 -0800").unwrap()),
+                     }],
+    }
+}
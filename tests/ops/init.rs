@@ -0,0 +1,69 @@
+extern crate tweetr;
+
+use self::tweetr::ops::{AppTokens, init};
+use self::tweetr::options::Options;
+use self::tweetr::Outcome;
+use std::env;
+
+
+#[test]
+fn data_from_json() {
+    assert_eq!(init::data_from_json(&mut &br#"{"key": "qdPD7N8CcPYDKiNv81QWNWaHK",
+                                               "secret": "U9A5CM1LzwNliBiHGPIJyx6tFYAGVr3bCMbVkWKu8Zb13kHD4p"}"#[..]),
+               Ok(AppTokens {
+                   key: "qdPD7N8CcPYDKiNv81QWNWaHK".to_string(),
+                   secret: "U9A5CM1LzwNliBiHGPIJyx6tFYAGVr3bCMbVkWKu8Zb13kHD4p".to_string(),
+                   secret_command: None,
+               }));
+    assert_eq!(init::data_from_json(&mut &br#"{"key": "qdPD7N8CcPYDKiNv81QWNWaHK",
+                                               "secret": "U9A5CM1LzwNliBiHGPIJyx6tFYAGVr3bCMbVkWKu8Zb13kHD4p",
+                                               "secret_command": "pass show tweetr"}"#[..]),
+               Ok(AppTokens {
+                   key: "qdPD7N8CcPYDKiNv81QWNWaHK".to_string(),
+                   secret: "U9A5CM1LzwNliBiHGPIJyx6tFYAGVr3bCMbVkWKu8Zb13kHD4p".to_string(),
+                   secret_command: Some("pass show tweetr".to_string()),
+               }));
+}
+
+#[test]
+fn data_from_json_malformed() {
+    for json in &[&b""[..], &b"qdPD7N8CcPYDKiNv81QWNWaHK"[..], &br#"{"key": "qdPD7N8CcPYDKiNv81QWNWaHK","#[..],
+                  &br#"["qdPD7N8CcPYDKiNv81QWNWaHK", "U9A5CM1LzwNliBiHGPIJyx6tFYAGVr3bCMbVkWKu8Zb13kHD4p"]"#[..]] {
+        match init::data_from_json(&mut &json[..]) {
+            Err(Outcome::FileParsingFailed { desc, errors }) => {
+                assert_eq!(desc, "application tokens JSON");
+                assert_eq!(errors.len(), 1);
+            }
+            res => panic!("{:?} parsed to {:?}", String::from_utf8_lossy(json), res),
+        }
+    }
+}
+
+#[test]
+fn data_from_json_missing_fields() {
+    for json in &[&br#"{}"#[..],
+                  &br#"{"key": "qdPD7N8CcPYDKiNv81QWNWaHK"}"#[..],
+                  &br#"{"secret": "U9A5CM1LzwNliBiHGPIJyx6tFYAGVr3bCMbVkWKu8Zb13kHD4p"}"#[..],
+                  &br#"{"key": "qdPD7N8CcPYDKiNv81QWNWaHK", "secret": null}"#[..]] {
+        match init::data_from_json(&mut &json[..]) {
+            Err(Outcome::FileParsingFailed { desc, .. }) => assert_eq!(desc, "application tokens JSON"),
+            res => panic!("{:?} parsed to {:?}", String::from_utf8_lossy(json), res),
+        }
+    }
+}
+
+#[test]
+fn tokens_precedence() {
+    env::set_var("TWEETR_TEST_INIT_KEY", "GeVFiYk7q8DhUmgMXE0iODrFa");
+    env::set_var("TWEETR_TEST_INIT_SECRET", "bH3VIvYEwwVmMXkTnXB8N3HEQf4ShOf2Z4e1dkaqSJNGorK2pe");
+
+    assert_eq!(Options::token_pair(Some(("qdPD7N8CcPYDKiNv81QWNWaHK", "U9A5CM1LzwNliBiHGPIJyx6tFYAGVr3bCMbVkWKu8Zb13kHD4p")),
+                                   true,
+                                   "TWEETR_TEST_INIT_KEY",
+                                   "TWEETR_TEST_INIT_SECRET"),
+               Some(("qdPD7N8CcPYDKiNv81QWNWaHK".to_string(), "U9A5CM1LzwNliBiHGPIJyx6tFYAGVr3bCMbVkWKu8Zb13kHD4p".to_string())));
+    assert_eq!(Options::token_pair(None, true, "TWEETR_TEST_INIT_KEY", "TWEETR_TEST_INIT_SECRET"),
+               Some(("GeVFiYk7q8DhUmgMXE0iODrFa".to_string(), "bH3VIvYEwwVmMXkTnXB8N3HEQf4ShOf2Z4e1dkaqSJNGorK2pe".to_string())));
+    // --json
+    assert_eq!(Options::token_pair(None, false, "TWEETR_TEST_INIT_KEY", "TWEETR_TEST_INIT_SECRET"), None);
+}
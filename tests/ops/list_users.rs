@@ -0,0 +1,71 @@
+extern crate tweetr;
+extern crate chrono;
+
+use self::tweetr::ops::{Suspension, UserGroup, User, list_users};
+use std::iter::FromIterator;
+use self::chrono::DateTime;
+
+
+#[test]
+fn no_users() {
+    assert_eq!(print(&vec![], &vec![]), "No users added.\n");
+}
+
+#[test]
+fn plain() {
+    assert_eq!(print(&vec![user("nabijaczleweli", 0x81423), user("danerangLP", 0x2A)], &vec![]),
+               "nabijaczleweli#529443\ndanerangLP#42\n");
+}
+
+#[test]
+fn limits() {
+    let users = vec![User {
+                         app: Some("work".to_string()),
+                         min_minutes_between_posts: Some(30),
+                         max_posts_per_day: Some(12),
+                         jitter_minutes: Some(5),
+                         ..user("nabijaczleweli", 0x81423)
+                     }];
+    assert_eq!(print(&users, &vec![]),
+               "nabijaczleweli#529443 (app work, at least 30 minutes between posts, at most 12 posts per day, 5 minutes of jitter)\n");
+}
+
+#[test]
+fn suspended() {
+    let users = vec![User {
+                         suspended: Some(Suspension {
+                             reason: "Invalid or expired token.".to_string(),
+                             time: DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap(),
+                         }),
+                         ..user("nabijaczleweli", 0x81423)
+                     }];
+    assert_eq!(print(&users, &vec![]),
+               "nabijaczleweli#529443\n  Suspended at 2016-09-09T00:33:30+02:00: Invalid or expired token.\n");
+}
+
+#[test]
+fn groups() {
+    let groups = vec![UserGroup {
+                          name: "everyone".to_string(),
+                          members: vec!["nabijaczleweli".to_string(), "danerangLP".to_string()],
+                          stagger_minutes: None,
+                      },
+                      UserGroup {
+                          name: "staggered".to_string(),
+                          members: vec!["danerangLP".to_string()],
+                          stagger_minutes: Some(10),
+                      }];
+    assert_eq!(print(&vec![user("nabijaczleweli", 0x81423), user("danerangLP", 0x2A)], &groups),
+               "nabijaczleweli#529443\ndanerangLP#42\nGroup everyone: nabijaczleweli, danerangLP\nGroup staggered: danerangLP (10 minutes apart)\n");
+}
+
+
+fn print(users: &Vec<User>, groups: &Vec<UserGroup>) -> String {
+    let mut out = vec![];
+    list_users::print_users(&mut out, users, groups);
+    String::from_iter(out.iter().map(|&i| i as char))
+}
+
+fn user(name: &str, id: i64) -> User {
+    User::new(name, id, "529443-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4", "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5")
+}
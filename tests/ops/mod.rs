@@ -1,4 +1,19 @@
 mod queued_tweet;
+mod evergreen_pool;
 mod queue_tweet;
+mod init;
+mod add_user;
+mod refresh_users;
+mod remove_user;
+mod reschedule;
+mod respread;
+mod start_daemon;
+mod doctor;
+mod check;
+mod rekey;
+mod secret_key;
+mod reauth_user;
+mod verify_users;
+mod list_users;
 mod token;
 mod user;
@@ -12,18 +12,215 @@ mod get_tweet {
         assert_eq!(queue_tweet::get_tweet(&mut BufReader::new(b"tweetr_test\n\
                                                                 Test tweet\n\
                                                                 Fri, 9 Sep 2016 00:33:30 +0200\n" as &[u8]),
-                                          &mut Vec::new()),
-                   Some(QueuedTweet {
-                       author: "tweetr_test".to_string(),
-                       time: DateTime::parse_from_rfc2822("Fri, 9 Sep 2016 00:33:30 +0200").unwrap(),
-                       content: "Test tweet".to_string(),
-                       time_posted: None,
-                       id: None,
-                   }));
+                                          &mut Vec::new(),
+                                          None),
+                   Some(QueuedTweet::new("tweetr_test", DateTime::parse_from_rfc2822("Fri, 9 Sep 2016 00:33:30 +0200").unwrap(), "Test tweet")));
     }
 
     #[test]
     fn empty() {
-        assert_eq!(queue_tweet::get_tweet(&mut BufReader::new(b"" as &[u8]), &mut Vec::new()), None);
+        assert_eq!(queue_tweet::get_tweet(&mut BufReader::new(b"" as &[u8]), &mut Vec::new(), None), None);
+    }
+}
+
+mod apply_jitter {
+    extern crate tweetr;
+    extern crate chrono;
+
+    use self::tweetr::ops::{QueuedTweet, User, queue_tweet};
+    use self::chrono::{DateTime, Duration};
+
+
+    #[test]
+    fn none() {
+        let mut tweet = tweet(None, None);
+        queue_tweet::apply_jitter(&mut tweet, &vec![user(None)]);
+        assert_eq!(tweet, self::tweet(None, None));
+    }
+
+    #[test]
+    fn author() {
+        let mut tweet = tweet(None, None);
+        queue_tweet::apply_jitter(&mut tweet, &vec![user(Some(7))]);
+
+        let mut tweet_jitter = self::tweet(Some(7), None);
+        queue_tweet::apply_jitter(&mut tweet_jitter, &vec![]);
+        assert_eq!(tweet.time, tweet_jitter.time);
+    }
+
+    #[test]
+    fn tweet_overrides_author() {
+        let mut tweet = tweet(Some(0), None);
+        queue_tweet::apply_jitter(&mut tweet, &vec![user(Some(7))]);
+        assert_eq!(tweet, self::tweet(Some(0), None));
+    }
+
+    #[test]
+    fn not_past_post_by() {
+        let time = DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap();
+        for i in 0..100 {
+            let mut tweet = tweet(Some(60), Some(time));
+            tweet.content = format!("Test tweet #{}", i);
+            queue_tweet::apply_jitter(&mut tweet, &vec![]);
+
+            assert!(tweet.time <= time);
+            assert!(tweet.time >= time - Duration::minutes(60));
+        }
+    }
+
+    #[test]
+    fn not_past_expires() {
+        let time = DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap();
+        for i in 0..100 {
+            let mut tweet = tweet(Some(60), None);
+            tweet.content = format!("Test tweet #{}", i);
+            tweet.expires = Some(time);
+            queue_tweet::apply_jitter(&mut tweet, &vec![]);
+
+            assert!(tweet.time <= time);
+        }
+    }
+
+    #[test]
+    fn deadline_in_range() {
+        let time = DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap();
+        for i in 0..100 {
+            let mut tweet = tweet(Some(60), Some(time + Duration::minutes(10)));
+            tweet.content = format!("Test tweet #{}", i);
+            tweet.expires = Some(time + Duration::minutes(20));
+            queue_tweet::apply_jitter(&mut tweet, &vec![]);
+
+            assert!(tweet.time < time + Duration::minutes(10));
+            assert!(tweet.time >= time - Duration::minutes(60));
+        }
+    }
+
+
+    fn tweet(jitter: Option<u64>, post_by: Option<DateTime<self::chrono::FixedOffset>>) -> QueuedTweet {
+        QueuedTweet {
+            post_by: post_by,
+            jitter_minutes: jitter,
+            ..QueuedTweet::new("tweetr_test", DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap(), "Test tweet")
+        }
+    }
+
+    fn user(jitter: Option<u64>) -> User {
+        User {
+            jitter_minutes: jitter,
+            ..User::new("tweetr_test", 481, "481-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4", "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5")
+        }
+    }
+}
+
+mod validate_tweets {
+    extern crate tweetr;
+    extern crate chrono;
+
+    use self::tweetr::ops::{QueuedTweet, TweetAction, TweetTarget, Poll, queue_tweet};
+    use self::tweetr::util::mul_str;
+    use self::chrono::DateTime;
+
+
+    #[test]
+    fn no_poll() {
+        assert!(queue_tweet::validate_tweets(&vec![tweet(None)], None).is_ok());
+    }
+
+    #[test]
+    fn valid_poll() {
+        assert!(queue_tweet::validate_tweets(&vec![tweet(Some(poll(vec!["Tabs", "Spaces"], 60)))], None).is_ok());
+        assert!(queue_tweet::validate_tweets(&vec![tweet(Some(poll(vec!["1", "2", "3", "Twenty-five characters!!!"], 60 * 24 * 7)))], None).is_ok());
+    }
+
+    #[test]
+    fn option_count() {
+        assert!(queue_tweet::validate_tweets(&vec![tweet(Some(poll(vec!["Tabs"], 60)))], None).is_err());
+        assert!(queue_tweet::validate_tweets(&vec![tweet(Some(poll(vec!["1", "2", "3", "4", "5"], 60)))], None).is_err());
+    }
+
+    #[test]
+    fn option_length() {
+        assert!(queue_tweet::validate_tweets(&vec![tweet(Some(poll(vec!["Tabs", "Twenty-six characters!!!!!"], 60)))], None).is_err());
+        assert!(queue_tweet::validate_tweets(&vec![tweet(Some(poll(vec!["Tabs", " "], 60)))], None).is_err());
+    }
+
+    #[test]
+    fn duration() {
+        assert!(queue_tweet::validate_tweets(&vec![tweet(Some(poll(vec!["Tabs", "Spaces"], 4)))], None).is_err());
+        assert!(queue_tweet::validate_tweets(&vec![tweet(Some(poll(vec!["Tabs", "Spaces"], 60 * 24 * 7 + 1)))], None).is_err());
+    }
+
+    #[test]
+    fn wrong_action() {
+        let mut tweet = tweet(Some(poll(vec!["Tabs", "Spaces"], 60)));
+        tweet.action = TweetAction::Retweet(TweetTarget::Id(773264221226950656));
+        assert!(queue_tweet::validate_tweets(&vec![tweet], None).is_err());
+    }
+
+    #[test]
+    fn known_author() {
+        assert!(queue_tweet::validate_tweets(&vec![tweet(None)], Some(&vec!["nabijaczleweli".to_string(), "tweetr_test".to_string()])).is_ok());
+        assert!(queue_tweet::validate_tweets(&vec![tweet(None)], Some(&vec!["TweetR_Test".to_string()])).is_ok());
+    }
+
+    #[test]
+    fn unknown_author() {
+        match queue_tweet::validate_tweets(&vec![tweet(None)], Some(&vec!["tweetr_tset".to_string()])) {
+            Err(tweetr::Outcome::InvalidTweets(errors)) => {
+                assert_eq!(errors.len(), 1);
+                assert!(errors[0].ends_with("unknown author \"tweetr_test\", did you mean \"tweetr_tset\"?"));
+            }
+            res => panic!("{:?}", res),
+        }
+
+        match queue_tweet::validate_tweets(&vec![tweet(None)], Some(&vec![])) {
+            Err(tweetr::Outcome::InvalidTweets(errors)) => assert!(errors[0].ends_with("unknown author \"tweetr_test\"")),
+            res => panic!("{:?}", res),
+        }
+    }
+
+    #[test]
+    fn long_urls() {
+        let mut tweet = tweet(None);
+        tweet.content = mul_str("https://github.com/nabijaczleweli/tweetr/blob/master/README.md ", 11);
+        assert!(queue_tweet::validate_tweets(&vec![tweet], None).is_ok());
+    }
+
+    #[test]
+    fn wide_characters() {
+        let mut tweet = tweet(None);
+        tweet.content = mul_str("資本主義", 35);
+        assert!(queue_tweet::validate_tweets(&vec![tweet.clone()], None).is_ok());
+
+        tweet.content.push('!');
+        match queue_tweet::validate_tweets(&vec![tweet], None) {
+            Err(tweetr::Outcome::InvalidTweets(errors)) => {
+                assert!(errors[0].ends_with("tweet is 281 characters long as counted by Twitter, over the limit of 280"))
+            }
+            res => panic!("{:?}", res),
+        }
+    }
+
+    #[test]
+    fn all_errors() {
+        match queue_tweet::validate_tweets(&vec![tweet(Some(poll(vec!["Tabs"], 60))), tweet(None), tweet(Some(poll(vec!["Tabs", "Spaces"], 4)))], None) {
+            Err(tweetr::Outcome::InvalidTweets(errors)) => assert_eq!(errors.len(), 2),
+            res => panic!("{:?}", res),
+        }
+    }
+
+
+    fn poll(options: Vec<&str>, duration_minutes: u64) -> Poll {
+        Poll {
+            options: options.into_iter().map(str::to_string).collect(),
+            duration_minutes: duration_minutes,
+        }
+    }
+
+    fn tweet(poll: Option<Poll>) -> QueuedTweet {
+        QueuedTweet {
+            poll: poll,
+            ..QueuedTweet::new("tweetr_test", DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap(), "Tabs or spaces?")
+        }
     }
 }
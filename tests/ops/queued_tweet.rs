@@ -1,10 +1,11 @@
 extern crate tweetr;
 extern crate chrono;
 
-use self::chrono::{DateTime, Local};
-use self::tweetr::ops::QueuedTweet;
+use self::tweetr::ops::{QueuedTweet, TweetAction, TweetTarget, ProfileUpdate, Recipient, Poll};
+use self::chrono::{DateTime, Duration, Local};
+use std::fs::{self, File};
 use std::env::temp_dir;
-use std::fs;
+use std::io::Write;
 
 
 #[test]
@@ -22,11 +23,176 @@ fn posted_trans_eq() {
     trans_scaffold("posted_trans_eq", vec![posted()]);
 }
 
+#[test]
+fn dropped_trans_eq() {
+    trans_scaffold("dropped_trans_eq", vec![dropped()]);
+}
+
+#[test]
+fn deleted_trans_eq() {
+    trans_scaffold("deleted_trans_eq", vec![deleted()]);
+}
+
+#[test]
+fn actions_trans_eq() {
+    trans_scaffold("actions_trans_eq", vec![retweet(), quote(), pin(), profile_update(), direct_message()]);
+}
+
+#[test]
+fn poll_trans_eq() {
+    trans_scaffold("poll_trans_eq", vec![poll()]);
+}
+
+#[test]
+fn anchored_trans_eq() {
+    trans_scaffold("anchored_trans_eq", vec![unposted(), anchored()]);
+}
+
 #[test]
 fn mixed_trans_eq() {
-    trans_scaffold("mixed_trans_eq", vec![unposted(), posted()]);
+    trans_scaffold("mixed_trans_eq", vec![unposted(), posted(), dropped(), anchored(), deleted(), retweet(), quote(), pin(), profile_update(), direct_message(), poll()]);
+}
+
+#[test]
+fn read_with_lines_spaced_headers() {
+    let lines = lines_scaffold("read_with_lines_spaced_headers",
+                               "[[tweet]]\nauthor = \"nabijaczleweli\"\ntime = \"2016-09-09T00:33:30+02:00\"\n\n  [[ tweet ]] # second\nauthor = \
+                                \"nabijaczleweli\"\ntime = \"2016-09-09T00:33:30+02:00\"\n");
+    assert_eq!(lines, vec![Some(1), Some(5)]);
+}
+
+#[test]
+fn read_with_lines_multiline_content() {
+    let lines = lines_scaffold("read_with_lines_multiline_content",
+                               "[[tweet]]\nauthor = \"nabijaczleweli\"\ntime = \"2016-09-09T00:33:30+02:00\"\ncontent = \"\"\"\nHow to queue:\n[[tweet]]\n\"\"\"\n\n\
+                                [[tweet]]\nauthor = \"nabijaczleweli\"\ntime = \"2016-09-09T00:33:30+02:00\"\n");
+    assert_eq!(lines, vec![Some(1), Some(9)]);
+}
+
+#[test]
+fn read_with_lines_inline_array() {
+    let lines = lines_scaffold("read_with_lines_inline_array",
+                               "tweet = [{ author = \"nabijaczleweli\", time = \"2016-09-09T00:33:30+02:00\" },\n         \
+                                { author = \"nabijaczleweli\", time = \"2016-09-09T00:33:30+02:00\" }]\n");
+    assert_eq!(lines, vec![None, None]);
+}
+
+#[test]
+fn resolve_anchors_unposted() {
+    let mut tweets = vec![anchored(), unposted()];
+    QueuedTweet::resolve_anchors(&mut tweets, &mut vec![]);
+    assert_eq!(tweets[0].time, unposted().time + Duration::minutes(45));
+}
+
+#[test]
+fn resolve_anchors_posted() {
+    let mut anchor = unposted();
+    anchor.time_posted = Some(anchor.time + Duration::hours(3));
+    anchor.id = Some(420);
+
+    let mut tweets = vec![anchor.clone(), anchored()];
+    QueuedTweet::resolve_anchors(&mut tweets, &mut vec![]);
+    assert_eq!(tweets[1].time, anchor.time + Duration::hours(3) + Duration::minutes(45));
+}
+
+#[test]
+fn resolve_anchors_chain() {
+    let mut second = anchored();
+    second.local_id = Some("reminder".to_string());
+    let mut third = anchored();
+    third.after = Some("reminder".to_string());
+
+    let mut tweets = vec![third, second, unposted()];
+    QueuedTweet::resolve_anchors(&mut tweets, &mut vec![]);
+    assert_eq!(tweets[1].time, unposted().time + Duration::minutes(45));
+    assert_eq!(tweets[0].time, unposted().time + Duration::minutes(90));
+}
+
+#[test]
+fn resolve_anchors_nonexistant() {
+    let mut tweets = vec![anchored()];
+    let mut out = vec![];
+    QueuedTweet::resolve_anchors(&mut tweets, &mut out);
+    assert!(tweets[0].failed.is_some());
+    assert!(!out.is_empty());
+}
+
+#[test]
+fn resolve_anchors_dropped() {
+    let mut anchor = dropped();
+    anchor.local_id = Some("announcement".to_string());
+
+    let mut tweets = vec![anchor, anchored()];
+    QueuedTweet::resolve_anchors(&mut tweets, &mut vec![]);
+    assert!(tweets[1].failed.is_some());
+}
+
+#[test]
+fn resolve_anchors_cycle() {
+    let mut first = unposted();
+    first.after = Some("reminder".to_string());
+    first.after_minutes = Some(45);
+    let mut second = anchored();
+    second.local_id = Some("reminder".to_string());
+
+    let mut tweets = vec![first, second, anchored()];
+    let mut out = vec![];
+    QueuedTweet::resolve_anchors(&mut tweets, &mut out);
+    assert_eq!(tweets[0].failed, Some("anchors and targets form a cycle: announcement -> reminder -> announcement".to_string()));
+    assert_eq!(tweets[1].failed, Some("anchors and targets form a cycle: reminder -> announcement -> reminder".to_string()));
+    assert!(tweets[2].failed.is_some());
+    assert_eq!(String::from_utf8(out).unwrap().lines().count(), 3);
+}
+
+#[test]
+fn resolve_anchors_duplicate_local_id() {
+    let mut tweets = vec![unposted(), unposted(), anchored()];
+    QueuedTweet::resolve_anchors(&mut tweets, &mut vec![]);
+    assert_eq!(tweets[0].failed, Some("local_id \"announcement\" isn't unique".to_string()));
+    assert_eq!(tweets[1].failed, Some("local_id \"announcement\" isn't unique".to_string()));
+    assert!(tweets[2].failed.is_some());
+}
+
+#[test]
+fn resolve_targets_unposted() {
+    let mut tweets = vec![pin(), unposted()];
+    QueuedTweet::resolve_anchors(&mut tweets, &mut vec![]);
+    assert_eq!(tweets[0].action, pin().action);
+    assert!(tweets[0].failed.is_none());
 }
 
+#[test]
+fn resolve_targets_posted() {
+    let mut target = unposted();
+    target.time_posted = Some(target.time);
+    target.id = Some(420);
+
+    let mut tweets = vec![pin(), target];
+    QueuedTweet::resolve_anchors(&mut tweets, &mut vec![]);
+    assert_eq!(tweets[0].action, TweetAction::Pin(TweetTarget::Id(420)));
+}
+
+#[test]
+fn resolve_targets_nonexistant() {
+    let mut tweets = vec![pin()];
+    let mut out = vec![];
+    QueuedTweet::resolve_anchors(&mut tweets, &mut out);
+    assert!(tweets[0].failed.is_some());
+    assert!(!out.is_empty());
+}
+
+
+fn lines_scaffold(name: &str, contents: &str) -> Vec<Option<usize>> {
+    let td = temp_dir().join("tweetr-test").join(format!("ops-queued_tweet-{}", name));
+    fs::create_dir_all(&td).unwrap();
+
+    let tf = td.join("tweets.toml");
+    File::create(&tf).unwrap().write_all(contents.as_bytes()).unwrap();
+
+    let tweets = QueuedTweet::read_with_lines(&tf).unwrap();
+    assert!(tweets.iter().all(|&(_, ref t)| t.is_ok()));
+    tweets.into_iter().map(|(l, _)| l).collect()
+}
 
 fn trans_scaffold(name: &str, tweets: Vec<QueuedTweet>) {
     let td = temp_dir().join("tweetr-test").join(format!("ops-queued_tweet-{}", name));
@@ -43,21 +209,110 @@ fn trans_scaffold(name: &str, tweets: Vec<QueuedTweet>) {
 
 fn unposted() -> QueuedTweet {
     QueuedTweet {
-        author: "nabijaczleweli".to_string(),
-        time: DateTime::parse_from_rfc2822("Tue, 1 Jul 2098 10:52:37 +0200").unwrap(),
-        content: "This tweet was not posted yet, so das good".to_string(),
-        time_posted: None,
-        id: None,
+        local_id: Some("announcement".to_string()),
+        ..QueuedTweet::new("nabijaczleweli",
+                           DateTime::parse_from_rfc2822("Tue, 1 Jul 2098 10:52:37 +0200").unwrap(),
+                           "This tweet was not posted yet, so das good")
+    }
+}
+
+fn deleted() -> QueuedTweet {
+    QueuedTweet {
+        time_posted: Some(DateTime::parse_from_rfc2822("Sat, 1 Jul 2000 15:12:58 -0800").unwrap()),
+        id: Some(421),
+        delete_after_minutes: Some(120),
+        delete_at: Some(DateTime::parse_from_rfc2822("Sat, 1 Jul 2000 18:00:00 -0800").unwrap()),
+        time_deleted: Some(DateTime::parse_from_rfc2822("Sat, 1 Jul 2000 17:13:02 -0800").unwrap()),
+        ..QueuedTweet::new("nabijaczleweli",
+                           DateTime::parse_from_rfc2822("Sat, 1 Jul 2000 15:12:57 -0800").unwrap(),
+                           "This tweet was up only for the duration of the giveaway")
+    }
+}
+
+fn retweet() -> QueuedTweet {
+    QueuedTweet {
+        content: "".to_string(),
+        action: TweetAction::Retweet(TweetTarget::Id(773264221226950656)),
+        ..unposted()
+    }
+}
+
+fn quote() -> QueuedTweet {
+    QueuedTweet {
+        content: "Look at this".to_string(),
+        action: TweetAction::Quote(TweetTarget::Id(773264221226950656)),
+        ..unposted()
+    }
+}
+
+fn pin() -> QueuedTweet {
+    QueuedTweet {
+        content: "".to_string(),
+        action: TweetAction::Pin(TweetTarget::Queued("announcement".to_string())),
+        local_id: None,
+        ..unposted()
+    }
+}
+
+fn profile_update() -> QueuedTweet {
+    QueuedTweet {
+        content: "".to_string(),
+        action: TweetAction::UpdateProfile(ProfileUpdate {
+            name: Some("nabijaczleweli (on holiday)".to_string()),
+            description: None,
+        }),
+        local_id: None,
+        ..unposted()
+    }
+}
+
+fn direct_message() -> QueuedTweet {
+    QueuedTweet {
+        content: "Thanks for your order, how's it working out?".to_string(),
+        action: TweetAction::DirectMessage(Recipient::Handle("a_customer".to_string())),
+        local_id: None,
+        ..unposted()
+    }
+}
+
+fn poll() -> QueuedTweet {
+    let mut posted = posted();
+    posted.poll = Some(Poll {
+        options: vec!["Yes".to_string(), "No".to_string(), "Maybe".to_string()],
+        duration_minutes: 60 * 24,
+    });
+    posted.card_id = Some("773264221226950657".to_string());
+    posted
+}
+
+fn anchored() -> QueuedTweet {
+    QueuedTweet {
+        after: Some("announcement".to_string()),
+        after_minutes: Some(45),
+        ..QueuedTweet::new("nabijaczleweli", DateTime::parse_from_rfc2822("Tue, 1 Jul 2098 11:37:37 +0200").unwrap(), "This tweet follows the announcement")
     }
 }
 
 fn posted() -> QueuedTweet {
     let now = Local::now();
     QueuedTweet {
-        author: "nabijaczleweli".to_string(),
-        time: DateTime::parse_from_rfc2822("Sat, 1 Jul 2000 15:12:57 -0800").unwrap(),
-        content: "This tweet got posted just now, aww yeah, boii".to_string(),
+        author_id: Some(0x81423),
         time_posted: Some(now.with_timezone(now.offset())),
         id: Some(420),
+        ..QueuedTweet::new("nabijaczleweli",
+                           DateTime::parse_from_rfc2822("Sat, 1 Jul 2000 15:12:57 -0800").unwrap(),
+                           "This tweet got posted just now, aww yeah, boii")
+    }
+}
+
+fn dropped() -> QueuedTweet {
+    QueuedTweet {
+        post_by: Some(DateTime::parse_from_rfc2822("Sat, 1 Jul 2000 16:12:57 -0800").unwrap()),
+        failed: Some("missed the deadline of 2000-07-01T16:12:57-08:00".to_string()),
+        jitter_minutes: Some(15),
+        expires: Some(DateTime::parse_from_rfc2822("Sat, 1 Jul 2000 18:00:00 -0800").unwrap()),
+        ..QueuedTweet::new("nabijaczleweli",
+                           DateTime::parse_from_rfc2822("Sat, 1 Jul 2000 15:12:57 -0800").unwrap(),
+                           "This tweet was dropped, because it had to be posted within the hour")
     }
 }
@@ -0,0 +1,36 @@
+extern crate tweetr;
+
+use self::tweetr::ops::{User, reauth_user};
+use self::tweetr::Outcome;
+
+
+#[test]
+fn secret_command() {
+    let mut user = User {
+        secret_command: Some("pass show tweetr/nabijaczleweli".to_string()),
+        ..User::new("nabijaczleweli", 0x81423, "529443-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4", "")
+    };
+
+    assert_eq!(reauth_user::replace_tokens(&mut user, authorised()), Ok(Some("P8B46wSaV1j0stxx94L2Z4dc3UnR0zMMdO4E4Pq3rsOVf".to_string())));
+    assert_eq!(user.access_token_key, "529443-vHqE7HBgwoJLAKbTUuc1k4KmnaQHlZ2lbtGyHLW");
+    assert_eq!(user.access_token_secret, "");
+    assert_eq!(user.secret_command, Some("pass show tweetr/nabijaczleweli".to_string()));
+}
+
+#[test]
+fn mismatch() {
+    let mut user = User::new("danerangLP", 0x2A, "42-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4", "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5");
+    let before = user.clone();
+
+    assert_eq!(reauth_user::replace_tokens(&mut user, authorised()),
+               Err(Outcome::UserMismatch {
+                   expected: "danerangLP#42".to_string(),
+                   authorised: "nabijaczleweli#529443".to_string(),
+               }));
+    assert_eq!(user, before);
+}
+
+
+fn authorised() -> User {
+    User::new("nabijaczleweli", 0x81423, "529443-vHqE7HBgwoJLAKbTUuc1k4KmnaQHlZ2lbtGyHLW", "P8B46wSaV1j0stxx94L2Z4dc3UnR0zMMdO4E4Pq3rsOVf")
+}
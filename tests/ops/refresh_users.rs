@@ -0,0 +1,66 @@
+extern crate tweetr;
+extern crate chrono;
+
+use self::tweetr::ops::{QueuedTweet, UserGroup, User, refresh_users};
+use self::chrono::DateTime;
+
+
+#[test]
+fn renamed() {
+    let mut tweets = vec![tweet("nabijaczleweli_old", Some(481)), tweet("danerangLP", Some(482))];
+    assert_eq!(refresh_users::rename_authors(&mut tweets, &users()), 1);
+    assert_eq!(tweets, vec![tweet("nabijaczleweli", Some(481)), tweet("danerangLP", Some(482))]);
+}
+
+#[test]
+fn unbound_untouched() {
+    let mut tweets = vec![tweet("nabijaczleweli_old", None), tweet("DANERANGLP", None)];
+    assert_eq!(refresh_users::rename_authors(&mut tweets, &users()), 0);
+    assert_eq!(tweets, vec![tweet("nabijaczleweli_old", None), tweet("DANERANGLP", None)]);
+}
+
+#[test]
+fn unknown_untouched() {
+    let mut tweets = vec![tweet("nabijaczleweli_old", Some(483))];
+    assert_eq!(refresh_users::rename_authors(&mut tweets, &users()), 0);
+    assert_eq!(tweets, vec![tweet("nabijaczleweli_old", Some(483))]);
+}
+
+#[test]
+fn members_renamed() {
+    let mut groups = vec![group(vec!["NABIJACZLEWELI_OLD", "danerangLP", "brand_dev"])];
+    let before = vec![user("nabijaczleweli_old", 481), user("danerangLP", 482), user("brand_dev", 483)];
+    assert_eq!(refresh_users::rename_members(&mut groups, &before, &users()), 1);
+    assert_eq!(groups, vec![group(vec!["nabijaczleweli", "danerangLP", "brand_dev"])]);
+}
+
+#[test]
+fn members_unknown_untouched() {
+    let mut groups = vec![group(vec!["nabijaczleweli_old"])];
+    assert_eq!(refresh_users::rename_members(&mut groups, &vec![], &users()), 0);
+    assert_eq!(groups, vec![group(vec!["nabijaczleweli_old"])]);
+}
+
+
+fn users() -> Vec<User> {
+    vec![user("nabijaczleweli", 481), user("danerangLP", 482)]
+}
+
+fn user(name: &str, id: i64) -> User {
+    User::new(name, id, format!("{}-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4", id), "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5")
+}
+
+fn group(members: Vec<&str>) -> UserGroup {
+    UserGroup {
+        name: "brand-accounts".to_string(),
+        members: members.into_iter().map(String::from).collect(),
+        stagger_minutes: None,
+    }
+}
+
+fn tweet(author: &str, author_id: Option<i64>) -> QueuedTweet {
+    QueuedTweet {
+        author_id: author_id,
+        ..QueuedTweet::new(author, DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap(), "Capitalism")
+    }
+}
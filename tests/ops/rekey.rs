@@ -0,0 +1,93 @@
+extern crate tweetr;
+
+use self::tweetr::ops::{AppTokens, SecretKey, User, UserGroup, rekey};
+use std::fs::{self, File};
+use std::path::PathBuf;
+use std::env::temp_dir;
+use std::io::Write;
+
+
+#[test]
+fn encrypt() {
+    let paths = scaffold("encrypt", None);
+    let key = key("hunter2");
+
+    assert_eq!(rekey::rekey(&paths, None, Some(&key), &mut vec![]), Ok(()));
+    assert_eq!(SecretKey::unlock(paths.0.parent().unwrap(), Some("hunter2")), Ok(Some(key.clone())));
+    assert_contents(&paths, Some(&key));
+}
+
+#[test]
+fn change_passphrase() {
+    let old_key = key("hunter2");
+    let paths = scaffold("change_passphrase", Some(&old_key));
+    let new_key = key("hunter3");
+
+    assert_eq!(rekey::rekey(&paths, Some(&old_key), Some(&new_key), &mut vec![]), Ok(()));
+    assert_eq!(SecretKey::unlock(paths.0.parent().unwrap(), Some("hunter3")), Ok(Some(new_key.clone())));
+    assert!(SecretKey::unlock(paths.0.parent().unwrap(), Some("hunter2")).is_err());
+    assert_contents(&paths, Some(&new_key));
+}
+
+#[test]
+fn decrypt() {
+    let key = key("hunter2");
+    let paths = scaffold("decrypt", Some(&key));
+
+    let mut out = vec![];
+    assert_eq!(rekey::rekey(&paths, Some(&key), None, &mut out), Ok(()));
+    assert!(!paths.0.exists());
+    assert_contents(&paths, None);
+    assert_eq!(String::from_utf8(out).unwrap(), "Decrypted 1 app profiles and 1 users.\n");
+}
+
+#[test]
+fn wrong_old_passphrase() {
+    let key = key("hunter2");
+    let paths = scaffold("wrong_old_passphrase", Some(&key));
+
+    assert!(rekey::rekey(&paths, Some(&self::key("hunter3")), Some(&self::key("hunter4")), &mut vec![]).is_err());
+    assert_eq!(SecretKey::unlock(paths.0.parent().unwrap(), Some("hunter2")), Ok(Some(key.clone())));
+    assert_contents(&paths, Some(&key));
+}
+
+
+fn key(passphrase: &str) -> SecretKey {
+    SecretKey::derive(passphrase, b"tweetr-test".to_vec(), 4)
+}
+
+fn app() -> AppTokens {
+    AppTokens {
+        key: "qzuqpwr101q4RtK9mDorI9ndm".to_string(),
+        secret: "HW4YG3Kdcap5ovcZ5fZfBJFedKR6GQe9MtZDS9Gm34hXiirkU5".to_string(),
+        secret_command: None,
+    }
+}
+
+fn user() -> User {
+    User::new("nabijaczleweli", 0x81423, "529443-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4", "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5")
+}
+
+fn scaffold(name: &str, key: Option<&SecretKey>) -> (PathBuf, PathBuf) {
+    let td = temp_dir().join("tweetr-test").join(format!("ops-rekey-{}", name));
+    let _ = fs::remove_dir_all(&td);
+    fs::create_dir_all(&td).unwrap();
+
+    if let Some(key) = key {
+        key.write(&td.join("secrets.toml"));
+    }
+    app().write(&td.join("app.toml"), key);
+    File::create(td.join("users.toml")).unwrap().write_all(b"[[group]]\nname = \"everyone\"\nmembers = [\"nabijaczleweli\"]\n").unwrap();
+    User::write(vec![user()], &td.join("users.toml"), key).unwrap();
+
+    (td.join("secrets.toml"), td.join("users.toml"))
+}
+
+fn assert_contents(paths: &(PathBuf, PathBuf), key: Option<&SecretKey>) {
+    let td = paths.0.parent().unwrap();
+    assert_eq!(AppTokens::read(&td.join("app.toml"), key), Ok(app()));
+    assert_eq!(User::read(&paths.1, key), Ok(vec![user()]));
+    assert_eq!(UserGroup::read(&paths.1).unwrap().len(), 1);
+    assert_eq!(fs::read_dir(td).unwrap().filter(|f| f.as_ref().unwrap().file_name().to_str().unwrap().ends_with(".new")).count(),
+               0);
+}
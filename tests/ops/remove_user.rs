@@ -0,0 +1,62 @@
+extern crate tweetr;
+extern crate chrono;
+
+use self::tweetr::ops::{QueuedTweet, UserGroup, User, remove_user};
+use self::chrono::DateTime;
+
+
+#[test]
+fn unposted() {
+    let tweets = vec![tweet("nabijaczleweli", None), tweet("danerangLP", None), tweet("NABIJACZLEWELI", None)];
+    assert_eq!(remove_user::orphaned_tweets(&tweets, &user()), vec![&tweets[0], &tweets[2]]);
+}
+
+#[test]
+fn posted() {
+    let tweets = vec![tweet("nabijaczleweli", Some(0x2A))];
+    assert!(remove_user::orphaned_tweets(&tweets, &user()).is_empty());
+}
+
+#[test]
+fn failed() {
+    let mut tweets = vec![tweet("nabijaczleweli", None)];
+    tweets[0].failed = Some("Twitter API error: Status is a duplicate.".to_string());
+    assert!(remove_user::orphaned_tweets(&tweets, &user()).is_empty());
+}
+
+#[test]
+fn pending_deletion() {
+    let mut tweets = vec![tweet("nabijaczleweli", Some(0x2A)), tweet("nabijaczleweli", Some(0x2B))];
+    tweets[0].delete_after_minutes = Some(60);
+    tweets[1].delete_after_minutes = Some(60);
+    tweets[1].time_deleted = Some(DateTime::parse_from_rfc3339("2016-09-09T01:33:30+02:00").unwrap());
+    assert_eq!(remove_user::orphaned_tweets(&tweets, &user()), vec![&tweets[0]]);
+}
+
+#[test]
+fn groups() {
+    let mut groups = vec![group("everyone", &["nabijaczleweli", "danerangLP"]), group("others", &["danerangLP"]), group("alone", &["NabijaczLeweli"])];
+    assert_eq!(remove_user::remove_from_groups(&mut groups, "nabijaczleweli"), vec!["everyone".to_string(), "alone".to_string()]);
+    assert_eq!(groups, vec![group("everyone", &["danerangLP"]), group("others", &["danerangLP"]), group("alone", &[])]);
+}
+
+
+fn user() -> User {
+    User::new("nabijaczleweli", 0x81423, "529443-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4", "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5")
+}
+
+fn tweet(author: &str, id: Option<i64>) -> QueuedTweet {
+    QueuedTweet {
+        time_posted: id.map(|_| DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap()),
+        id: id,
+        ..QueuedTweet::new(author, DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap(), "Capitalism")
+    }
+}
+
+fn group(name: &str, members: &[&str]) -> UserGroup {
+    UserGroup {
+        name: name.to_string(),
+        members: members.iter().map(|m| m.to_string()).collect(),
+        stagger_minutes: None,
+    }
+}
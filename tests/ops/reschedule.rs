@@ -0,0 +1,73 @@
+extern crate tweetr;
+extern crate chrono;
+
+use self::tweetr::ops::{TweetSelector, QueuedTweet, reschedule};
+use self::chrono::{DateTime, Duration};
+
+
+#[test]
+fn all() {
+    let mut tweets = vec![tweet("nabijaczleweli", "2016-09-09T00:33:30+02:00"), tweet("danerangLP", "2016-09-09T01:33:30+02:00")];
+    assert_eq!(reschedule::shift(&mut tweets, &TweetSelector::default(), Duration::minutes(-30)), 2);
+    assert_eq!(tweets,
+               vec![tweet("nabijaczleweli", "2016-09-09T00:03:30+02:00"), tweet("danerangLP", "2016-09-09T01:03:30+02:00")]);
+}
+
+#[test]
+fn selected() {
+    let mut tweets = vec![tweet("nabijaczleweli", "2016-09-09T00:33:30+02:00"),
+                          tweet("nabijaczleweli", "2016-09-10T00:33:30+02:00"),
+                          tweet("danerangLP", "2016-09-09T01:33:30+02:00")];
+    let selector = TweetSelector {
+        author: Some("nabijaczleweli".to_string()),
+        from: None,
+        to: Some(DateTime::parse_from_rfc3339("2016-09-09T12:00:00+02:00").unwrap()),
+    };
+
+    assert_eq!(reschedule::shift(&mut tweets, &selector, Duration::days(1)), 1);
+    assert_eq!(tweets,
+               vec![tweet("nabijaczleweli", "2016-09-10T00:33:30+02:00"),
+                    tweet("nabijaczleweli", "2016-09-10T00:33:30+02:00"),
+                    tweet("danerangLP", "2016-09-09T01:33:30+02:00")]);
+}
+
+#[test]
+fn posted_untouched() {
+    let mut tweets = vec![tweet("nabijaczleweli", "2016-09-09T00:33:30+02:00")];
+    tweets[0].id = Some(773264221226950656);
+
+    assert_eq!(reschedule::shift(&mut tweets, &TweetSelector::default(), Duration::hours(2)), 0);
+    assert_eq!(tweets[0].time, DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap());
+}
+
+#[test]
+fn post_by() {
+    let mut tweets = vec![tweet("nabijaczleweli", "2016-09-09T00:33:30+02:00")];
+    tweets[0].post_by = Some(DateTime::parse_from_rfc3339("2016-09-09T01:00:00+02:00").unwrap());
+
+    reschedule::shift(&mut tweets, &TweetSelector::default(), Duration::hours(2));
+    assert_eq!(tweets[0].post_by, Some(DateTime::parse_from_rfc3339("2016-09-09T03:00:00+02:00").unwrap()));
+}
+
+#[test]
+fn expires() {
+    let mut tweets = vec![tweet("nabijaczleweli", "2016-09-09T00:33:30+02:00")];
+    tweets[0].expires = Some(DateTime::parse_from_rfc3339("2016-09-09T01:00:00+02:00").unwrap());
+
+    reschedule::shift(&mut tweets, &TweetSelector::default(), Duration::hours(2));
+    assert_eq!(tweets[0].expires, Some(DateTime::parse_from_rfc3339("2016-09-09T03:00:00+02:00").unwrap()));
+}
+
+#[test]
+fn delete_at() {
+    let mut tweets = vec![tweet("nabijaczleweli", "2016-09-09T00:33:30+02:00")];
+    tweets[0].delete_at = Some(DateTime::parse_from_rfc3339("2016-09-09T01:00:00+02:00").unwrap());
+
+    reschedule::shift(&mut tweets, &TweetSelector::default(), Duration::hours(2));
+    assert_eq!(tweets[0].delete_at, Some(DateTime::parse_from_rfc3339("2016-09-09T03:00:00+02:00").unwrap()));
+}
+
+
+fn tweet(author: &str, time: &str) -> QueuedTweet {
+    QueuedTweet::new(author, DateTime::parse_from_rfc3339(time).unwrap(), "Capitalism")
+}
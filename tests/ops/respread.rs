@@ -0,0 +1,69 @@
+extern crate tweetr;
+extern crate chrono;
+
+use self::tweetr::ops::{TweetSelector, QueuedTweet, respread};
+use self::chrono::{DateTime, FixedOffset};
+
+
+#[test]
+fn even() {
+    let mut tweets = vec![tweet("2016-09-09T00:33:30+02:00"), tweet("2016-09-09T00:34:30+02:00"), tweet("2016-09-09T05:00:00+02:00")];
+    assert_eq!(respread::respread(&mut tweets, &TweetSelector::default(), time("2016-09-10T12:00:00+02:00"), time("2016-09-10T18:00:00+02:00")),
+               3);
+    assert_eq!(tweets,
+               vec![tweet("2016-09-10T12:00:00+02:00"), tweet("2016-09-10T15:00:00+02:00"), tweet("2016-09-10T18:00:00+02:00")]);
+}
+
+#[test]
+fn keeps_order() {
+    let mut tweets = vec![tweet("2016-09-09T05:00:00+02:00"), tweet("2016-09-09T00:33:30+02:00")];
+    respread::respread(&mut tweets, &TweetSelector::default(), time("2016-09-10T12:00:00+02:00"), time("2016-09-10T13:00:00+02:00"));
+    assert_eq!(tweets, vec![tweet("2016-09-10T13:00:00+02:00"), tweet("2016-09-10T12:00:00+02:00")]);
+}
+
+#[test]
+fn single() {
+    let mut tweets = vec![tweet("2016-09-09T00:33:30+02:00")];
+    respread::respread(&mut tweets, &TweetSelector::default(), time("2016-09-10T12:00:00+02:00"), time("2016-09-10T13:00:00+02:00"));
+    assert_eq!(tweets, vec![tweet("2016-09-10T12:00:00+02:00")]);
+}
+
+#[test]
+fn selected() {
+    let mut tweets = vec![tweet("2016-09-09T00:33:30+02:00"), tweet("2016-09-11T00:33:30+02:00")];
+    let selector = TweetSelector { to: Some(time("2016-09-10T00:00:00+02:00")), ..TweetSelector::default() };
+
+    assert_eq!(respread::respread(&mut tweets, &selector, time("2016-09-10T12:00:00+02:00"), time("2016-09-10T13:00:00+02:00")),
+               1);
+    assert_eq!(tweets, vec![tweet("2016-09-10T12:00:00+02:00"), tweet("2016-09-11T00:33:30+02:00")]);
+}
+
+#[test]
+fn post_by() {
+    let mut tweets = vec![QueuedTweet { post_by: Some(time("2016-09-09T01:33:30+02:00")), ..tweet("2016-09-09T00:33:30+02:00") }];
+    respread::respread(&mut tweets, &TweetSelector::default(), time("2016-09-10T12:00:00+02:00"), time("2016-09-10T13:00:00+02:00"));
+    assert_eq!(tweets, vec![QueuedTweet { post_by: Some(time("2016-09-10T13:00:00+02:00")), ..tweet("2016-09-10T12:00:00+02:00") }]);
+}
+
+#[test]
+fn expires() {
+    let mut tweets = vec![QueuedTweet { expires: Some(time("2016-09-09T01:33:30+02:00")), ..tweet("2016-09-09T00:33:30+02:00") }];
+    respread::respread(&mut tweets, &TweetSelector::default(), time("2016-09-10T12:00:00+02:00"), time("2016-09-10T13:00:00+02:00"));
+    assert_eq!(tweets, vec![QueuedTweet { expires: Some(time("2016-09-10T13:00:00+02:00")), ..tweet("2016-09-10T12:00:00+02:00") }]);
+}
+
+#[test]
+fn delete_at() {
+    let mut tweets = vec![QueuedTweet { delete_at: Some(time("2016-09-09T01:33:30+02:00")), ..tweet("2016-09-09T00:33:30+02:00") }];
+    respread::respread(&mut tweets, &TweetSelector::default(), time("2016-09-10T12:00:00+02:00"), time("2016-09-10T13:00:00+02:00"));
+    assert_eq!(tweets, vec![QueuedTweet { delete_at: Some(time("2016-09-10T13:00:00+02:00")), ..tweet("2016-09-10T12:00:00+02:00") }]);
+}
+
+
+fn tweet(t: &str) -> QueuedTweet {
+    QueuedTweet::new("nabijaczleweli", time(t), "Capitalism")
+}
+
+fn time(t: &str) -> DateTime<FixedOffset> {
+    DateTime::parse_from_rfc3339(t).unwrap()
+}
@@ -0,0 +1,40 @@
+extern crate tweetr;
+
+use self::tweetr::ops::SecretKey;
+use std::fs::{self, File};
+use std::env::temp_dir;
+use self::tweetr::Outcome;
+use std::path::PathBuf;
+use std::io::Write;
+
+
+#[test]
+fn invalid_salt() {
+    let td = scaffold("invalid_salt");
+    File::create(td.join("secrets.toml")).unwrap().write_all(b"salt = \"not base64!\"\nlog_n = 4\ncheck = \"\"\n").unwrap();
+
+    match SecretKey::unlock(&td, Some("hunter2")) {
+        Err(Outcome::FileParsingFailed { desc: "secret key", errors }) => assert!(errors[0].starts_with("salt \"not base64!\" isn't valid base64")),
+        other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn unreadable() {
+    let td = scaffold("unreadable");
+    fs::create_dir(td.join("secrets.toml")).unwrap();
+
+    assert_eq!(SecretKey::unlock(&td, Some("hunter2")),
+               Err(Outcome::FileParsingFailed {
+                   desc: "secret key",
+                   errors: vec![format!("couldn't read {}", td.join("secrets.toml").display())],
+               }));
+}
+
+
+fn scaffold(name: &str) -> PathBuf {
+    let td = temp_dir().join("tweetr-test").join(format!("ops-secret_key-{}", name));
+    let _ = fs::remove_dir_all(&td);
+    fs::create_dir_all(&td).unwrap();
+    td
+}
@@ -0,0 +1,227 @@
+extern crate tweetr;
+extern crate chrono;
+
+#[cfg(unix)]
+use std::os::unix::fs::PermissionsExt;
+use self::tweetr::ops::{EvergreenSelection, EvergreenTweet, EvergreenPool, QueuedTweet, TweetAction, TweetTarget, UserGroup, Suspension, User, start_daemon};
+use self::chrono::{DateTime, Duration, FixedOffset, Local};
+use self::tweetr::ops::start_daemon::CatchUpPolicy;
+use self::tweetr::util::create_private_file;
+use self::tweetr::Outcome;
+use std::env::temp_dir;
+use std::path::PathBuf;
+use std::fs;
+
+
+#[test]
+fn expand_groups_post_by() {
+    let mut tweets = vec![QueuedTweet { post_by: Some(time() + Duration::minutes(15)), ..tweet("brand-accounts") }];
+    start_daemon::expand_groups(&mut tweets, &vec![group(vec!["brand", "brand_support", "brand_dev"])], &mut vec![]);
+
+    assert_eq!(tweets.len(), 3);
+    for tweet in &tweets {
+        assert_eq!(tweet.post_by, Some(time() + Duration::minutes(15)));
+    }
+    assert_eq!(tweets[2].time, time() + Duration::minutes(20));
+}
+
+#[test]
+fn expand_groups_empty() {
+    let mut tweets = vec![tweet("brand-accounts")];
+    let mut out = vec![];
+    start_daemon::expand_groups(&mut tweets, &vec![group(vec![])], &mut out);
+
+    assert_eq!(tweets.len(), 1);
+    assert_eq!(tweets[0].failed, Some("group brand-accounts has no members".to_string()));
+    assert!(!out.is_empty());
+}
+
+#[test]
+fn expand_groups_other_author() {
+    let mut tweets = vec![tweet("brand")];
+    start_daemon::expand_groups(&mut tweets, &vec![group(vec!["brand_support"])], &mut vec![]);
+    assert_eq!(tweets, vec![tweet("brand")]);
+}
+#[test]
+fn apply_catch_up_policy_respread_postable_only() {
+    let now = Local::now();
+    let now = now.with_timezone(now.offset());
+    let late = |author| QueuedTweet::new(author, now - Duration::hours(5), "We're launching today!");
+
+    let mut tweets = vec![late("brand"), late("brand_support"), late("brand_dev"), late("brand"), late("brand")];
+    let users = vec![user("brand"), User { suspended: Some(suspension()), ..user("brand_support") }];
+    start_daemon::apply_catch_up_policy(&mut tweets, &users, &CatchUpPolicy::Respread(Duration::hours(1)), Duration::minutes(1), true, &mut vec![]);
+
+    assert_eq!(tweets[1], late("brand_support"));
+    assert_eq!(tweets[2], late("brand_dev"));
+    assert!(tweets[0].time < now);
+    assert!(tweets[3].time > now + Duration::minutes(19) && tweets[3].time < now + Duration::minutes(21));
+    assert!(tweets[4].time > now + Duration::minutes(39) && tweets[4].time < now + Duration::minutes(41));
+}
+
+#[test]
+fn apply_catch_up_policy_respread_once() {
+    let now = Local::now();
+    let now = now.with_timezone(now.offset());
+    let late = || QueuedTweet::new("brand", now - Duration::hours(5), "We're launching today!");
+
+    let mut tweets = vec![late(), late()];
+    let policy = CatchUpPolicy::Respread(Duration::hours(1));
+    start_daemon::apply_catch_up_policy(&mut tweets, &vec![user("brand")], &policy, Duration::minutes(1), false, &mut vec![]);
+    assert_eq!(tweets, vec![late(), late()]);
+}
+
+#[test]
+fn apply_catch_up_policy_respread_deadline() {
+    let now = Local::now();
+    let now = now.with_timezone(now.offset());
+    let late = |deadline| QueuedTweet { expires: deadline, ..QueuedTweet::new("brand", now - Duration::hours(5), "We're launching today!") };
+
+    let mut tweets = vec![late(None), late(Some(now + Duration::minutes(10)))];
+    let policy = CatchUpPolicy::Respread(Duration::hours(1));
+    start_daemon::apply_catch_up_policy(&mut tweets, &vec![user("brand")], &policy, Duration::minutes(1), true, &mut vec![]);
+    assert_eq!(tweets, vec![late(None), late(Some(now + Duration::minutes(10)))]);
+}
+
+#[test]
+fn apply_catch_up_policy_skip() {
+    let now = Local::now();
+    let now = now.with_timezone(now.offset());
+
+    let mut tweets = vec![QueuedTweet::new("brand", now - Duration::hours(5), "We're launching today!")];
+    let mut out = vec![];
+    let policy = CatchUpPolicy::SkipLate(Duration::minutes(30));
+    start_daemon::apply_catch_up_policy(&mut tweets, &vec![user("brand")], &policy, Duration::minutes(1), true, &mut out);
+
+    assert_eq!(tweets[0].failed, None);
+    assert!(!out.is_empty());
+    assert_eq!(start_daemon::tweet_indices_to_post(&mut tweets, &vec![user("brand")], &CatchUpPolicy::SkipLate(Duration::minutes(30))),
+               Vec::<usize>::new());
+}
+
+#[test]
+fn tweet_indices_to_post_limits_only_tweets() {
+    let now = Local::now();
+    let now = now.with_timezone(now.offset());
+    let like = |time| QueuedTweet { action: TweetAction::Like(TweetTarget::Id(773264221226950656)), ..QueuedTweet::new("brand", time, "") };
+
+    let mut tweets = vec![QueuedTweet { time_posted: Some(now - Duration::minutes(5)), id: Some(773264221226950657), ..like(now - Duration::minutes(5)) },
+                          like(now - Duration::minutes(1)),
+                          QueuedTweet::new("brand", now - Duration::minutes(1), "We're launching today!"),
+                          QueuedTweet::new("brand", now - Duration::minutes(1), "Seriously, today!")];
+    let users = vec![User {
+                         min_minutes_between_posts: Some(15),
+                         max_posts_per_day: Some(1),
+                         ..user("brand")
+                     }];
+
+    assert_eq!(start_daemon::tweet_indices_to_post(&mut tweets, &users, &CatchUpPolicy::PostAll), vec![1, 2]);
+    assert!(tweets[3].time > now + Duration::hours(23));
+}
+
+#[test]
+fn evergreen_tweet_index_to_post_renamed_author() {
+    let mut tweet = QueuedTweet::new("brand_old", time() + Duration::days(365 * 100), "We're launching today!");
+    tweet.author_id = Some(481);
+
+    assert_eq!(start_daemon::evergreen_tweet_index_to_post(&evergreen_pool(), &vec![tweet], &vec![user("brand")]), None);
+}
+
+#[test]
+fn evergreen_tweet_index_to_post_unknown_author() {
+    let tweet = QueuedTweet::new("BRAND", time() + Duration::days(365 * 100), "We're launching today!");
+    assert_eq!(start_daemon::evergreen_tweet_index_to_post(&evergreen_pool(), &vec![tweet], &vec![]), None);
+    assert_eq!(start_daemon::evergreen_tweet_index_to_post(&evergreen_pool(), &vec![], &vec![]), Some(0));
+}
+
+#[test]
+fn evergreen_tweet_index_to_post_suspended() {
+    let users = vec![User { suspended: Some(suspension()), ..user("brand") }];
+    assert_eq!(start_daemon::evergreen_tweet_index_to_post(&evergreen_pool(), &vec![], &users), None);
+}
+
+#[test]
+fn evergreen_tweet_index_to_post_limits() {
+    let now = Local::now();
+    let now = now.with_timezone(now.offset());
+    let posted = QueuedTweet { time_posted: Some(now - Duration::minutes(5)), id: Some(773264221226950656), ..QueuedTweet::new("brand", now, "Hi!") };
+
+    let users = vec![User { min_minutes_between_posts: Some(15), ..user("brand") }];
+    assert_eq!(start_daemon::evergreen_tweet_index_to_post(&evergreen_pool(), &vec![posted.clone()], &users), None);
+
+    let users = vec![User { max_posts_per_day: Some(1), ..user("brand") }];
+    assert_eq!(start_daemon::evergreen_tweet_index_to_post(&evergreen_pool(), &vec![posted.clone()], &users), None);
+
+    let users = vec![User { min_minutes_between_posts: Some(1), max_posts_per_day: Some(2), ..user("brand") }];
+    assert_eq!(start_daemon::evergreen_tweet_index_to_post(&evergreen_pool(), &vec![posted], &users), Some(0));
+}
+
+#[cfg(unix)]
+#[test]
+fn check_permissions_insecure() {
+    let td = scaffold("check_permissions_insecure");
+    fs::set_permissions(td.1.join("users.toml"), fs::Permissions::from_mode(0o644)).unwrap();
+
+    assert_eq!(start_daemon::check_permissions(&td),
+               Outcome::InsecurePermissions(vec!["$TEMP/ops-start_daemon-check_permissions_insecure/users.toml (mode 644)".to_string()]));
+}
+
+#[cfg(unix)]
+#[test]
+fn check_permissions_rewritten() {
+    let td = scaffold("check_permissions_rewritten");
+    fs::set_permissions(td.1.join("users.toml"), fs::Permissions::from_mode(0o644)).unwrap();
+    create_private_file(&td.1.join("users.toml")).unwrap();
+
+    assert_eq!(start_daemon::check_permissions(&td), Outcome::NoError);
+}
+
+
+fn scaffold(name: &str) -> (String, PathBuf) {
+    let td = temp_dir().join("tweetr-test").join(format!("ops-start_daemon-{}", name));
+    let _ = fs::remove_dir_all(&td);
+    fs::create_dir_all(&td).unwrap();
+    create_private_file(&td.join("app.toml")).unwrap();
+    create_private_file(&td.join("users.toml")).unwrap();
+    (format!("$TEMP/ops-start_daemon-{}", name), td)
+}
+
+fn evergreen_pool() -> EvergreenPool {
+    EvergreenPool {
+        author: "brand".to_string(),
+        min_days_between_repeats: 7,
+        selection: EvergreenSelection::RoundRobin,
+        max_per_day: 2,
+        tweets: vec![EvergreenTweet {
+                         content: "Check out our products!".to_string(),
+                         last_posted: None,
+                     }],
+    }
+}
+
+fn user(name: &str) -> User {
+    User::new(name, 481, "481-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4", "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5")
+}
+
+fn suspension() -> Suspension {
+    Suspension {
+        reason: "Invalid or expired token.".to_string(),
+        time: time(),
+    }
+}
+
+fn group(members: Vec<&str>) -> UserGroup {
+    UserGroup {
+        name: "brand-accounts".to_string(),
+        members: members.into_iter().map(String::from).collect(),
+        stagger_minutes: Some(10),
+    }
+}
+
+fn tweet(author: &str) -> QueuedTweet {
+    QueuedTweet::new(author, time(), "We're launching today!")
+}
+
+fn time() -> DateTime<FixedOffset> {
+    DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap()
+}
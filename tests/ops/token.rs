@@ -1,7 +1,9 @@
 extern crate tweetr;
 
 use self::tweetr::ops::AppTokens;
+use self::tweetr::Outcome;
 use std::env::temp_dir;
+use std::io::Write;
 use std::fs;
 
 
@@ -16,9 +18,93 @@ fn trans_eq() {
     let tokens = AppTokens {
         key: "qzuqpwr101q4RtK9mDorI9ndm".to_string(),
         secret: "HW4YG3Kdcap5ovcZ5fZfBJFedKR6GQe9MtZDS9Gm34hXiirkU5".to_string(),
+        secret_command: None,
     };
-    tokens.write(&tf);
-    let read_tokens = AppTokens::read(&tf).unwrap();
+    tokens.write(&tf, None);
+    let read_tokens = AppTokens::read(&tf, None).unwrap();
 
     assert_eq!(tokens, read_tokens);
 }
+
+
+#[test]
+fn secret_command_trans_eq() {
+    let td = temp_dir().join("tweetr-test").join("ops-token-secret_command_trans_eq");
+    fs::create_dir_all(&td).unwrap();
+
+    let tf = td.join("app.toml");
+    let _ = fs::remove_file(&tf);
+
+    let tokens = AppTokens {
+        key: "qzuqpwr101q4RtK9mDorI9ndm".to_string(),
+        secret: String::new(),
+        secret_command: Some("pass show tweetr/app".to_string()),
+    };
+    tokens.write(&tf, None);
+    let read_tokens = AppTokens::read(&tf, None).unwrap();
+
+    assert_eq!(tokens, read_tokens);
+}
+
+#[test]
+fn read_profiles() {
+    let td = temp_dir().join("tweetr-test").join("ops-token-read_profiles");
+    let _ = fs::remove_dir_all(&td);
+    fs::create_dir_all(&td).unwrap();
+
+    let default = AppTokens {
+        key: "qzuqpwr101q4RtK9mDorI9ndm".to_string(),
+        secret: "HW4YG3Kdcap5ovcZ5fZfBJFedKR6GQe9MtZDS9Gm34hXiirkU5".to_string(),
+        secret_command: None,
+    };
+    let staging = AppTokens {
+        key: "hGf0MuCG4wTBGhDsxnBLnM1K3".to_string(),
+        secret: "xq2yPFTVkBqwKF9DUa4SqzYDDsuCPvRIFW1OGmMPoLZ7zSghFu".to_string(),
+        secret_command: None,
+    };
+    default.write(&td.join(AppTokens::profile_file_name(None)), None);
+    staging.write(&td.join(AppTokens::profile_file_name(Some("staging"))), None);
+    fs::File::create(td.join("users.toml")).unwrap();
+
+    let (profiles, errors) = AppTokens::read_profiles(&td, None).unwrap();
+    assert!(errors.is_empty());
+    assert_eq!(profiles.len(), 2);
+    assert_eq!(profiles.get(&None), Some(&default));
+    assert_eq!(profiles.get(&Some("staging".to_string())), Some(&staging));
+}
+
+#[test]
+fn read_profiles_broken() {
+    let td = temp_dir().join("tweetr-test").join("ops-token-read_profiles_broken");
+    let _ = fs::remove_dir_all(&td);
+    fs::create_dir_all(&td).unwrap();
+
+    let staging = AppTokens {
+        key: "hGf0MuCG4wTBGhDsxnBLnM1K3".to_string(),
+        secret: "xq2yPFTVkBqwKF9DUa4SqzYDDsuCPvRIFW1OGmMPoLZ7zSghFu".to_string(),
+        secret_command: None,
+    };
+    staging.write(&td.join(AppTokens::profile_file_name(Some("staging"))), None);
+    fs::File::create(td.join("app.toml")).unwrap().write_all(b"key = \"qzuqpwr101q4RtK9mDorI9ndm\"\n").unwrap();
+    fs::File::create(td.join("app-ignored.profile.toml")).unwrap();
+
+    let (profiles, errors) = AppTokens::read_profiles(&td, None).unwrap();
+    assert_eq!(profiles.len(), 1);
+    assert_eq!(profiles.get(&Some("staging".to_string())), Some(&staging));
+    assert_eq!(errors,
+               vec![Outcome::FileParsingFailed {
+                        desc: "application tokens",
+                        errors: vec!["app.toml: neither secret nor secret_command specified".to_string()],
+                    }]);
+}
+
+#[test]
+fn read_profiles_nonexistant_dir() {
+    let td = temp_dir().join("tweetr-test").join("ops-token-read_profiles_nonexistant_dir");
+    let _ = fs::remove_dir_all(&td);
+
+    match AppTokens::read_profiles(&td, None) {
+        Err(Outcome::FileParsingFailed { .. }) => (),
+        res => panic!("{:?}", res),
+    }
+}
@@ -0,0 +1,99 @@
+extern crate tweetr;
+
+use self::tweetr::ops::{User, UserGroup};
+use std::io::{Read, Write};
+use self::tweetr::Outcome;
+use std::fs::{self, File};
+use std::env::temp_dir;
+use std::path::PathBuf;
+
+
+#[test]
+fn none() {
+    let tf = scaffold("none", "");
+    User::write(vec![], &tf, None).unwrap();
+    assert_eq!(UserGroup::read(&tf).unwrap(), vec![]);
+}
+
+#[test]
+fn read() {
+    let tf = scaffold("read", GROUPS);
+    assert_eq!(UserGroup::read(&tf).unwrap(), groups());
+}
+
+#[test]
+fn kept_by_user_write() {
+    let tf = scaffold("kept_by_user_write", GROUPS);
+    User::write(vec![User::new("brand", 481, "481-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4", "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5")],
+                &tf,
+                None)
+        .unwrap();
+
+    assert_eq!(UserGroup::read(&tf).unwrap(), groups());
+    assert_eq!(User::read(&tf, None).unwrap().len(), 1);
+}
+
+#[test]
+fn named_like_user() {
+    let tf = scaffold("named_like_user", &format!("{}\n{}", USER, GROUPS.replace("user = []\n", "").replace("name = \"devs\"", "name = \"Brand\"")));
+    assert_eq!(UserGroup::read(&tf),
+               Err(Some(Outcome::FileParsingFailed {
+                   desc: "user groups",
+                   errors: vec!["group Brand is named the same as a user".to_string()],
+               })));
+}
+
+#[test]
+fn unreadable_not_overwritten() {
+    let contents = format!("{}\n[[group]]\nname = \"devs\"\nmembers = \"brand_dev\"\n", USER);
+    let tf = scaffold("unreadable_not_overwritten", &contents);
+
+    assert!(User::write(vec![], &tf, None).is_err());
+    let mut read = String::new();
+    File::open(&tf).unwrap().read_to_string(&mut read).unwrap();
+    assert_eq!(read, contents);
+}
+
+
+static USER: &'static str = r#"
+[[user]]
+name = "brand"
+id = 481
+access_token_key = "481-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4"
+access_token_secret = "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5"
+"#;
+
+static GROUPS: &'static str = r#"
+user = []
+
+[[group]]
+name = "brand-accounts"
+members = ["brand", "brand_support", "brand_dev"]
+stagger_minutes = 10
+
+[[group]]
+name = "devs"
+members = ["brand_dev"]
+"#;
+
+fn groups() -> Vec<UserGroup> {
+    vec![UserGroup {
+             name: "brand-accounts".to_string(),
+             members: vec!["brand".to_string(), "brand_support".to_string(), "brand_dev".to_string()],
+             stagger_minutes: Some(10),
+         },
+         UserGroup {
+             name: "devs".to_string(),
+             members: vec!["brand_dev".to_string()],
+             stagger_minutes: None,
+         }]
+}
+
+fn scaffold(name: &str, contents: &str) -> PathBuf {
+    let td = temp_dir().join("tweetr-test").join(format!("ops-user-group-{}", name));
+    fs::create_dir_all(&td).unwrap();
+
+    let tf = td.join("users.toml");
+    File::create(&tf).unwrap().write_all(contents.as_bytes()).unwrap();
+    tf
+}
@@ -0,0 +1,79 @@
+extern crate tweetr;
+
+use self::tweetr::ops::User;
+use self::tweetr::Outcome;
+use std::fs::{self, File};
+use std::env::temp_dir;
+use std::path::PathBuf;
+use std::io::Write;
+
+
+#[test]
+fn no_secret() {
+    let tf = scaffold("no_secret", "[[user]]\nname = \"brand\"\nid = 481\naccess_token_key = \"481-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4\"\n");
+    assert_eq!(User::read(&tf, None),
+               Err(Some(Outcome::FileParsingFailed {
+                   desc: "users",
+                   errors: vec!["user brand: neither access_token_secret nor secret_command specified".to_string()],
+               })));
+}
+
+#[test]
+fn suspension_without_time() {
+    let tf = scaffold("suspension_without_time",
+                      &format!("{}suspended = \"Invalid or expired token.\"\n\n{}time_suspended = \"2016-09-09T00:33:30+02:00\"\n",
+                               user("brand"),
+                               user("brand_dev")));
+    assert_eq!(User::read(&tf, None),
+               Err(Some(Outcome::FileParsingFailed {
+                   desc: "users",
+                   errors: vec!["user brand: suspended without time_suspended".to_string(),
+                                "user brand_dev: time_suspended without suspended".to_string()],
+               })));
+}
+
+#[test]
+fn invalid_suspension_time() {
+    let tf = scaffold("invalid_suspension_time",
+                      &format!("{}suspended = \"Invalid or expired token.\"\ntime_suspended = \"yesterday\"\n", user("brand")));
+    match User::read(&tf, None) {
+        Err(Some(Outcome::FileParsingFailed { errors, .. })) => assert!(errors[0].starts_with("user brand: time_suspended \"yesterday\" isn't a valid")),
+        res => panic!("{:?}", res),
+    }
+}
+
+#[test]
+fn invalid_app() {
+    let tf = scaffold("invalid_app", &format!("{}app = \"../../etc/staging\"\n", user("brand")));
+    assert_eq!(User::read(&tf, None),
+               Err(Some(Outcome::FileParsingFailed {
+                   desc: "users",
+                   errors: vec!["user brand: app \"../../etc/staging\" isn't a valid app profile name".to_string()],
+               })));
+}
+
+#[test]
+fn no_posts_per_day() {
+    let tf = scaffold("no_posts_per_day", &format!("{}max_posts_per_day = 0\n", user("brand")));
+    assert_eq!(User::read(&tf, None),
+               Err(Some(Outcome::FileParsingFailed {
+                   desc: "users",
+                   errors: vec!["user brand: max_posts_per_day is 0, remove it or the user instead".to_string()],
+               })));
+}
+
+
+fn user(name: &str) -> String {
+    format!("[[user]]\nname = \"{}\"\nid = 481\naccess_token_key = \"481-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4\"\n\
+             access_token_secret = \"GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5\"\n",
+            name)
+}
+
+fn scaffold(name: &str, contents: &str) -> PathBuf {
+    let td = temp_dir().join("tweetr-test").join(format!("ops-user-read-{}", name));
+    fs::create_dir_all(&td).unwrap();
+
+    let tf = td.join("users.toml");
+    File::create(&tf).unwrap().write_all(contents.as_bytes()).unwrap();
+    tf
+}
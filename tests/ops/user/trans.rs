@@ -1,6 +1,8 @@
 extern crate tweetr;
+extern crate chrono;
 
-use self::tweetr::ops::User;
+use self::tweetr::ops::{SecretKey, Suspension, User};
+use self::chrono::DateTime;
 use std::env::temp_dir;
 use std::fs;
 
@@ -13,37 +15,66 @@ fn empty_eq() {
 #[test]
 fn single_eq() {
     trans_scaffold("single_trans_eq",
-                   vec![User {
-                            name: "nabijaczleweli".to_string(),
-                            id: 481,
-                            access_token_key: "481-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4".to_string(),
-                            access_token_secret: "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5".to_string(),
-                        }]);
+                   vec![User::new("nabijaczleweli", 481, "481-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4", "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5")]);
 }
 
 #[test]
 fn multi_eq() {
     trans_scaffold("multi_trans_eq",
-                   vec![User {
-                            name: "nabijaczleweli".to_string(),
-                            id: 481,
-                            access_token_key: "481-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4".to_string(),
-                            access_token_secret: "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5".to_string(),
-                        },
+                   vec![User::new("nabijaczleweli", 481, "481-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4", "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5"),
                         User {
-                            name: "danerangLP".to_string(),
-                            id: 334776,
-                            access_token_key: "334776-WTRvsJI4DQgvzYwDYT8YYdEBxnpCQpQB2t4SrEK".to_string(),
-                            access_token_secret: "qbOzYrP9bwcOEGAUnuiPVfA7JqAAsWhrd8DcbwDH5RwTA".to_string(),
+                            app: Some("staging".to_string()),
+                            min_minutes_between_posts: Some(30),
+                            max_posts_per_day: Some(8),
+                            ..User::new("danerangLP", 334776, "334776-WTRvsJI4DQgvzYwDYT8YYdEBxnpCQpQB2t4SrEK", "qbOzYrP9bwcOEGAUnuiPVfA7JqAAsWhrd8DcbwDH5RwTA")
                         },
-                        User {
-                            name: "LinesFromNLSS".to_string(),
-                            id: 6695520,
-                            access_token_key: "6695520-KhiuVzAS41GS0V3hCBA7VFnHHNdwQpUDCaNfiOn".to_string(),
-                            access_token_secret: "3cx12ULmXYkhcnEiPXBbpoilLPdQOVd8KigUoPQmaw8f5".to_string(),
+                        User::new("LinesFromNLSS",
+                                  6695520,
+                                  "6695520-KhiuVzAS41GS0V3hCBA7VFnHHNdwQpUDCaNfiOn",
+                                  "3cx12ULmXYkhcnEiPXBbpoilLPdQOVd8KigUoPQmaw8f5")]);
+}
+
+#[test]
+fn suspended_eq() {
+    trans_scaffold("suspended_trans_eq",
+                   vec![User {
+                            suspended: Some(Suspension {
+                                reason: "Twitter error: #89: Invalid or expired token.".to_string(),
+                                time: DateTime::parse_from_rfc3339("2016-09-09T00:33:30+02:00").unwrap(),
+                            }),
+                            ..User::new("nabijaczleweli", 481, "481-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4", "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5")
+                        }]);
+}
+
+#[test]
+fn secret_command_eq() {
+    trans_scaffold("secret_command_trans_eq",
+                   vec![User {
+                            secret_command: Some("pass show tweetr/nabijaczleweli".to_string()),
+                            ..User::new("nabijaczleweli", 481, "481-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4", "")
                         }]);
 }
 
+#[test]
+fn encrypted_eq() {
+    let td = temp_dir().join("tweetr-test").join("ops-user-encrypted_trans_eq");
+    fs::create_dir_all(&td).unwrap();
+
+    let tf = td.join("users.toml");
+    let _ = fs::remove_file(&tf);
+
+    let users = vec![User::new("nabijaczleweli", 481, "481-FNlJkpZCE7a4Bbd7f1k65GtgaH7SmHlReWSESD4", "GVQDq88qLtJ45KR6u44A6AljW31JSSippjdipQg6gPYE5")];
+    let key = SecretKey::derive("hunter2", b"tweetr-test".to_vec(), 4);
+
+    User::write(users.clone(), &tf, Some(&key)).unwrap();
+    assert_eq!(User::read(&tf, Some(&key)).unwrap(), users);
+
+    let opaque_users = User::read(&tf, None).unwrap();
+    assert!(SecretKey::is_encrypted(&opaque_users[0].access_token_secret));
+    User::write(opaque_users, &tf, None).unwrap();
+    assert_eq!(User::read(&tf, Some(&key)).unwrap(), users);
+}
+
 
 fn trans_scaffold(name: &str, users: Vec<User>) {
     let td = temp_dir().join("tweetr-test").join(format!("ops-user-{}", name));
@@ -52,8 +83,8 @@ fn trans_scaffold(name: &str, users: Vec<User>) {
     let tf = td.join("users.toml");
     let _ = fs::remove_file(&tf);
 
-    User::write(users.clone(), &tf);
-    let read_users = User::read(&tf).unwrap();
+    User::write(users.clone(), &tf, None).unwrap();
+    let read_users = User::read(&tf, None).unwrap();
 
     assert_eq!(users, read_users);
 }
@@ -0,0 +1,35 @@
+extern crate tweetr;
+
+use self::tweetr::ops::verify_users;
+use self::tweetr::Outcome;
+
+
+#[test]
+fn all_ok() {
+    assert_eq!(verify_users::revoked_outcome(&vec![], 0), Outcome::NoError);
+}
+
+#[test]
+fn revoked() {
+    assert_eq!(verify_users::revoked_outcome(&vec!["nabijaczleweli".to_string()], 0),
+               Outcome::RequiredDataFromSubsystemNonexistant {
+                   subsys: "reauth-user",
+                   desc: "re-authorise users nabijaczleweli".to_string(),
+               });
+}
+
+#[test]
+fn failed() {
+    let out = verify_users::revoked_outcome(&vec![], 1);
+    assert_eq!(out, Outcome::ConfigurationProblems(1));
+    assert!(out.exit_value() != 0);
+}
+
+#[test]
+fn revoked_and_failed() {
+    assert_eq!(verify_users::revoked_outcome(&vec!["nabijaczleweli".to_string(), "danerangLP".to_string()], 3),
+               Outcome::RequiredDataFromSubsystemNonexistant {
+                   subsys: "reauth-user",
+                   desc: "re-authorise users nabijaczleweli, danerangLP".to_string(),
+               });
+}
@@ -1,4 +1,5 @@
 mod prompt_nonzero_len;
+mod prompt_suggesting;
 mod prompt_exact_len;
 mod prompt_multiline;
 mod prompt_any_len;
@@ -0,0 +1,56 @@
+extern crate tweetr;
+
+use self::tweetr::util::prompt_suggesting;
+use std::iter::FromIterator;
+use std::io::Cursor;
+
+
+#[test]
+fn no_tab() {
+    let mut out = Vec::new();
+    let result = prompt_suggesting(&mut Cursor::new(b"nabi\n"), &mut out, "User", &candidates()).unwrap();
+
+    assert_eq!(result, Some("nabi".to_string()));
+    assert_eq!(out, Vec::from_iter(b"User: ".iter().cloned()));
+}
+
+#[test]
+fn empty() {
+    let mut out = Vec::new();
+    let result = prompt_suggesting(&mut Cursor::new(b"\n"), &mut out, "User", &candidates()).unwrap();
+
+    assert_eq!(result, None);
+    assert_eq!(out, Vec::from_iter(b"User: ".iter().cloned()));
+}
+
+#[test]
+fn unique() {
+    let mut out = Vec::new();
+    let result = prompt_suggesting(&mut Cursor::new(b"DANE\t\n"), &mut out, "User", &candidates()).unwrap();
+
+    assert_eq!(result, Some("danerangLP".to_string()));
+    assert_eq!(out, Vec::from_iter(b"User: User: danerangLP\n".iter().cloned()));
+}
+
+#[test]
+fn ambiguous() {
+    let mut out = Vec::new();
+    let result = prompt_suggesting(&mut Cursor::new(b"nabi\t\nnabijaczleweli\n"), &mut out, "User", &candidates()).unwrap();
+
+    assert_eq!(result, Some("nabijaczleweli".to_string()));
+    assert_eq!(out, Vec::from_iter(b"User: nabijaczleweli  nabijaczleweli_bot\nUser: ".iter().cloned()));
+}
+
+#[test]
+fn nonexistant() {
+    let mut out = Vec::new();
+    let result = prompt_suggesting(&mut Cursor::new(b"xyz\t\n\n"), &mut out, "User", &candidates()).unwrap();
+
+    assert_eq!(result, None);
+    assert_eq!(out, Vec::from_iter(b"User: Nothing starts with \"xyz\".\nUser: ".iter().cloned()));
+}
+
+
+fn candidates() -> Vec<String> {
+    vec!["nabijaczleweli".to_string(), "nabijaczleweli_bot".to_string(), "danerangLP".to_string()]
+}